@@ -5,6 +5,7 @@ use std::time::{Duration, Instant};
 
 use criterion::{criterion_group, criterion_main, Criterion};
 
+use sais_rs::radix_sort::make_suffix_array;
 use sais_rs::sort;
 
 fn sort_benchmark(c: &mut Criterion) {
@@ -48,9 +49,34 @@ fn sort_benchmark(c: &mut Criterion) {
     }
 }
 
+/// SA-IS against the radix sort on texts the size of small reduced texts, the radix sort is no
+/// faster base case for the recursion at any of these sizes
+fn base_case_benchmark(c: &mut Criterion) {
+    let mut state = 7u64;
+    for &len in &[64, 256, 1024, 4096] {
+        let text = (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect::<Vec<_>>();
+        c.bench_function(&format!("base-sais-{}", len), |b| {
+            let mut indices = vec![0u32; len];
+            let mut types = vec![Default::default(); len];
+            let mut buckets = vec![0u32; 256];
+            b.iter(|| sort(&text, &mut indices, &mut types, &mut buckets))
+        });
+        c.bench_function(&format!("base-radix-{}", len), |b| {
+            b.iter(|| make_suffix_array::<u32>(&text))
+        });
+    }
+}
+
 criterion_group!(
     name = benches;
     config = Criterion::default().warm_up_time(Duration::from_secs(10)).measurement_time(Duration::from_secs(20));
-    targets = sort_benchmark
+    targets = sort_benchmark, base_case_benchmark
 );
 criterion_main!(benches);
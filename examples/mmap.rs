@@ -0,0 +1,27 @@
+//! Writes the suffix array of a file as little endian 64 bit words without reading the file into
+//! memory, only the suffix array itself is allocated:
+//! `cargo run --release --example mmap --features mmap <input> <output>`
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use sais_rs::mmap::{map_text, suffix_array};
+
+fn main() -> io::Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let (input, output) = match args.as_slice() {
+        [input, output] => (input, output),
+        _ => {
+            eprintln!("usage: mmap <input> <output>");
+            std::process::exit(2);
+        }
+    };
+    // Safety: the input is not modified while it is indexed
+    let text = unsafe { map_text(input)? };
+    let suffix_array = suffix_array::<u64>(&text)?;
+
+    let mut out = BufWriter::new(File::create(output)?);
+    for suffix in suffix_array {
+        out.write_all(&suffix.to_le_bytes())?;
+    }
+    out.flush()
+}
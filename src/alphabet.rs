@@ -0,0 +1,178 @@
+use crate::sorter::SuffixSorter;
+use crate::suffix_index::{AsIndex, SuffixIndex};
+
+/// The symbols occurring in a text mapped to dense ranks in their order.
+///
+/// Compacting a text keeps the order of its suffixes, the suffix array of the compacted text is
+/// the suffix array of the text and only needs as many buckets as there are symbols, see
+/// [`try_sort`](crate::try_sort). Results containing ranks are mapped back with
+/// [`Alphabet::restore`].
+#[derive(Debug, Clone)]
+pub struct Alphabet {
+    /// The rank of every symbol, meaningless for symbols not in the alphabet
+    ranks: [u8; 0x100],
+    symbols: Vec<u8>,
+}
+
+impl Alphabet {
+    /// The symbols occurring in `text`
+    pub fn new(text: &[u8]) -> Self {
+        let mut occurs = [false; 0x100];
+        for &c in text {
+            occurs[c as usize] = true;
+        }
+        Self::from_symbols((0..=u8::MAX).filter(|&c| occurs[c as usize]))
+    }
+
+    /// The alphabet of the given symbols
+    pub fn from_symbols<S: IntoIterator<Item = u8>>(symbols: S) -> Self {
+        let mut symbols = symbols.into_iter().collect::<Vec<_>>();
+        symbols.sort_unstable();
+        symbols.dedup();
+        let mut ranks = [0u8; 0x100];
+        for (rank, &c) in symbols.iter().enumerate() {
+            ranks[c as usize] = rank as u8;
+        }
+        Self { ranks, symbols }
+    }
+
+    /// The number of symbols
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// All symbols in increasing order, the index of a symbol is its rank
+    pub fn symbols(&self) -> &[u8] {
+        &self.symbols
+    }
+
+    pub fn contains(&self, c: u8) -> bool {
+        self.symbols.get(self.ranks[c as usize] as usize) == Some(&c)
+    }
+
+    /// The rank of `c`, `None` if it is not in the alphabet
+    pub fn rank(&self, c: u8) -> Option<u8> {
+        self.contains(c).then(|| self.ranks[c as usize])
+    }
+
+    pub fn symbol(&self, rank: u8) -> u8 {
+        self.symbols[rank as usize]
+    }
+
+    /// Replaces every symbol of `text` by its rank, all symbols must be in the alphabet
+    pub fn compact(&self, text: &[u8]) -> Vec<u8> {
+        text.iter()
+            .map(|&c| self.rank(c).expect("the symbol is not in the alphabet"))
+            .collect()
+    }
+
+    /// Replaces every rank by its symbol, the inverse of [`Alphabet::compact`]
+    pub fn restore(&self, ranks: &[u8]) -> Vec<u8> {
+        ranks.iter().map(|&rank| self.symbol(rank)).collect()
+    }
+}
+
+/// Replaces the characters of `text` by their ranks among the characters that occur, returns the
+/// ranks and the indices of the occurring characters in increasing order.
+///
+/// The ranks keep the order of the suffixes and are less than the length of the text, even for
+/// sparse alphabets like token ids in the millions. Characters up to [`COUNTING_MAX`] are ranked
+/// with a table, larger ones by sorting.
+pub fn ranks<C: AsIndex, R: SuffixIndex>(text: &[C]) -> (Vec<R>, Vec<usize>) {
+    let max = text.iter().map(AsIndex::as_index).max().unwrap_or(0);
+    if max <= COUNTING_MAX.max(text.len()) {
+        let mut table = vec![usize::MAX; max + 1];
+        for c in text {
+            table[c.as_index()] = 0;
+        }
+        let mut symbols = Vec::new();
+        for (c, rank) in table.iter_mut().enumerate() {
+            if *rank == 0 {
+                *rank = symbols.len();
+                symbols.push(c);
+            }
+        }
+        let ranks = text
+            .iter()
+            .map(|c| R::from_index(table[c.as_index()]))
+            .collect();
+        return (ranks, symbols);
+    }
+    let mut symbols = text.iter().map(AsIndex::as_index).collect::<Vec<_>>();
+    symbols.sort_unstable();
+    symbols.dedup();
+    let ranks = text
+        .iter()
+        .map(|c| R::from_index(symbols.binary_search(&c.as_index()).unwrap()))
+        .collect();
+    (ranks, symbols)
+}
+
+/// The largest character [`ranks`] ranks with a table regardless of the length of the text
+pub const COUNTING_MAX: usize = u16::MAX as usize;
+
+/// Sorts over the [`ranks`] of the characters with another backend, which then needs as many
+/// buckets as there are distinct characters instead of the largest character
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Compacted<S>(pub S);
+
+impl<S: SuffixSorter> SuffixSorter for Compacted<S> {
+    fn sort<C: AsIndex + Ord, I: SuffixIndex>(&self, text: &[C], suffix_array: &mut [I]) {
+        let (ranked, _) = ranks::<_, I>(text);
+        self.0.sort(&ranked, suffix_array);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bwt::bwt;
+    use crate::sais::{suffix_array, try_sort, Type};
+    use crate::sorter::Sais;
+
+    #[test]
+    fn test_alphabet() {
+        let text = b"mississippi";
+        let alphabet = Alphabet::new(text);
+        assert_eq!(alphabet.symbols(), b"imps");
+        assert_eq!(alphabet.rank(b'p'), Some(2));
+        assert_eq!(alphabet.rank(b'a'), None);
+        assert!(!alphabet.contains(0));
+        let compacted = alphabet.compact(text);
+        assert_eq!(alphabet.restore(&compacted), text);
+
+        let mut compacted_suffix_array = vec![0u32; text.len()];
+        let mut buckets = vec![0u32; alphabet.len()];
+        try_sort(
+            &compacted,
+            &mut compacted_suffix_array,
+            &mut vec![Type::L; text.len()],
+            &mut buckets,
+        )
+        .unwrap();
+        assert_eq!(compacted_suffix_array, suffix_array::<u32>(text));
+
+        let (transformed, primary) = bwt(&compacted);
+        assert_eq!((alphabet.restore(&transformed), primary), bwt(text));
+        assert!(Alphabet::new(b"").is_empty());
+    }
+
+    #[test]
+    fn test_compacted() {
+        let text = [7u64 << 40, 3, 7 << 40, 1 << 50, 3, 7 << 40];
+        let (ranked, symbols) = ranks::<_, u32>(&text);
+        assert_eq!(ranked, [1, 0, 1, 2, 0, 1]);
+        assert_eq!(symbols, [3, 7 << 40, 1 << 50]);
+        let (ranked, symbols) = ranks::<_, u16>(&[500u32, 3, 500, 60_000, 3]);
+        assert_eq!(ranked, [1, 0, 1, 2, 0]);
+        assert_eq!(symbols, [3, 500, 60_000]);
+        assert_eq!(
+            Compacted(Sais).suffix_array::<_, u32>(&text),
+            suffix_array::<u32>(&[1, 0, 1, 2, 0, 1])
+        );
+    }
+}
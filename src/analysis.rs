@@ -0,0 +1,1765 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::iter::repeat_n;
+use std::mem::{replace, swap};
+use std::ops::Range;
+
+use crate::fm::{BiInterval, BidirectionalFmIndex};
+use crate::generalized::GeneralizedSuffixArray;
+use crate::lce::LceIndex;
+use crate::lcp::lcp_intervals;
+use crate::rmq::{Rmq, SparseTable};
+use crate::sais::{inverse, suffix_array, suffix_array_with_lcp};
+use crate::suffix_index::{AsIndex, SuffixIndex};
+use crate::suffix_tree::SuffixTree;
+
+/// A passage occurring in two different documents that can not be extended in either direction.
+///
+/// Positions are `(document, offset)` pairs, the first document is the smaller one.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct SharedPassage {
+    pub first: (usize, usize),
+    pub second: (usize, usize),
+    pub len: usize,
+}
+
+/// Finds all passages of at least `min_len` characters shared by two documents of `index`.
+///
+/// These are the maximal repeats restricted to pairs of occurrences in different documents.
+/// Returns the passages sorted by their first and second positions.
+pub fn shared_passages<I: SuffixIndex>(
+    index: &GeneralizedSuffixArray<I>,
+    min_len: usize,
+) -> Vec<SharedPassage> {
+    let min_len = min_len.max(1);
+    let text = index.text();
+    let suffix_array = index.suffix_array();
+    let lcp = index.lcp_array();
+
+    let mut passages = Vec::new();
+    // Children of an lcp interval share exactly `lcp` characters, report pairs across children
+    fold_lcp_intervals(
+        &lcp,
+        |rank| vec![suffix_array[rank].as_index()],
+        |lcp, children| {
+            let mut positions = Vec::new();
+            for mut child in children {
+                if lcp >= min_len {
+                    for &left in &positions {
+                        let (left_document, left_offset) = index.document_position(left);
+                        for &right in &child {
+                            let (right_document, right_offset) = index.document_position(right);
+                            let left_maximal = left_offset == 0
+                                || right_offset == 0
+                                || text[left - 1] != text[right - 1];
+                            if left_document != right_document && left_maximal {
+                                let (first, second) = if left_document < right_document {
+                                    ((left_document, left_offset), (right_document, right_offset))
+                                } else {
+                                    ((right_document, right_offset), (left_document, left_offset))
+                                };
+                                passages.push(SharedPassage {
+                                    first,
+                                    second,
+                                    len: lcp,
+                                });
+                            }
+                        }
+                    }
+                }
+                if positions.len() < child.len() {
+                    swap(&mut positions, &mut child);
+                }
+                positions.extend(child);
+            }
+            positions
+        },
+    );
+
+    passages.sort_unstable();
+    passages
+}
+
+/// Returns the first longest substring occurring in at least `min_documents` documents of
+/// `index` as a range of [`GeneralizedSuffixArray::text`], `None` if there is none.
+///
+/// A window slides over the suffix array while it covers suffixes of `min_documents` documents,
+/// the common prefix of the window is the minimum of its lcp values.
+pub fn longest_common_substring<I: SuffixIndex>(
+    index: &GeneralizedSuffixArray<I>,
+    min_documents: usize,
+) -> Option<Range<usize>> {
+    assert!(min_documents > 0, "at least one document is required");
+    let suffix_array = index.suffix_array();
+    let documents = index.document_array();
+    let lcp = index.lcp_array();
+
+    let mut counts = vec![0usize; index.document_count()];
+    let mut distinct = 0;
+    // Indices of increasing lcp values in the window, excluding its first suffix
+    let mut minima = VecDeque::new();
+    let mut start = 0;
+    let mut longest: Option<Range<usize>> = None;
+    for (end, document) in documents.iter().enumerate() {
+        let document = document.as_index();
+        counts[document] += 1;
+        distinct += (counts[document] == 1) as usize;
+        if end > start {
+            while minima.back().is_some_and(|&back| lcp[back] >= lcp[end]) {
+                minima.pop_back();
+            }
+            minima.push_back(end);
+        }
+        while distinct >= min_documents {
+            let suffix = suffix_array[start].as_index();
+            let len = match minima.front() {
+                Some(&index) => lcp[index].as_index(),
+                None => index.suffix(suffix).len(),
+            };
+            if longest.as_ref().is_none_or(|longest| len > longest.len()) {
+                longest = Some(suffix..suffix + len);
+            }
+            let document = documents[start].as_index();
+            counts[document] -= 1;
+            distinct -= (counts[document] == 0) as usize;
+            start += 1;
+            if minima.front() == Some(&start) {
+                minima.pop_front();
+            }
+        }
+    }
+    longest.filter(|longest| !longest.is_empty())
+}
+
+/// Computes the longest overlap of every ordered pair of different documents of `index` with at
+/// least `min_len` characters, a proper suffix of the first document that is a proper prefix of
+/// the second one (Gusfield, Landau and Schieber, 1992).
+///
+/// Returns `(first, second, len)` triples sorted by document.
+pub fn suffix_prefix_overlaps<I: SuffixIndex>(
+    index: &GeneralizedSuffixArray<I>,
+    min_len: usize,
+) -> Vec<(usize, usize, usize)> {
+    let min_len = min_len.max(1);
+    let lcp = index.lcp_array();
+    let count = index.document_count();
+
+    // The suffixes of every document prefixing the current suffix, increasing in length
+    let mut suffixes: Vec<Vec<usize>> = vec![Vec::new(); count];
+    let mut open: Vec<(usize, usize)> = Vec::new();
+    // The documents with a prefixing suffix, `slots` has their index in `active`
+    let mut active: Vec<usize> = Vec::new();
+    let mut slots = vec![0; count];
+    let mut overlaps = Vec::new();
+    for (row, suffix) in index.suffix_array().iter().enumerate() {
+        while open
+            .last()
+            .is_some_and(|&(_, len)| len > lcp[row].as_index())
+        {
+            let (document, _) = open.pop().unwrap();
+            suffixes[document].pop();
+            if suffixes[document].is_empty() {
+                active.swap_remove(slots[document]);
+                if let Some(&moved) = active.get(slots[document]) {
+                    slots[moved] = slots[document];
+                }
+            }
+        }
+
+        let (document, offset) = index.document_position(suffix.as_index());
+        let len = index.document_range(document).len() - offset;
+        if offset == 0 {
+            for &first in active.iter().filter(|&&first| first != document) {
+                let longest = suffixes[first].iter().rev().find(|&&overlap| overlap < len);
+                if let Some(&overlap) = longest {
+                    overlaps.push((first, document, overlap));
+                }
+            }
+        } else if len >= min_len {
+            if suffixes[document].is_empty() {
+                slots[document] = active.len();
+                active.push(document);
+            }
+            suffixes[document].push(len);
+            open.push((document, len));
+        }
+    }
+    overlaps.sort_unstable();
+    overlaps
+}
+
+/// Finds all maximal exact matches of `a` and `b` with a length of at least `min_len`.
+///
+/// Returns `(a_position, b_position, len)` triples sorted by position in `a`.
+pub fn maximal_exact_matches(a: &[u8], b: &[u8], min_len: usize) -> Vec<(usize, usize, usize)> {
+    let index = GeneralizedSuffixArray::<usize>::new(&[a, b]);
+    shared_passages(&index, min_len)
+        .into_iter()
+        .map(|passage| (passage.first.1, passage.second.1, passage.len))
+        .collect()
+}
+
+/// Occurrence counts per document in ascending document order
+fn merge_counts(mut left: Vec<(usize, u64)>, mut right: Vec<(usize, u64)>) -> Vec<(usize, u64)> {
+    if left.len() < right.len() {
+        swap(&mut left, &mut right);
+    }
+    for (document, count) in right {
+        match left.binary_search_by_key(&document, |&(document, _)| document) {
+            Ok(i) => left[i].1 += count,
+            Err(i) => left.insert(i, (document, count)),
+        }
+    }
+    left
+}
+
+/// Computes the substring kernel of all pairs of documents of `index`, the number of pairs of
+/// equal substrings with at most `max_len` characters. Use `usize::MAX` to count all substrings.
+pub fn substring_kernel<I: SuffixIndex>(
+    index: &GeneralizedSuffixArray<I>,
+    max_len: usize,
+) -> Vec<Vec<u64>> {
+    let documents = index.document_count();
+    let suffix_array = index.suffix_array();
+    let lcp = index.lcp_array();
+    let mut kernel = vec![vec![0u64; documents]; documents];
+
+    // The substrings with lengths in (parent lcp, lcp] share the occurrences of an lcp interval,
+    // the length of a suffix takes the place of the lcp for a single suffix
+    let mut add = |counts: &[(usize, u64)], lcp: usize, parent_lcp: usize| {
+        let lengths = (lcp.min(max_len) - parent_lcp.min(max_len)) as u64;
+        if lengths == 0 {
+            return;
+        }
+        for &(a, a_count) in counts {
+            for &(b, b_count) in counts {
+                kernel[a][b] += lengths * a_count * b_count;
+            }
+        }
+    };
+    fold_lcp_intervals(
+        &lcp,
+        |rank| {
+            let leaf = suffix_array[rank].as_index();
+            let document = index.document_position(leaf).0;
+            (index.suffix(leaf).len(), vec![(document, 1)])
+        },
+        |lcp, children| {
+            let mut counts = Vec::new();
+            for (child_lcp, child) in children {
+                add(&child, child_lcp, lcp);
+                counts = merge_counts(counts, child);
+            }
+            (lcp, counts)
+        },
+    );
+    kernel
+}
+
+/// Normalizes a kernel matrix to `k(a, b) / sqrt(k(a, a) * k(b, b))`
+pub fn normalize_kernel(kernel: &[Vec<u64>]) -> Vec<Vec<f64>> {
+    kernel
+        .iter()
+        .enumerate()
+        .map(|(a, row)| {
+            row.iter()
+                .enumerate()
+                .map(|(b, &value)| {
+                    let norm = (kernel[a][a] as f64 * kernel[b][b] as f64).sqrt();
+                    if norm == 0.0 {
+                        0.0
+                    } else {
+                        value as f64 / norm
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// A super-maximal exact match of a query
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Smem {
+    /// The matched part of the query
+    pub query: Range<usize>,
+    /// The interval of all occurrences
+    pub interval: BiInterval,
+}
+
+/// Finds all super-maximal exact matches of `query` in `index`, maximal exact matches that are
+/// not contained in another one, in the order of their start. Documents can be concatenated with
+/// a separator that does not occur in queries.
+///
+/// Matches shorter than `min_len` or with more than `max_occurrences` occurrences are skipped.
+pub fn super_maximal_exact_matches<I: SuffixIndex>(
+    index: &BidirectionalFmIndex<I>,
+    query: &[u8],
+    min_len: usize,
+    max_occurrences: usize,
+) -> Vec<Smem> {
+    let mut smems = Vec::new();
+    let mut position = 0;
+    while position < query.len() {
+        position = smems_covering(index, query, position, &mut smems);
+    }
+    smems.retain(|smem| {
+        smem.query.len() >= min_len && index.count(smem.interval) <= max_occurrences
+    });
+    smems
+}
+
+/// Appends the super-maximal exact matches containing `position` in the order of their start and
+/// returns the end of the longest match starting at `position`, every match starting later and
+/// ending before it is contained in that one
+fn smems_covering<I: SuffixIndex>(
+    index: &BidirectionalFmIndex<I>,
+    query: &[u8],
+    position: usize,
+    smems: &mut Vec<Smem>,
+) -> usize {
+    // The intervals of the matches starting at `position` whose extension to the right has fewer
+    // occurrences, the longest first
+    let mut matches = Vec::new();
+    let mut interval = index.extend_right(index.interval(), query[position]);
+    if interval.is_empty() {
+        return position + 1;
+    }
+    let mut end = position + 1;
+    while end < query.len() {
+        let extended = index.extend_right(interval, query[end]);
+        if extended.len != interval.len {
+            matches.push((interval, end));
+        }
+        if extended.is_empty() {
+            break;
+        }
+        interval = extended;
+        end += 1;
+    }
+    matches.push((interval, end));
+    matches.reverse();
+
+    // Extends all matches to the left at once, a match is super-maximal if it is the longest one
+    // that can not be extended and not contained in the last one found
+    let first = smems.len();
+    let mut start = position;
+    while !matches.is_empty() {
+        let mut extended_matches = Vec::<(BiInterval, usize)>::new();
+        for (interval, end) in matches {
+            let extended = match start.checked_sub(1) {
+                Some(previous) => index.extend_left(interval, query[previous]),
+                None => BiInterval::default(),
+            };
+            if extended.is_empty() {
+                let contained = smems[first..]
+                    .last()
+                    .is_some_and(|last| start >= last.query.start);
+                if extended_matches.is_empty() && !contained {
+                    smems.push(Smem {
+                        query: start..end,
+                        interval,
+                    });
+                }
+            } else if extended_matches
+                .last()
+                .is_none_or(|(last, _)| last.len != extended.len)
+            {
+                extended_matches.push((extended, end));
+            }
+        }
+        matches = extended_matches;
+        start = start.saturating_sub(1);
+    }
+    smems[first..].reverse();
+    end
+}
+
+/// Folds the lcp intervals of `lcp` bottom up, `node` gets the lcp of an interval and the values
+/// of its children in suffix array order, `leaf` the value of a single suffix by its index in the
+/// suffix array. Returns the value of the root interval.
+fn fold_lcp_intervals<I: SuffixIndex, T>(
+    lcp: &[I],
+    mut leaf: impl FnMut(usize) -> T,
+    mut node: impl FnMut(usize, Vec<T>) -> T,
+) -> Option<T> {
+    // The intervals whose parent is not closed yet in suffix array order
+    let mut closed: Vec<(Range<usize>, T)> = Vec::new();
+    for (interval_lcp, begin, end) in lcp_intervals(lcp) {
+        let first = closed.partition_point(|(interval, _)| interval.start < begin);
+        let mut children = Vec::new();
+        let mut index = begin;
+        for (interval, value) in closed.split_off(first) {
+            children.extend((index..interval.start).map(&mut leaf));
+            children.push(value);
+            index = interval.end;
+        }
+        children.extend((index..end).map(&mut leaf));
+        closed.push((begin..end, node(interval_lcp, children)));
+    }
+    closed.pop().map(|(_, value)| value)
+}
+
+/// A repeat that can neither be extended to the left nor to the right without losing occurrences
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MaximalRepeat {
+    pub len: usize,
+    /// The positions of all occurrences in ascending order
+    pub positions: Vec<usize>,
+}
+
+/// Returns all maximal repeats of `text` with at least `min_len` characters and `min_occurrences`
+/// occurrences
+pub fn maximal_repeats(text: &[u8], min_len: usize, min_occurrences: usize) -> Vec<MaximalRepeat> {
+    let (suffix_array, lcp) = suffix_array_with_lcp::<usize>(text);
+
+    let mut repeats = Vec::new();
+    // Every lcp interval is right maximal, it is left maximal if its preceding characters differ
+    for (len, begin, end) in lcp_intervals(&lcp) {
+        if len == 0 || len < min_len || end - begin < min_occurrences.max(2) {
+            continue;
+        }
+        let suffixes = &suffix_array[begin..end];
+        let previous = |suffix: usize| suffix.checked_sub(1).map(|i| text[i]);
+        let first = previous(suffixes[0]);
+        let left_maximal =
+            first.is_none() || suffixes.iter().any(|&suffix| previous(suffix) != first);
+        if left_maximal {
+            let mut positions = suffixes.to_vec();
+            positions.sort_unstable();
+            repeats.push(MaximalRepeat { len, positions });
+        }
+    }
+    repeats
+}
+
+/// Returns all supermaximal repeats of `text`, maximal repeats that do not occur in another
+/// maximal repeat, with at least `min_len` characters and `min_occurrences` occurrences
+pub fn supermaximal_repeats(
+    text: &[u8],
+    min_len: usize,
+    min_occurrences: usize,
+) -> Vec<MaximalRepeat> {
+    let (suffix_array, lcp) = suffix_array_with_lcp::<usize>(text);
+
+    let mut repeats = Vec::new();
+    // Supermaximal repeats are the lcp intervals without child intervals whose preceding
+    // characters are pairwise distinct
+    'intervals: for (len, begin, end) in lcp_intervals(&lcp) {
+        if len == 0 || len < min_len || end - begin < min_occurrences.max(2) {
+            continue;
+        }
+        if lcp[begin + 1..end].iter().any(|&lcp| lcp != len) {
+            continue;
+        }
+        let mut seen = [false; 0x100];
+        let mut positions = suffix_array[begin..end].to_vec();
+        for &position in &positions {
+            if let Some(previous) = position.checked_sub(1) {
+                if replace(&mut seen[text[previous] as usize], true) {
+                    continue 'intervals;
+                }
+            }
+        }
+        positions.sort_unstable();
+        repeats.push(MaximalRepeat { len, positions });
+    }
+    repeats
+}
+
+/// An occurrence of a duplicated block, all blocks of a group have the same content
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct DuplicateBlock {
+    pub file: usize,
+    pub offset: usize,
+    pub len: usize,
+    pub group: usize,
+}
+
+/// Finds blocks of at least `min_len` bytes occurring at least twice in the files of `index`.
+///
+/// Groups are maximal repeats taken longest first, occurrences overlapping an already reported
+/// block are dropped, so every byte belongs to at most one block. Storing every group once saves
+/// `(blocks - 1) * len` bytes per group. Blocks are sorted by file and offset, group ids are
+/// assigned in decreasing length order.
+pub fn duplicate_blocks<I: SuffixIndex>(
+    index: &GeneralizedSuffixArray<I>,
+    min_len: usize,
+) -> Vec<DuplicateBlock> {
+    let text = index.text();
+    let suffix_array = index.suffix_array();
+    let lcp = index.lcp_array();
+
+    let mut groups = Vec::new();
+    for (len, begin, end) in lcp_intervals(&lcp) {
+        if len == 0 || len < min_len || end - begin < 2 {
+            continue;
+        }
+        let previous = |suffix: usize| match index.document_position(suffix) {
+            (_, 0) => None,
+            _ => Some(text[suffix - 1]),
+        };
+        let mut positions = suffix_array[begin..end]
+            .iter()
+            .map(AsIndex::as_index)
+            .collect::<Vec<_>>();
+        let first = previous(positions[0]);
+        if first.is_none() || positions.iter().any(|&suffix| previous(suffix) != first) {
+            positions.sort_unstable();
+            groups.push((len, positions));
+        }
+    }
+    groups.sort_unstable_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+    let mut covered = vec![false; text.len()];
+    let mut blocks = Vec::new();
+    for (len, positions) in groups {
+        let mut kept = Vec::new();
+        for position in positions {
+            let overlaps_kept = kept.last().is_some_and(|&last| position < last + len);
+            if !overlaps_kept && !covered[position..position + len].contains(&true) {
+                kept.push(position);
+            }
+        }
+        if kept.len() < 2 {
+            continue;
+        }
+        let group = blocks
+            .last()
+            .map_or(0, |block: &DuplicateBlock| block.group + 1);
+        for position in kept {
+            covered[position..position + len].fill(true);
+            let (file, offset) = index.document_position(position);
+            blocks.push(DuplicateBlock {
+                file,
+                offset,
+                len,
+                group,
+            });
+        }
+    }
+    blocks.sort_unstable();
+    blocks
+}
+
+/// Longest common extension queries of two suffixes of the documents, extensions end at
+/// document ends
+struct Lce<R = SparseTable<usize>> {
+    index: LceIndex<usize, R>,
+    /// The end of the document of every position
+    ends: Vec<usize>,
+}
+
+impl<R: Rmq<usize>> Lce<R> {
+    fn new<D: AsRef<[u8]>>(documents: &[D]) -> Self {
+        let index = GeneralizedSuffixArray::<usize>::new(documents);
+        let ends = (0..index.document_count())
+            .flat_map(|document| {
+                let range = index.document_range(document);
+                repeat_n(range.end, range.len())
+            })
+            .collect();
+        Self {
+            index: LceIndex::from_parts(index.suffix_array(), index.lcp_array()),
+            ends,
+        }
+    }
+
+    fn lce(&self, i: usize, j: usize) -> usize {
+        if i == j {
+            return self.ends[i] - i;
+        }
+        self.index.lce(i, j)
+    }
+}
+
+/// A maximal repetition `text[start..start + len]` with smallest period `period` and
+/// `len >= 2 * period`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Run {
+    pub start: usize,
+    pub len: usize,
+    pub period: usize,
+}
+
+impl Run {
+    pub fn exponent(&self) -> f64 {
+        self.len as f64 / self.period as f64
+    }
+}
+
+/// Computes the Lyndon array of a text from the inverse of its suffix array, `lyndon[i]` is the
+/// length of the longest Lyndon word starting at `i`
+pub fn lyndon_array_from_inverse<I: SuffixIndex>(rank: &[I]) -> Vec<usize> {
+    // The longest Lyndon word starting at i ends at the next smaller suffix
+    let mut lyndon = vec![0; rank.len()];
+    let mut stack: Vec<usize> = Vec::new();
+    for i in (0..rank.len()).rev() {
+        while stack.last().is_some_and(|&j| rank[j] > rank[i]) {
+            stack.pop();
+        }
+        lyndon[i] = stack.last().copied().unwrap_or(rank.len()) - i;
+        stack.push(i);
+    }
+    lyndon
+}
+
+/// Computes the Lyndon array of `text`, the length of the longest Lyndon word starting at every
+/// position
+pub fn lyndon_array(text: &[u8]) -> Vec<usize> {
+    lyndon_array_from_inverse(&inverse(&suffix_array::<usize>(text)))
+}
+
+/// Computes the Lyndon factorization of a text from the inverse of its suffix array.
+///
+/// The factors start at the suffixes smaller than all suffixes starting before them.
+pub fn lyndon_factorization_from_inverse<I: SuffixIndex>(rank: &[I]) -> Vec<Range<usize>> {
+    let mut factors: Vec<Range<usize>> = Vec::new();
+    let mut smallest = None;
+    for (position, &suffix_rank) in rank.iter().enumerate() {
+        if smallest.is_none_or(|smallest| suffix_rank < smallest) {
+            if let Some(last) = factors.last_mut() {
+                last.end = position;
+            }
+            factors.push(position..rank.len());
+            smallest = Some(suffix_rank);
+        }
+    }
+    factors
+}
+
+/// Computes the Lyndon factorization of `text` with Duval's algorithm, the unique factorization
+/// into non-increasing Lyndon words
+pub fn lyndon_factorization(text: &[u8]) -> Vec<Range<usize>> {
+    let mut factors = Vec::new();
+    let mut start = 0;
+    while start < text.len() {
+        let mut i = start;
+        let mut j = start + 1;
+        while j < text.len() && text[i] <= text[j] {
+            if text[i] < text[j] {
+                i = start;
+            } else {
+                i += 1;
+            }
+            j += 1;
+        }
+        let len = j - i;
+        while start <= i {
+            factors.push(start..start + len);
+            start += len;
+        }
+    }
+    factors
+}
+
+/// Returns all runs (maximal repetitions) of `text` sorted by start position.
+///
+/// Every run has a Lyndon root that is the longest Lyndon word starting at its position for one
+/// of the two alphabet orders (Bannai et al.), these candidates are extended with lce queries.
+pub fn runs(text: &[u8]) -> Vec<Run> {
+    let forward = LceIndex::<usize>::new(text);
+    let reversed = text.iter().rev().copied().collect::<Vec<_>>();
+    let backward = LceIndex::<usize>::new(&reversed);
+    let n = text.len();
+
+    let mut runs = Vec::new();
+    for inverted in [false, true] {
+        let rank = if inverted {
+            let inverted_text = text.iter().map(|&c| !c).collect::<Vec<_>>();
+            inverse(&suffix_array::<usize>(&inverted_text))
+        } else {
+            forward.inverse().to_vec()
+        };
+        for (i, period) in lyndon_array_from_inverse(&rank).into_iter().enumerate() {
+            let j = i + period;
+            if j >= n {
+                continue;
+            }
+            let right = forward.lce(i, j);
+            let left = if i == 0 {
+                0
+            } else {
+                backward.lce(n - i, n - j)
+            };
+            if left + right >= period {
+                runs.push(Run {
+                    start: i - left,
+                    len: j + right - (i - left),
+                    period,
+                });
+            }
+        }
+    }
+    runs.sort_unstable();
+    runs.dedup();
+    runs
+}
+
+/// Longest palindrome queries for every center of a text.
+///
+/// Centers are numbered `0..=2 * len`, the palindrome `text[start..end]` has center `start + end`.
+/// Odd centers lie on a character, even centers between two characters.
+pub struct Palindromes<R = SparseTable<usize>> {
+    len: usize,
+    /// Over the text and its reverse
+    lce: Lce<R>,
+}
+
+impl Palindromes {
+    pub fn new(text: &[u8]) -> Self {
+        Self::with_backend(text)
+    }
+}
+
+impl<R: Rmq<usize>> Palindromes<R> {
+    /// Builds the lce queries with `R` as the range minimum backend
+    pub fn with_backend(text: &[u8]) -> Self {
+        let reversed = text.iter().rev().copied().collect::<Vec<_>>();
+        Self {
+            len: text.len(),
+            lce: Lce::new(&[text, &reversed]),
+        }
+    }
+
+    /// The longest palindrome with center `center`
+    pub fn longest_at(&self, center: usize) -> Range<usize> {
+        assert!(center <= 2 * self.len);
+        // Compare the text after the center with the reverse of the text before it
+        let (before, after) = (center / 2, center.div_ceil(2));
+        let radius = if before == 0 || after == self.len {
+            0
+        } else {
+            self.lce.lce(after, self.len + self.len - before)
+        };
+        before - radius..after + radius
+    }
+
+    /// Whether `text[range]` is a palindrome, the empty range is one
+    pub fn is_palindrome(&self, range: Range<usize>) -> bool {
+        assert!(range.start <= range.end && range.end <= self.len);
+        self.longest_at(range.start + range.end).len() >= range.len()
+    }
+}
+
+/// Returns the first longest palindromic substring of `text`
+pub fn longest_palindrome(text: &[u8]) -> Range<usize> {
+    let palindromes = Palindromes::new(text);
+    (0..=2 * text.len())
+        .map(|center| palindromes.longest_at(center))
+        .fold(0..0, |longest, palindrome| {
+            if palindrome.len() > longest.len() {
+                palindrome
+            } else {
+                longest
+            }
+        })
+}
+
+/// Returns the first occurrence of every distinct non-empty palindrome of `text` ordered by end.
+///
+/// Every position ends at most one new palindrome, the longest palindromic suffix of the prefix
+/// ending there, which is new if it has no previous occurrence.
+pub fn distinct_palindromes(text: &[u8]) -> Vec<Range<usize>> {
+    let palindromes = Palindromes::new(text);
+    let maximal = (0..=2 * text.len())
+        .map(|center| palindromes.longest_at(center))
+        .collect::<Vec<_>>();
+    let (lpf, _) = lpf_array(text);
+
+    let mut distinct = Vec::new();
+    // The longest palindromic suffix of `text[..end]` has the smallest center reaching `end`
+    let mut center = 0;
+    for end in 1..=text.len() {
+        while maximal[center].end < end {
+            center += 1;
+        }
+        let start = center - end;
+        if lpf[start] < end - start {
+            distinct.push(start..end);
+        }
+    }
+    distinct
+}
+
+/// Whether `text` is rich, containing the maximal number of `len` distinct non-empty palindromes
+pub fn is_rich(text: &[u8]) -> bool {
+    distinct_palindromes(text).len() == text.len()
+}
+
+/// A LZ77 factor: copy `len` characters from `position` followed by `next`.
+///
+/// The source may overlap the factor itself. In [`lz77_factorize`] only the last factor can lack a
+/// next character, in [`lz_factorize`] factors are either copies or single characters.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Factor {
+    pub position: usize,
+    pub len: usize,
+    pub next: Option<u8>,
+}
+
+/// For every suffix, the nearest smaller text positions before and after it in the suffix array
+fn previous_and_next_smaller(suffix_array: &[usize]) -> (Vec<Option<usize>>, Vec<Option<usize>>) {
+    let mut previous = vec![None; suffix_array.len()];
+    let mut next = vec![None; suffix_array.len()];
+    let mut stack: Vec<usize> = Vec::new();
+    for &suffix in suffix_array {
+        while let Some(&top) = stack.last() {
+            if top < suffix {
+                break;
+            }
+            next[top] = Some(suffix);
+            stack.pop();
+        }
+        previous[suffix] = stack.last().copied();
+        stack.push(suffix);
+    }
+    (previous, next)
+}
+
+/// Computes the LZ77 factorization of `text` with unbounded window.
+///
+/// Every factor refers to the longest previous occurrence, which is found among the nearest
+/// smaller text positions in suffix array order.
+pub fn lz77_factorize(text: &[u8]) -> Vec<Factor> {
+    let (previous, next) = previous_and_next_smaller(&suffix_array::<usize>(text));
+    let extension = |source: Option<usize>, position: usize| {
+        source.map_or((0, 0), |source| {
+            let len = text[source..]
+                .iter()
+                .zip(&text[position..])
+                .take_while(|(a, b)| a == b)
+                .count();
+            (len, source)
+        })
+    };
+
+    let mut factors = Vec::new();
+    let mut position = 0;
+    while position < text.len() {
+        let (len, source) =
+            extension(previous[position], position).max(extension(next[position], position));
+        factors.push(Factor {
+            position: if len == 0 { 0 } else { source },
+            len,
+            next: text.get(position + len).copied(),
+        });
+        position += len + 1;
+    }
+    factors
+}
+
+/// Computes the longest previous factor array and the previous occurrences of `text`.
+///
+/// `lpf[i]` is the length of the longest prefix of suffix `i` that also starts at a position
+/// `prev_occ[i] < i`, `prev_occ[i]` is `None` if `lpf[i] == 0`.
+pub fn lpf_array(text: &[u8]) -> (Vec<usize>, Vec<Option<usize>>) {
+    let (suffix_array, lcp) = suffix_array_with_lcp::<usize>(text);
+
+    let mut lpf = vec![0; text.len()];
+    let mut prev_occ = vec![None; text.len()];
+    let mut update = |position: usize, source: usize, len: usize| {
+        if len > lpf[position] {
+            lpf[position] = len;
+            prev_occ[position] = Some(source);
+        }
+    };
+
+    // Stack of suffixes with increasing positions in suffix array order. Every entry stores the
+    // lcp with the entry above it, the top stores the lcp with the current suffix.
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    for (index, &suffix) in suffix_array.iter().enumerate() {
+        if let Some(top) = stack.last_mut() {
+            top.1 = top.1.min(lcp[index]);
+        }
+        while let Some(&(top, top_lcp)) = stack.last() {
+            if top < suffix {
+                break;
+            }
+            stack.pop();
+            // `suffix` is the next smaller position of `top`
+            update(top, suffix, top_lcp);
+            if let Some(next) = stack.last_mut() {
+                next.1 = next.1.min(top_lcp);
+            }
+        }
+        if let Some(&(previous, previous_lcp)) = stack.last() {
+            update(suffix, previous, previous_lcp);
+        }
+        stack.push((suffix, usize::MAX));
+    }
+    (lpf, prev_occ)
+}
+
+/// Computes the Lempel-Ziv factorization of `text` from the longest previous factor array, every
+/// factor is the longest previous factor or a character without previous occurrence
+pub fn lz_factorize(text: &[u8]) -> Vec<Factor> {
+    let (lpf, prev_occ) = lpf_array(text);
+    let mut factors = Vec::new();
+    let mut position = 0;
+    while position < text.len() {
+        let factor = match prev_occ[position] {
+            Some(source) => Factor {
+                position: source,
+                len: lpf[position],
+                next: None,
+            },
+            None => Factor {
+                position: 0,
+                len: 0,
+                next: Some(text[position]),
+            },
+        };
+        position += factor.len.max(1);
+        factors.push(factor);
+    }
+    factors
+}
+
+/// Reverses [`lz77_factorize`] and [`lz_factorize`]
+pub fn lz77_decode(factors: &[Factor]) -> Vec<u8> {
+    let mut text = Vec::new();
+    for factor in factors {
+        for i in 0..factor.len {
+            text.push(text[factor.position + i]);
+        }
+        text.extend(factor.next);
+    }
+    text
+}
+
+/// Counts the distinct non-empty substrings of `text`, `n(n+1)/2` minus the sum of the lcp array
+pub fn count_distinct_substrings(text: &[u8]) -> u128 {
+    let n = text.len() as u128;
+    let repeated = suffix_array_with_lcp::<usize>(text)
+        .1
+        .into_iter()
+        .map(|lcp| lcp as u128)
+        .sum::<u128>();
+    n * (n + 1) / 2 - repeated
+}
+
+/// Counts the distinct substrings of every length up to `max_len`, `profile[len - 1]` is the
+/// number of distinct substrings of length `len`
+pub fn distinct_substring_profile(text: &[u8], max_len: usize) -> Vec<u64> {
+    let (suffix_array, lcp) = suffix_array_with_lcp::<usize>(text);
+
+    // Every suffix adds the substrings longer than its lcp with its predecessor
+    let mut differences = vec![0i64; max_len + 1];
+    for (&suffix, &lcp) in suffix_array.iter().zip(&lcp) {
+        let longest = (text.len() - suffix).min(max_len);
+        if lcp < longest {
+            differences[lcp] += 1;
+            differences[longest] -= 1;
+        }
+    }
+
+    let mut count = 0;
+    differences[..max_len]
+        .iter()
+        .map(|difference| {
+            count += difference;
+            count as u64
+        })
+        .collect()
+}
+
+/// Computes the substring complexity `δ` of `text`, the maximum over all lengths `k` of the number
+/// of distinct substrings of length `k` divided by `k`, a lower bound of compressed sizes
+pub fn substring_complexity(text: &[u8]) -> f64 {
+    distinct_substring_profile(text, text.len())
+        .into_iter()
+        .enumerate()
+        .map(|(len, count)| count as f64 / (len + 1) as f64)
+        .fold(0.0, f64::max)
+}
+
+/// Calls `f` with every distinct k-mer of `text` and its number of occurrences in lexicographic
+/// order
+pub fn for_each_kmer<'a, F: FnMut(&'a [u8], usize)>(text: &'a [u8], k: usize, mut f: F) {
+    assert_ne!(k, 0);
+    let (suffix_array, lcp) = suffix_array_with_lcp::<usize>(text);
+
+    let mut count = 0;
+    for (index, &suffix) in suffix_array.iter().enumerate() {
+        if suffix + k > text.len() {
+            continue;
+        }
+        count += 1;
+        if lcp.get(index + 1).is_none_or(|&lcp| lcp < k) {
+            f(&text[suffix..suffix + k], count);
+            count = 0;
+        }
+    }
+}
+
+/// Returns every distinct k-mer of `text` with its number of occurrences in lexicographic order
+pub fn kmer_counts(text: &[u8], k: usize) -> Vec<(&[u8], usize)> {
+    let mut counts = Vec::new();
+    for_each_kmer(text, k, |kmer, count| counts.push((kmer, count)));
+    counts
+}
+
+/// Returns the minimal absent words of `text` in lexicographic order, the words not occurring in
+/// `text` whose longest proper prefix and suffix both occur.
+///
+/// Only characters of `text` are considered. A minimal absent word `a u b` has a suffix tree node
+/// `u` whose children start with `b` and whose occurrences are preceded by `a` (Barton et al.).
+pub fn minimal_absent_words(text: &[u8]) -> Vec<Vec<u8>> {
+    let tree = SuffixTree::<usize>::new(text);
+    // The characters preceding the occurrences of every node
+    let mut left = vec![[0u64; 4]; tree.len()];
+    let insert = |set: &mut [u64; 4], c: u8| set[c as usize / 64] |= 1 << (c % 64);
+    let contains = |set: &[u64; 4], c: u8| set[c as usize / 64] & (1 << (c % 64)) != 0;
+
+    let mut words = Vec::new();
+    for node in tree.nodes() {
+        if tree.is_leaf(node) {
+            if let Some(c) = tree.suffix(node).checked_sub(1).map(|i| text[i]) {
+                insert(&mut left[node], c);
+            }
+            continue;
+        }
+        let depth = tree.depth(node);
+        for child in tree.children(node) {
+            let set = left[child];
+            for (word, set) in left[node].iter_mut().zip(set) {
+                *word |= set;
+            }
+        }
+        // The empty word also occurs after the last character
+        if let (true, Some(&c)) = (node == tree.root(), text.last()) {
+            insert(&mut left[node], c);
+        }
+        let label = tree.label(node);
+        for child in tree.children(node) {
+            // A leaf of the same depth ends with the node
+            let Some(&b) = tree.label(child).get(depth) else {
+                continue;
+            };
+            for a in 0..=u8::MAX {
+                if contains(&left[node], a) && !contains(&left[child], a) {
+                    words.push([&[a], label, &[b]].concat());
+                }
+            }
+        }
+    }
+    words.sort_unstable();
+    words
+}
+
+/// Returns the `n` most frequent k-mers of `text` with their number of occurrences, by descending
+/// count and then lexicographically
+pub fn top_kmers(text: &[u8], k: usize, n: usize) -> Vec<(&[u8], usize)> {
+    // The least frequent k-mer kept is at the top
+    let mut top = BinaryHeap::with_capacity(n + 1);
+    for_each_kmer(text, k, |kmer, count| {
+        top.push((Reverse(count), kmer));
+        if top.len() > n {
+            top.pop();
+        }
+    });
+    top.into_sorted_vec()
+        .into_iter()
+        .map(|(Reverse(count), kmer)| (kmer, count))
+        .collect()
+}
+
+/// Two occurrences of a repeated substring
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Repeat {
+    /// The positions of the occurrences in ascending order
+    pub positions: [usize; 2],
+    pub len: usize,
+}
+
+/// Returns the longest substring of `text` occurring at least twice, the occurrences may overlap
+pub fn longest_repeated_substring(text: &[u8]) -> Option<Repeat> {
+    let (suffix_array, lcp) = suffix_array_with_lcp::<usize>(text);
+
+    let mut longest = 0;
+    for (index, &len) in lcp.iter().enumerate() {
+        if len > lcp[longest] {
+            longest = index;
+        }
+    }
+    if lcp.get(longest).is_none_or(|&len| len == 0) {
+        return None;
+    }
+
+    let mut positions = [suffix_array[longest - 1], suffix_array[longest]];
+    positions.sort_unstable();
+    Some(Repeat {
+        positions,
+        len: lcp[longest],
+    })
+}
+
+/// Returns the longest substring of `text` occurring at least twice without overlapping
+pub fn longest_non_overlapping_repeated_substring(text: &[u8]) -> Option<Repeat> {
+    let (suffix_array, lcp) = suffix_array_with_lcp::<usize>(text);
+
+    // The leftmost and rightmost occurrences of a substring of length `len` are the best candidates
+    let find = |len: usize| {
+        let mut first = 0;
+        let mut last = 0;
+        for (index, &suffix) in suffix_array.iter().enumerate() {
+            if index == 0 || lcp[index] < len {
+                first = suffix;
+                last = suffix;
+            } else {
+                first = first.min(suffix);
+                last = last.max(suffix);
+                if last - first >= len {
+                    return Some([first, last]);
+                }
+            }
+        }
+        None
+    };
+
+    // A non-overlapping repeat of length `len` contains one of every shorter length
+    let mut best = None;
+    let mut low = 1;
+    let mut high = text.len() / 2;
+    while low <= high {
+        let len = (low + high) / 2;
+        match find(len) {
+            Some(positions) => {
+                best = Some(Repeat { positions, len });
+                low = len + 1;
+            }
+            None => high = len - 1,
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rmq::BlockRmq;
+    use crate::sais::suffix_array;
+
+    fn naive_maximal_exact_matches(
+        a: &[u8],
+        b: &[u8],
+        min_len: usize,
+    ) -> Vec<(usize, usize, usize)> {
+        let mut matches = Vec::new();
+        for i in 0..a.len() {
+            for j in 0..b.len() {
+                if i > 0 && j > 0 && a[i - 1] == b[j - 1] {
+                    continue;
+                }
+                let len = a[i..]
+                    .iter()
+                    .zip(&b[j..])
+                    .take_while(|(x, y)| x == y)
+                    .count();
+                if len >= min_len.max(1) {
+                    matches.push((i, j, len));
+                }
+            }
+        }
+        matches
+    }
+
+    #[test]
+    fn test_longest_repeated_substring() {
+        assert_eq!(
+            longest_repeated_substring(b"banana"),
+            Some(Repeat {
+                positions: [1, 3],
+                len: 3
+            })
+        );
+        assert_eq!(longest_repeated_substring(b"abc"), None);
+        assert_eq!(longest_repeated_substring(b""), None);
+
+        assert_eq!(
+            longest_non_overlapping_repeated_substring(b"banana"),
+            Some(Repeat {
+                positions: [1, 3],
+                len: 2
+            })
+        );
+        assert_eq!(
+            longest_non_overlapping_repeated_substring(b"aaaaa"),
+            Some(Repeat {
+                positions: [1, 3],
+                len: 2
+            })
+        );
+        assert_eq!(longest_non_overlapping_repeated_substring(b"a"), None);
+        let repeat = longest_non_overlapping_repeated_substring(b"xabcyabcabc").unwrap();
+        assert_eq!(repeat.len, 3);
+        let [first, second] = repeat.positions;
+        assert!(second - first >= 3);
+        assert_eq!(
+            b"xabcyabcabc"[first..first + 3],
+            b"xabcyabcabc"[second..second + 3]
+        );
+    }
+
+    fn naive_maximal_repeats(text: &[u8]) -> Vec<(usize, Vec<usize>)> {
+        let mut repeats = Vec::new();
+        for len in 1..text.len() {
+            let mut substrings = std::collections::BTreeMap::<&[u8], Vec<usize>>::new();
+            for (position, window) in text.windows(len).enumerate() {
+                substrings.entry(window).or_default().push(position);
+            }
+            for positions in substrings.into_values() {
+                let distinct = |c: &dyn Fn(usize) -> Option<u8>| {
+                    let mut c = positions.iter().map(|&p| c(p)).collect::<Vec<_>>();
+                    c.dedup();
+                    c.len() > 1 || c.contains(&None)
+                };
+                let left = distinct(&|p| p.checked_sub(1).map(|i| text[i]));
+                let right = distinct(&|p| text.get(p + len).copied());
+                if positions.len() >= 2 && left && right {
+                    repeats.push((len, positions));
+                }
+            }
+        }
+        repeats.sort();
+        repeats
+    }
+
+    #[test]
+    fn test_maximal_repeats() {
+        for text in [&b"abracadabra"[..], b"aaaaa", b"mississippi", b"abcabcab"] {
+            let mut repeats = maximal_repeats(text, 0, 0)
+                .into_iter()
+                .map(|repeat| (repeat.len, repeat.positions))
+                .collect::<Vec<_>>();
+            repeats.sort();
+            assert_eq!(repeats, naive_maximal_repeats(text));
+        }
+        let repeats = maximal_repeats(b"abracadabra", 2, 2);
+        assert_eq!(
+            repeats,
+            vec![MaximalRepeat {
+                len: 4,
+                positions: vec![0, 7]
+            }]
+        );
+        assert!(maximal_repeats(b"abracadabra", 1, 3)
+            .iter()
+            .all(|repeat| repeat.positions.len() >= 3));
+    }
+
+    #[test]
+    fn test_supermaximal_repeats() {
+        for text in [&b"abracadabra"[..], b"aaaaa", b"mississippi", b"abcabcab"] {
+            let maximal = maximal_repeats(text, 0, 0);
+            let mut expected = maximal
+                .iter()
+                .filter(|repeat| {
+                    let repeated = &text[repeat.positions[0]..][..repeat.len];
+                    !maximal.iter().any(|other| {
+                        other.len > repeat.len
+                            && text[other.positions[0]..][..other.len]
+                                .windows(repeat.len)
+                                .any(|window| window == repeated)
+                    })
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            let mut repeats = supermaximal_repeats(text, 0, 0);
+            expected.sort_by_key(|repeat| repeat.positions.clone());
+            repeats.sort_by_key(|repeat| repeat.positions.clone());
+            assert_eq!(repeats, expected);
+        }
+        assert_eq!(
+            supermaximal_repeats(b"mississippi", 3, 0),
+            vec![MaximalRepeat {
+                len: 4,
+                positions: vec![1, 4]
+            }]
+        );
+    }
+
+    fn is_palindrome(text: &[u8]) -> bool {
+        text.iter().eq(text.iter().rev())
+    }
+
+    #[test]
+    fn test_palindromes() {
+        for text in [
+            &b""[..],
+            b"a",
+            b"abacaba",
+            b"banana",
+            b"abba",
+            b"mississippi",
+            b"abcab",
+        ] {
+            let palindromes = Palindromes::new(text);
+            let block = Palindromes::<BlockRmq<usize>>::with_backend(text);
+            for center in 0..=2 * text.len() {
+                let longest = palindromes.longest_at(center);
+                assert_eq!(block.longest_at(center), longest);
+                assert_eq!(longest.start + longest.end, center);
+                assert!(is_palindrome(&text[longest.clone()]));
+                if longest.start > 0 && longest.end < text.len() {
+                    assert_ne!(text[longest.start - 1], text[longest.end]);
+                }
+            }
+
+            for start in 0..=text.len() {
+                for end in start..=text.len() {
+                    assert_eq!(
+                        palindromes.is_palindrome(start..end),
+                        is_palindrome(&text[start..end])
+                    );
+                }
+            }
+
+            let mut expected = Vec::new();
+            for end in 1..=text.len() {
+                for start in 0..end {
+                    if is_palindrome(&text[start..end])
+                        && !expected
+                            .iter()
+                            .any(|range: &Range<usize>| text[range.clone()] == text[start..end])
+                    {
+                        expected.push(start..end);
+                    }
+                }
+            }
+            expected.sort_by_key(|range| (range.end, range.start));
+            assert_eq!(distinct_palindromes(text), expected);
+        }
+        assert_eq!(longest_palindrome(b"banana"), 1..6);
+        assert_eq!(longest_palindrome(b"xabbay"), 1..5);
+        assert_eq!(longest_palindrome(b""), 0..0);
+        assert!(is_rich(b"abacaba"));
+        assert!(!is_rich(b"abcab"));
+    }
+
+    #[test]
+    fn test_duplicate_blocks() {
+        let files: &[&[u8]] = &[b"0123456789abcdef", b"xx0123456789yy", b"abcdefabcdef"];
+        let index = GeneralizedSuffixArray::<u32>::new(files);
+        let block = |file, offset, len, group| DuplicateBlock {
+            file,
+            offset,
+            len,
+            group,
+        };
+        assert_eq!(
+            duplicate_blocks(&index, 5),
+            vec![
+                block(0, 0, 10, 0),
+                block(0, 10, 6, 1),
+                block(1, 2, 10, 0),
+                block(2, 0, 6, 1),
+                block(2, 6, 6, 1),
+            ]
+        );
+        assert_eq!(duplicate_blocks(&index, 11), vec![]);
+        // overlapping occurrences of the same block are not counted twice
+        let index = GeneralizedSuffixArray::<u32>::new(&[b"aaaaaaa"]);
+        assert_eq!(
+            duplicate_blocks(&index, 3),
+            vec![block(0, 0, 3, 0), block(0, 3, 3, 0)]
+        );
+    }
+
+    fn naive_runs(text: &[u8]) -> Vec<Run> {
+        let mut runs = Vec::new();
+        for period in 1..=text.len() / 2 {
+            let mut start = 0;
+            while start + period < text.len() {
+                let mut end = start + period;
+                while end < text.len() && text[end] == text[end - period] {
+                    end += 1;
+                }
+                let run = &text[start..end];
+                let smallest_period = (1..=period)
+                    .find(|&p| run.iter().skip(p).zip(run).all(|(a, b)| a == b))
+                    .unwrap();
+                if end - start >= 2 * period && smallest_period == period {
+                    runs.push(Run {
+                        start,
+                        len: end - start,
+                        period,
+                    });
+                }
+                start = if end == start + period {
+                    start + 1
+                } else {
+                    end - period + 1
+                };
+            }
+        }
+        runs.sort_unstable();
+        runs
+    }
+
+    #[test]
+    fn test_runs() {
+        for text in [
+            &b"abaababaab"[..],
+            b"aaaaa",
+            b"mississippi",
+            b"abcabcab",
+            b"",
+            b"a",
+            b"aabaabaabbabbab",
+        ] {
+            assert_eq!(runs(text), naive_runs(text));
+        }
+        let runs = runs(b"xabcabcaby");
+        assert_eq!(
+            runs,
+            vec![Run {
+                start: 1,
+                len: 8,
+                period: 3
+            }]
+        );
+        assert!((runs[0].exponent() - 8.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_lz77() {
+        let factors = lz77_factorize(b"abababbx");
+        assert_eq!(
+            factors,
+            vec![
+                Factor {
+                    position: 0,
+                    len: 0,
+                    next: Some(b'a')
+                },
+                Factor {
+                    position: 0,
+                    len: 0,
+                    next: Some(b'b')
+                },
+                Factor {
+                    position: 0,
+                    len: 4,
+                    next: Some(b'b')
+                },
+                Factor {
+                    position: 0,
+                    len: 0,
+                    next: Some(b'x')
+                }
+            ]
+        );
+        assert_eq!(lz77_factorize(b"aaaa").last().unwrap().next, None);
+        for text in [
+            &b"mississippi"[..],
+            b"",
+            b"aaaaaaaa",
+            b"abracadabra abracadabra",
+        ] {
+            let factors = lz77_factorize(text);
+            assert_eq!(lz77_decode(&factors), text);
+        }
+    }
+
+    #[test]
+    fn test_lz() {
+        let literal = |c| Factor {
+            position: 0,
+            len: 0,
+            next: Some(c),
+        };
+        let copy = |position, len| Factor {
+            position,
+            len,
+            next: None,
+        };
+        assert_eq!(
+            lz_factorize(b"abababbx"),
+            vec![
+                literal(b'a'),
+                literal(b'b'),
+                copy(0, 4),
+                copy(5, 1),
+                literal(b'x')
+            ]
+        );
+        for text in [
+            &b"mississippi"[..],
+            b"",
+            b"aaaaaaaa",
+            b"abracadabra abracadabra",
+        ] {
+            let factors = lz_factorize(text);
+            assert_eq!(lz77_decode(&factors), text);
+            assert!(factors.len() <= lz77_factorize(text).len() * 2);
+        }
+    }
+
+    #[test]
+    fn test_lpf_array() {
+        for text in [
+            &b"abaababaab"[..],
+            b"aaaaa",
+            b"mississippi",
+            b"",
+            b"abcabcab",
+        ] {
+            let (lpf, prev_occ) = lpf_array(text);
+            for i in 0..text.len() {
+                let common = |j: usize| {
+                    text[i..]
+                        .iter()
+                        .zip(&text[j..])
+                        .take_while(|(a, b)| a == b)
+                        .count()
+                };
+                let expected = (0..i).map(common).max().unwrap_or(0);
+                assert_eq!(lpf[i], expected);
+                match prev_occ[i] {
+                    Some(j) => assert!(j < i && common(j) == expected),
+                    None => assert_eq!(expected, 0),
+                }
+            }
+        }
+    }
+
+    fn is_lyndon(word: &[u8]) -> bool {
+        !word.is_empty() && (1..word.len()).all(|i| word < &word[i..])
+    }
+
+    #[test]
+    fn test_lyndon() {
+        for text in [
+            &b"abaababaab"[..],
+            b"aaaaa",
+            b"mississippi",
+            b"",
+            b"cba",
+            b"abcabcab",
+        ] {
+            let factors = lyndon_factorization(text);
+            assert_eq!(factors.iter().map(|f| f.len()).sum::<usize>(), text.len());
+            assert!(factors.iter().all(|f| is_lyndon(&text[f.clone()])));
+            assert!(factors
+                .windows(2)
+                .all(|w| text[w[0].clone()] >= text[w[1].clone()]));
+
+            let rank = inverse(&suffix_array::<u32>(text));
+            assert_eq!(lyndon_factorization_from_inverse(&rank), factors);
+            assert_eq!(lyndon_array_from_inverse(&rank), lyndon_array(text));
+
+            let lyndon = lyndon_array(text);
+            for (i, &len) in lyndon.iter().enumerate() {
+                let expected = (1..=text.len() - i)
+                    .filter(|&len| is_lyndon(&text[i..i + len]))
+                    .max()
+                    .unwrap();
+                assert_eq!(len, expected);
+            }
+        }
+        assert_eq!(
+            lyndon_factorization(b"banana"),
+            vec![0..1, 1..3, 3..5, 5..6]
+        );
+    }
+
+    #[test]
+    fn test_distinct_substrings() {
+        for text in [&b"abaababaab"[..], b"aaaaa", b"mississippi", b"", b"abc"] {
+            let mut total = 0;
+            let profile = distinct_substring_profile(text, text.len() + 2);
+            for len in 1..=text.len() + 2 {
+                let distinct = text
+                    .windows(len)
+                    .collect::<std::collections::BTreeSet<_>>()
+                    .len();
+                assert_eq!(profile[len - 1], distinct as u64);
+                total += distinct;
+            }
+            assert_eq!(count_distinct_substrings(text), total as u128);
+        }
+        assert_eq!(distinct_substring_profile(b"banana", 2), vec![3, 3]);
+        assert!(distinct_substring_profile(b"banana", 0).is_empty());
+        assert_eq!(substring_complexity(b"banana"), 3.0);
+        assert_eq!(substring_complexity(b"aaaaaaaa"), 1.0);
+        assert_eq!(substring_complexity(b"abcd"), 4.0);
+        assert_eq!(substring_complexity(b""), 0.0);
+    }
+
+    #[test]
+    fn test_substring_kernel() {
+        let documents: &[&[u8]] = &[b"abab", b"bab", b"", b"ab", b"abab"];
+        let index = GeneralizedSuffixArray::<u32>::new(documents);
+        for max_len in [1, 2, 3, usize::MAX] {
+            let kernel = substring_kernel(&index, max_len);
+            for (a, x) in documents.iter().enumerate() {
+                for (b, y) in documents.iter().enumerate() {
+                    let mut expected = 0;
+                    for len in 1..=x.len().min(y.len()).min(max_len) {
+                        for u in x.windows(len) {
+                            expected += y.windows(len).filter(|v| u == *v).count() as u64;
+                        }
+                    }
+                    assert_eq!(kernel[a][b], expected, "{} {} {}", a, b, max_len);
+                }
+            }
+            let normalized = normalize_kernel(&kernel);
+            assert!((normalized[0][4] - 1.0).abs() < 1e-12);
+            assert!(normalized[0][1] < 1.0);
+            assert_eq!(normalized[2][0], 0.0);
+        }
+    }
+
+    #[test]
+    fn test_longest_common_substring() {
+        let documents: &[&[u8]] = &[b"the quick brown fox", b"a quick brown dog", b"brown fox"];
+        let index = GeneralizedSuffixArray::<u32>::new(documents);
+        let substring = |min_documents| {
+            longest_common_substring(&index, min_documents).map(|range| &index.text()[range])
+        };
+        assert_eq!(substring(1), Some(&b"the quick brown fox"[..]));
+        assert_eq!(substring(2), Some(&b" quick brown "[..]));
+        assert_eq!(substring(3), Some(&b"brown "[..]));
+        assert_eq!(substring(4), None);
+        let index = GeneralizedSuffixArray::<u32>::new(&[b"abc", b"def"]);
+        assert_eq!(longest_common_substring(&index, 2), None);
+    }
+
+    #[test]
+    fn test_suffix_prefix_overlaps() {
+        let documents: &[&[u8]] = &[b"ACGTTA", b"TTACG", b"ACGTT", b"GGG", b"TACGT"];
+        let index = GeneralizedSuffixArray::<u32>::new(documents);
+        let mut expected = Vec::new();
+        for (first, a) in documents.iter().enumerate() {
+            for (second, b) in documents.iter().enumerate() {
+                let longest = (2..a.len().min(b.len()))
+                    .rev()
+                    .find(|&len| a[a.len() - len..] == b[..len]);
+                if let (true, Some(len)) = (first != second, longest) {
+                    expected.push((first, second, len));
+                }
+            }
+        }
+        assert_eq!(suffix_prefix_overlaps(&index, 2), expected);
+        assert!(expected.contains(&(0, 1, 3)));
+    }
+
+    #[test]
+    fn test_shared_passages() {
+        let documents: &[&[u8]] = &[b"the quick brown fox", b"a quick brown dog", b"brown fox"];
+        let index = GeneralizedSuffixArray::<u32>::new(documents);
+        let passages = shared_passages(&index, 6);
+        assert_eq!(
+            passages,
+            vec![
+                SharedPassage {
+                    first: (0, 3),
+                    second: (1, 1),
+                    len: 13
+                },
+                SharedPassage {
+                    first: (0, 10),
+                    second: (2, 0),
+                    len: 9
+                },
+                SharedPassage {
+                    first: (1, 8),
+                    second: (2, 0),
+                    len: 6
+                },
+            ]
+        );
+        // repeats inside a single document are not reported
+        let documents: &[&[u8]] = &[b"abcabc", b"xyz"];
+        let index = GeneralizedSuffixArray::<u32>::new(documents);
+        assert!(shared_passages(&index, 1).is_empty());
+    }
+
+    #[test]
+    fn test_kmer_counts() {
+        let text = b"ACGTACGTTACG";
+        for k in 1..=text.len() + 1 {
+            let mut expected = std::collections::BTreeMap::<&[u8], usize>::new();
+            for kmer in text.windows(k) {
+                *expected.entry(kmer).or_default() += 1;
+            }
+            assert_eq!(
+                kmer_counts(text, k),
+                expected.into_iter().collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_minimal_absent_words() {
+        assert_eq!(
+            minimal_absent_words(b"abaab"),
+            [&b"aaa"[..], b"aaba", b"bab", b"bb"]
+        );
+        for text in [&b""[..], b"a", b"aaaa", b"mississippi", b"ACGTTGCAAC"] {
+            let occurs = |word: &[u8]| text.windows(word.len()).any(|window| window == word);
+            let mut alphabet = text.to_vec();
+            alphabet.sort_unstable();
+            alphabet.dedup();
+            let mut expected = Vec::new();
+            for len in 2..=text.len() + 1 {
+                for start in 0..text.len() + 1 - (len - 1) {
+                    for &c in &alphabet {
+                        let word = [&text[start..start + len - 1], &[c]].concat();
+                        if !occurs(&word) && occurs(&word[1..]) && !expected.contains(&word) {
+                            expected.push(word);
+                        }
+                    }
+                }
+            }
+            expected.sort_unstable();
+            assert_eq!(minimal_absent_words(text), expected);
+        }
+    }
+
+    #[test]
+    fn test_top_kmers() {
+        let text = b"ACGTACGTTACG";
+        assert_eq!(
+            top_kmers(text, 2, 3),
+            [(&b"AC"[..], 3), (&b"CG"[..], 3), (&b"GT"[..], 2)]
+        );
+        assert_eq!(top_kmers(text, 3, 100).len(), kmer_counts(text, 3).len());
+        assert!(top_kmers(text, 2, 0).is_empty());
+    }
+
+    #[test]
+    fn test_super_maximal_exact_matches() {
+        let index = BidirectionalFmIndex::<u32>::new(b"ACGTTACGAT\0GATTACA");
+        let smems = super_maximal_exact_matches(&index, b"TTACGATTACAX", 1, usize::MAX);
+        let matched = smems
+            .iter()
+            .map(|smem| smem.query.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(matched, vec![0..7, 4..11]);
+        assert_eq!(index.count(smems[1].interval), 1);
+
+        let smems = super_maximal_exact_matches(&index, b"TTACGATTACAX", 8, usize::MAX);
+        assert!(smems.is_empty());
+        let smems = super_maximal_exact_matches(&index, b"GAT", 1, 1);
+        assert!(smems.is_empty());
+    }
+
+    #[test]
+    fn test_maximal_exact_matches() {
+        let a = b"ACGTACGTTGCAACGT";
+        let b = b"TTACGTACGAACGTTG";
+        for min_len in 0..6 {
+            assert_eq!(
+                maximal_exact_matches(a, b, min_len),
+                naive_maximal_exact_matches(a, b, min_len)
+            );
+        }
+        assert_eq!(maximal_exact_matches(b"abc", b"", 1), vec![]);
+        assert_eq!(
+            maximal_exact_matches(b"aaaa", b"aa", 2),
+            vec![(0, 0, 2), (1, 0, 2), (2, 0, 2)]
+        );
+    }
+}
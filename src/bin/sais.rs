@@ -0,0 +1,273 @@
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::fs;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::process::exit;
+
+use sais_rs::bwt::{bwt, try_unbwt};
+use sais_rs::lcp::lcp_array;
+use sais_rs::{suffix_array, SuffixArray, SuffixIndex};
+
+const USAGE: &str = "\
+usage: sais <command> [--width 32|64] [--format binary|text] <input> <output>
+       sais search [--width 32|64] [--format binary|text] <input> <index> [<query>]
+
+commands:
+    build   write the suffix array of <input>
+    lcp     write the lcp array of <input>
+    bwt     write the burrows-wheeler transform of <input>
+    unbwt   invert a transform written by bwt
+    search  answer a query over <input> with its suffix array <index> written by build
+
+queries:
+    count <pattern>       the number of occurrences of <pattern>
+    locate <pattern>      the positions of all occurrences of <pattern> in ascending order
+    extract <start> <end> the bytes of <input> from <start> to <end>
+
+Without a query on the command line, queries are read from stdin, one per line.
+
+Integers are written as little endian words of the selected width or as decimal lines.
+The transform is the primary index, the position of the omitted end marker, followed by the
+transformed bytes.";
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Format {
+    Binary,
+    Text,
+}
+
+struct Options {
+    command: String,
+    width: usize,
+    format: Format,
+    paths: Vec<String>,
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let command = args.next().ok_or("missing command")?;
+    if !["build", "lcp", "bwt", "unbwt", "search"].contains(&command.as_str()) {
+        return Err(format!("unknown command {}", command));
+    }
+    let mut width = 32;
+    let mut format = Format::Binary;
+    let mut paths = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--width" => {
+                width = match args.next().as_deref() {
+                    Some("32") => 32,
+                    Some("64") => 64,
+                    _ => return Err("width must be 32 or 64".into()),
+                }
+            }
+            "--format" => {
+                format = match args.next().as_deref() {
+                    Some("binary") => Format::Binary,
+                    Some("text") => Format::Text,
+                    _ => return Err("format must be binary or text".into()),
+                }
+            }
+            _ => paths.push(arg),
+        }
+    }
+    if paths.len() < 2 || (command != "search" && paths.len() > 2) {
+        return Err("expected an input and an output path".into());
+    }
+    Ok(Options {
+        command,
+        width,
+        format,
+        paths,
+    })
+}
+
+struct IntegerWriter<W> {
+    out: W,
+    width: usize,
+    format: Format,
+}
+
+impl<W: Write> IntegerWriter<W> {
+    fn write(&mut self, value: usize) -> io::Result<()> {
+        match self.format {
+            Format::Binary => self
+                .out
+                .write_all(&(value as u64).to_le_bytes()[..self.width / 8]),
+            Format::Text => writeln!(self.out, "{}", value),
+        }
+    }
+}
+
+fn read_integer(input: &mut &[u8], width: usize, format: Format) -> Result<usize, String> {
+    let value = match format {
+        Format::Binary => {
+            if input.len() < width / 8 {
+                return Err("truncated input".into());
+            }
+            let mut bytes = [0; 8];
+            bytes[..width / 8].copy_from_slice(&input[..width / 8]);
+            *input = &input[width / 8..];
+            u64::from_le_bytes(bytes)
+        }
+        Format::Text => {
+            let end = input
+                .iter()
+                .position(|&c| c == b'\n')
+                .ok_or("truncated input")?;
+            let line = std::str::from_utf8(&input[..end]).map_err(|e| e.to_string())?;
+            *input = &input[end + 1..];
+            line.trim().parse().map_err(|_| "invalid integer")?
+        }
+    };
+    usize::try_from(value).map_err(|_| "integer overflow".into())
+}
+
+fn query<I: SuffixIndex, W: Write>(
+    suffix_array: &SuffixArray<I>,
+    query: &[String],
+    out: &mut W,
+) -> Result<(), String> {
+    let result = match query {
+        [command, pattern] if command == "count" => {
+            writeln!(out, "{}", suffix_array.count(pattern.as_bytes()))
+        }
+        [command, pattern] if command == "locate" => {
+            let mut positions = suffix_array.locate(pattern.as_bytes()).collect::<Vec<_>>();
+            positions.sort_unstable();
+            let positions = positions
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            writeln!(out, "{}", positions.join(" "))
+        }
+        [command, start, end] if command == "extract" => {
+            let start = start.parse::<usize>().map_err(|_| "invalid start")?;
+            let end = end.parse::<usize>().map_err(|_| "invalid end")?;
+            let bytes = suffix_array.text().get(start..end).ok_or("invalid range")?;
+            out.write_all(bytes).and_then(|_| writeln!(out))
+        }
+        _ => return Err(format!("invalid query {}", query.join(" "))),
+    };
+    result.map_err(|e| e.to_string())
+}
+
+fn search<I: SuffixIndex>(options: &Options, text: &[u8]) -> Result<(), String> {
+    let path = &options.paths[1];
+    let input = fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+    let mut input = &input[..];
+    let mut suffix_array = Vec::with_capacity(text.len());
+    while !input.is_empty() {
+        let suffix = read_integer(&mut input, options.width, options.format)?;
+        if suffix >= text.len() {
+            return Err(format!("{}: index does not match the input", path));
+        }
+        suffix_array.push(I::from_index(suffix));
+    }
+    if suffix_array.len() != text.len() {
+        return Err(format!("{}: index does not match the input", path));
+    }
+    let suffix_array = SuffixArray::from_parts(Cow::Borrowed(text), suffix_array);
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    if options.paths.len() > 2 {
+        query(&suffix_array, &options.paths[2..], &mut out)?;
+    } else {
+        for line in io::stdin().lock().lines() {
+            let line = line.map_err(|e| e.to_string())?;
+            let words = line
+                .split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>();
+            if !words.is_empty() {
+                query(&suffix_array, &words, &mut out)?;
+            }
+        }
+    }
+    out.flush().map_err(|e| e.to_string())
+}
+
+fn run<I: SuffixIndex>(options: &Options) -> Result<(), String> {
+    let (input_path, output_path) = (&options.paths[0], &options.paths[1]);
+    // Safety: the input is not expected to change while it is processed
+    #[cfg(feature = "mmap")]
+    let input = unsafe { sais_rs::mmap::map_text(input_path) };
+    #[cfg(not(feature = "mmap"))]
+    let input = fs::read(input_path);
+    let input = input.map_err(|e| format!("{}: {}", input_path, e))?;
+    let input = &input[..];
+    if options.command != "unbwt" && input.len() >= I::MAX {
+        return Err("input is too large for the index width".into());
+    }
+    if options.command == "search" {
+        return search::<I>(options, input);
+    }
+    let file = fs::File::create(output_path).map_err(|e| format!("{}: {}", output_path, e))?;
+    let mut out = IntegerWriter {
+        out: BufWriter::new(file),
+        width: options.width,
+        format: options.format,
+    };
+
+    let result = match options.command.as_str() {
+        "build" => suffix_array::<I>(input)
+            .iter()
+            .try_for_each(|suffix| out.write(suffix.as_index())),
+        "lcp" => lcp_array(input, &suffix_array::<I>(input))
+            .iter()
+            .try_for_each(|lcp| out.write(lcp.as_index())),
+        "bwt" => {
+            let (transformed, primary) = bwt(input);
+            out.write(primary)
+                .and_then(|_| out.out.write_all(&transformed))
+        }
+        "unbwt" => {
+            let mut transformed = input;
+            let primary = read_integer(&mut transformed, options.width, options.format)?;
+            let text = try_unbwt(transformed, primary).ok_or("invalid transform")?;
+            out.out.write_all(&text)
+        }
+        _ => unreachable!(),
+    };
+    result
+        .and_then(|_| out.out.flush())
+        .map_err(|e| format!("{}: {}", output_path, e))
+}
+
+fn main() {
+    let result = parse_options(std::env::args().skip(1)).and_then(|options| {
+        if options.width == 64 {
+            run::<u64>(&options)
+        } else {
+            run::<u32>(&options)
+        }
+    });
+    if let Err(error) = result {
+        eprintln!("error: {}\n\n{}", error, USAGE);
+        exit(2);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_query() {
+        let suffix_array = SuffixArray::<u32>::new(b"mississippi");
+        let run = |words: &[&str]| {
+            let words = words
+                .iter()
+                .map(|&word| word.to_owned())
+                .collect::<Vec<_>>();
+            let mut out = Vec::new();
+            query(&suffix_array, &words, &mut out).map(|_| String::from_utf8(out).unwrap())
+        };
+        assert_eq!(run(&["count", "ssi"]).unwrap(), "2\n");
+        assert_eq!(run(&["count", "x"]).unwrap(), "0\n");
+        assert_eq!(run(&["locate", "ssi"]).unwrap(), "2 5\n");
+        assert_eq!(run(&["extract", "2", "6"]).unwrap(), "ssis\n");
+        assert!(run(&["extract", "6", "20"]).is_err());
+        assert!(run(&["find", "ssi"]).is_err());
+    }
+}
@@ -0,0 +1,271 @@
+use std::cmp::Ordering;
+use std::num::NonZeroUsize;
+use std::thread::{self, available_parallelism};
+
+use crate::sais::{sort_bwt, Types};
+use crate::suffix_index::SuffixIndex;
+
+/// Computes the Burrows-Wheeler transform of `text` followed by a unique smallest end marker.
+///
+/// The end marker is omitted from the transform, the primary index is the row it would be in.
+pub fn bwt(text: &[u8]) -> (Vec<u8>, usize) {
+    let mut transformed = text.to_vec();
+    let primary = bwt_inplace(&mut transformed);
+    (transformed, primary)
+}
+
+/// Replaces `text` with its transform and returns the primary index, see [`bwt`].
+///
+/// The transform is induced into the buffer of the suffix array, the suffix array itself and
+/// a separate output are never stored.
+pub fn bwt_inplace(text: &mut [u8]) -> usize {
+    let rate = text.len().max(1);
+    bwt_aux_inplace(text, rate).first().copied().unwrap_or(0)
+}
+
+/// Like [`bwt`], also returns the row of every suffix starting at a multiple of `rate`.
+///
+/// The first entry is the primary index. The samples split the inversion into independent blocks
+/// of `rate` characters, see [`unbwt_aux`].
+pub fn bwt_aux(text: &[u8], rate: usize) -> (Vec<u8>, Vec<usize>) {
+    let mut transformed = text.to_vec();
+    let aux = bwt_aux_inplace(&mut transformed, rate);
+    (transformed, aux)
+}
+
+fn bwt_aux_inplace(text: &mut [u8], rate: usize) -> Vec<usize> {
+    assert!(rate > 0, "the sampling rate must be positive");
+    if text.len() < u32::MAX as usize {
+        bwt_aux_inplace_with::<u32>(text, rate)
+    } else {
+        bwt_aux_inplace_with::<u64>(text, rate)
+    }
+}
+
+fn bwt_aux_inplace_with<I: SuffixIndex>(text: &mut [u8], rate: usize) -> Vec<usize> {
+    let Some(&last) = text.last() else {
+        return Vec::new();
+    };
+    let mut buffer = vec![I::from_index(0); text.len()];
+    let mut aux = vec![I::from_index(0); text.len().div_ceil(rate)];
+    let mut types = Types::new(text.len());
+    let mut buckets = vec![I::from_index(0); u8::MAX as usize + 1];
+    sort_bwt(text, &mut buffer, &mut aux, rate, &mut types, &mut buckets);
+    drop(types);
+
+    let end = I::from_index(I::MAX);
+    text[0] = last;
+    let rows = buffer.iter().filter(|&&c| c != end);
+    for (c, &row) in text[1..].iter_mut().zip(rows) {
+        *c = row.as_index() as u8;
+    }
+    aux.iter().map(|row| row.as_index()).collect()
+}
+
+/// Like [`bwt`] with the suffix array of `text` computed elsewhere
+pub fn bwt_from_suffix_array(
+    text: &[u8],
+    suffix_array: impl IntoIterator<Item = usize>,
+) -> (Vec<u8>, usize) {
+    let mut primary = 0;
+    let mut transformed = Vec::with_capacity(text.len());
+    // The row of the end marker comes first and is preceded by the last character
+    transformed.extend(text.last());
+    for (index, suffix) in suffix_array.into_iter().enumerate() {
+        match suffix {
+            0 => primary = index + 1,
+            suffix => transformed.push(text[suffix - 1]),
+        }
+    }
+    assert_eq!(transformed.len(), text.len());
+    (transformed, primary)
+}
+
+/// Inverts the transform computed by [`bwt`].
+///
+/// Panics if `transformed` and `primary` are not the transform of any text.
+pub fn unbwt(transformed: &[u8], primary: usize) -> Vec<u8> {
+    try_unbwt(transformed, primary).expect("invalid transform")
+}
+
+/// Like [`unbwt`], but returns `None` if `transformed` and `primary` are not the transform of any
+/// text
+pub fn try_unbwt(transformed: &[u8], primary: usize) -> Option<Vec<u8>> {
+    if transformed.len() < u32::MAX as usize {
+        unbwt_with::<u32>(transformed, primary)
+    } else {
+        unbwt_with::<u64>(transformed, primary)
+    }
+}
+
+fn unbwt_with<I: SuffixIndex>(transformed: &[u8], primary: usize) -> Option<Vec<u8>> {
+    let inverse = Inverse::<I>::new(transformed, primary)?;
+    let mut text = vec![0; transformed.len()];
+    inverse.decode(0, &mut text)?;
+    Some(text)
+}
+
+/// Inverts the transform computed by [`bwt_aux`] with the same `rate`, decoding the blocks between
+/// samples on all available threads.
+///
+/// Panics if `transformed` and `aux` are not the transform of any text.
+pub fn unbwt_aux(transformed: &[u8], aux: &[usize], rate: usize) -> Vec<u8> {
+    try_unbwt_aux(transformed, aux, rate).expect("invalid transform")
+}
+
+/// Like [`unbwt_aux`], but returns `None` if `transformed` and `aux` are not the transform of any
+/// text
+pub fn try_unbwt_aux(transformed: &[u8], aux: &[usize], rate: usize) -> Option<Vec<u8>> {
+    assert!(rate > 0, "the sampling rate must be positive");
+    if transformed.len() < u32::MAX as usize {
+        unbwt_aux_with::<u32>(transformed, aux, rate)
+    } else {
+        unbwt_aux_with::<u64>(transformed, aux, rate)
+    }
+}
+
+fn unbwt_aux_with<I: SuffixIndex + Sync>(
+    transformed: &[u8],
+    aux: &[usize],
+    rate: usize,
+) -> Option<Vec<u8>> {
+    if aux.len() != transformed.len().div_ceil(rate)
+        || aux.iter().any(|&row| row > transformed.len())
+    {
+        return None;
+    }
+    let inverse = Inverse::<I>::new(transformed, aux.first().copied().unwrap_or(0))?;
+    let mut text = vec![0; transformed.len()];
+    let threads = available_parallelism().map_or(1, NonZeroUsize::get);
+    let blocks_per_thread = aux.len().div_ceil(threads).max(1);
+    let decoded = thread::scope(|scope| {
+        let handles = text
+            .chunks_mut(blocks_per_thread * rate)
+            .enumerate()
+            .map(|(chunk, text)| {
+                let inverse = &inverse;
+                scope.spawn(move || {
+                    text.chunks_mut(rate).enumerate().all(|(i, block)| {
+                        // A block is decoded backwards from the row of the suffix following it
+                        let next = chunk * blocks_per_thread + i + 1;
+                        let row = aux.get(next).copied().unwrap_or(0);
+                        inverse.decode(row, block).is_some()
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+        handles.into_iter().all(|handle| handle.join().unwrap())
+    });
+    decoded.then_some(text)
+}
+
+/// The LF mapping of a transform
+struct Inverse<'a, I> {
+    transformed: &'a [u8],
+    primary: usize,
+    /// The row of the suffix starting one character earlier for every row
+    next: Vec<I>,
+}
+
+impl<'a, I: SuffixIndex> Inverse<'a, I> {
+    fn new(transformed: &'a [u8], primary: usize) -> Option<Self> {
+        if primary > transformed.len() || (primary == 0) != transformed.is_empty() {
+            return None;
+        }
+        let mut inverse = Self {
+            transformed,
+            primary,
+            next: vec![I::from_index(0); transformed.len() + 1],
+        };
+
+        // The end marker sorts first
+        let mut starts = [0; u8::MAX as usize + 1];
+        for &c in transformed {
+            starts[c as usize] += 1;
+        }
+        let mut sum = 1;
+        for start in starts.iter_mut() {
+            sum += *start;
+            *start = sum - *start;
+        }
+        for row in 0..inverse.next.len() {
+            if let Some(c) = inverse.row_char(row) {
+                inverse.next[row] = I::from_index(starts[c as usize]);
+                starts[c as usize] += 1;
+            }
+        }
+        Some(inverse)
+    }
+
+    /// The character in front of `row`, the row of the end marker is included
+    fn row_char(&self, row: usize) -> Option<u8> {
+        match row.cmp(&self.primary) {
+            Ordering::Less => Some(self.transformed[row]),
+            Ordering::Equal => None,
+            Ordering::Greater => Some(self.transformed[row - 1]),
+        }
+    }
+
+    /// Decodes the characters in front of the suffix in `row` into `out` back to front
+    fn decode(&self, mut row: usize, out: &mut [u8]) -> Option<()> {
+        for c in out.iter_mut().rev() {
+            *c = self.row_char(row)?;
+            row = self.next[row].as_index();
+        }
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sais::suffix_array;
+
+    #[test]
+    fn test_bwt() {
+        assert_eq!(bwt(b""), (vec![], 0));
+        assert_eq!(bwt(b"banana"), (b"annbaa".to_vec(), 4));
+        assert_eq!(bwt(b"mississippi"), (b"ipssmpissii".to_vec(), 5));
+        let mut transformed = b"abracadabra".to_vec();
+        assert_eq!(bwt_inplace(&mut transformed), 3);
+        assert_eq!(transformed, b"ardrcaaaabb");
+        for text in [&b"a"[..], b"aaaa", b"abababab", b"yabbadabbado mississippi"] {
+            let suffix_array = suffix_array::<u32>(text);
+            let expected =
+                bwt_from_suffix_array(text, suffix_array.iter().map(|&suffix| suffix as usize));
+            assert_eq!(bwt(text), expected);
+        }
+    }
+
+    #[test]
+    fn test_unbwt() {
+        for text in [
+            &b""[..],
+            b"a",
+            b"banana",
+            b"mississippi",
+            b"aaaa",
+            b"abcabcab",
+        ] {
+            let (transformed, primary) = bwt(text);
+            assert_eq!(unbwt(&transformed, primary), text);
+        }
+        assert_eq!(try_unbwt(b"annbaa", 7), None);
+        assert_eq!(try_unbwt(b"", 1), None);
+        // the row of the end marker is reached before the first character
+        assert_eq!(try_unbwt(b"ab", 1), None);
+    }
+
+    #[test]
+    fn test_bwt_aux() {
+        assert_eq!(bwt_aux(b"banana", 2), (b"annbaa".to_vec(), vec![4, 6, 5]));
+        assert_eq!(bwt_aux(b"", 4), (vec![], vec![]));
+        let text = b"the quick brown fox jumps over the lazy dog".repeat(20);
+        for rate in [1, 2, 3, 16, 1000] {
+            let (transformed, aux) = bwt_aux(&text, rate);
+            assert_eq!((transformed.clone(), aux[0]), bwt(&text));
+            assert_eq!(unbwt_aux(&transformed, &aux, rate), text);
+        }
+        assert_eq!(try_unbwt_aux(b"annbaa", &[4, 6], 2), None);
+    }
+}
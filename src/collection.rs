@@ -0,0 +1,156 @@
+use crate::fm::{Builder, FmIndex, Sampling};
+use crate::rindex::RIndex;
+use crate::sais::suffix_array;
+use crate::suffix_index::SuffixIndex;
+
+/// The average run length of the transform from which a collection is indexed with an [`RIndex`]
+pub const MIN_AVERAGE_RUN: usize = 8;
+
+/// The representation chosen by a [`CollectionIndex`]
+#[derive(Debug, Clone)]
+pub enum Representation<I> {
+    /// For highly repetitive collections like many genomes of one species
+    RIndex(RIndex<I>),
+    /// For all other collections, sampled in text order
+    FmIndex(FmIndex<I>),
+}
+
+/// Full-text index of a collection of sequences that picks its representation from how
+/// repetitive the collection is.
+///
+/// The sequences are concatenated with a `0` separator, which patterns must not contain. If the
+/// runs of the transform are on average at least [`MIN_AVERAGE_RUN`] long the collection is
+/// indexed with an [`RIndex`], whose memory grows with the number of runs. Otherwise it is
+/// indexed with an [`FmIndex`] that samples as many suffixes as an r-index would, two per run.
+#[derive(Debug, Clone)]
+pub struct CollectionIndex<I> {
+    representation: Representation<I>,
+    /// The position of the first character of every sequence in the concatenation
+    starts: Vec<usize>,
+}
+
+impl<I: SuffixIndex> CollectionIndex<I> {
+    pub fn new<S: AsRef<[u8]>>(sequences: &[S]) -> Self {
+        let mut text = Vec::new();
+        let mut starts = Vec::with_capacity(sequences.len());
+        for sequence in sequences {
+            if !starts.is_empty() {
+                text.push(0);
+            }
+            starts.push(text.len());
+            text.extend_from_slice(sequence.as_ref());
+        }
+
+        // The runs of the transform are the runs of the characters preceding the sorted suffixes
+        let suffix_array = suffix_array::<I>(&text);
+        let preceding = suffix_array
+            .iter()
+            .map(|suffix| suffix.as_index().checked_sub(1).map(|i| text[i]));
+        let runs = 1 + preceding
+            .clone()
+            .zip(preceding.skip(1))
+            .filter(|(a, b)| a != b)
+            .count();
+        let average_run = text.len() / runs;
+        let representation = if average_run >= MIN_AVERAGE_RUN {
+            Representation::RIndex(RIndex::from_suffix_array(&text, &suffix_array))
+        } else {
+            let mut builder = Builder::new();
+            builder
+                .sampling(Sampling::TextOrder)
+                .rate((average_run / 2).max(1));
+            Representation::FmIndex(builder.build_from_suffix_array(&text, &suffix_array))
+        };
+        Self {
+            representation,
+            starts,
+        }
+    }
+
+    pub fn representation(&self) -> &Representation<I> {
+        &self.representation
+    }
+
+    /// The number of sequences
+    pub fn sequences(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// The number of occurrences of `pattern`
+    pub fn count(&self, pattern: &[u8]) -> usize {
+        match &self.representation {
+            Representation::RIndex(index) => index.count(pattern),
+            Representation::FmIndex(index) => index.count(pattern),
+        }
+    }
+
+    /// Returns the sequence and the offset in it of every occurrence of `pattern` in ascending
+    /// order
+    pub fn locate(&self, pattern: &[u8]) -> Vec<(usize, usize)> {
+        let mut positions = match &self.representation {
+            Representation::RIndex(index) => index.locate(pattern),
+            Representation::FmIndex(index) => index.locate(pattern).collect(),
+        };
+        positions.sort_unstable();
+        positions
+            .into_iter()
+            .map(|position| {
+                let sequence = self.starts.partition_point(|&start| start <= position) - 1;
+                (sequence, position - self.starts[sequence])
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn naive(sequences: &[Vec<u8>], pattern: &[u8]) -> Vec<(usize, usize)> {
+        let mut positions = Vec::new();
+        for (index, sequence) in sequences.iter().enumerate() {
+            for offset in 0..sequence.len() {
+                if sequence[offset..].starts_with(pattern) {
+                    positions.push((index, offset));
+                }
+            }
+        }
+        positions
+    }
+
+    #[test]
+    fn test_collection_index() {
+        let mut state = 5u32;
+        let mut random = || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as usize
+        };
+        let reference = (0..500).map(|_| b"ACGT"[random() % 4]).collect::<Vec<_>>();
+        let genomes = (0..20)
+            .map(|_| {
+                let mut genome = reference.clone();
+                let mutation = random() % genome.len();
+                genome[mutation] = b"ACGT"[random() % 4];
+                genome
+            })
+            .collect::<Vec<_>>();
+        let unrelated = (0..20)
+            .map(|_| (0..50).map(|_| b"ACGT"[random() % 4]).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        let index = CollectionIndex::<u32>::new(&genomes);
+        assert!(matches!(index.representation(), Representation::RIndex(_)));
+        let other = CollectionIndex::<u32>::new(&unrelated);
+        assert!(matches!(other.representation(), Representation::FmIndex(_)));
+        assert_eq!(index.sequences(), 20);
+        for (index, sequences) in [(&index, &genomes), (&other, &unrelated)] {
+            for pattern in [&reference[100..108], &reference[..3], b"ACGTACGTACGT", b"A"] {
+                let expected = naive(sequences, pattern);
+                assert_eq!(index.count(pattern), expected.len());
+                assert_eq!(index.locate(pattern), expected);
+            }
+        }
+    }
+}
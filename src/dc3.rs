@@ -0,0 +1,151 @@
+use crate::alphabet::ranks;
+use crate::sorter::SuffixSorter;
+use crate::suffix_index::{AsIndex, SuffixIndex};
+
+/// The difference cover modulo 3 algorithm (Kärkkäinen and Sanders, 2003) for integer alphabets,
+/// the characters are replaced by their ranks first
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Dc3;
+
+impl SuffixSorter for Dc3 {
+    fn sort<C: AsIndex + Ord, I: SuffixIndex>(&self, text: &[C], suffix_array: &mut [I]) {
+        assert_eq!(text.len(), suffix_array.len());
+        assert!(text.len() < I::MAX, "text is too large for the index type");
+        let (mut ranks, alphabet) = ranks::<_, usize>(text);
+        for rank in ranks.iter_mut() {
+            *rank += 1;
+        }
+        for (suffix, sorted) in suffix_array.iter_mut().zip(dc3(&ranks, alphabet.len())) {
+            *suffix = I::from_index(sorted);
+        }
+    }
+}
+
+/// Sorts `values` into `sorted` by `key` in `0..=max` with a counting sort
+fn radix_pass<K: Fn(usize) -> usize>(values: &[usize], sorted: &mut [usize], key: K, max: usize) {
+    let mut counts = vec![0; max + 1];
+    for &value in values {
+        counts[key(value)] += 1;
+    }
+    let mut sum = 0;
+    for count in counts.iter_mut() {
+        sum += std::mem::replace(count, sum);
+    }
+    for &value in values {
+        let count = &mut counts[key(value)];
+        sorted[*count] = value;
+        *count += 1;
+    }
+}
+
+/// The suffix array of `text` with characters in `1..=max`
+fn dc3(text: &[usize], max: usize) -> Vec<usize> {
+    let n = text.len();
+    if n <= 1 {
+        return (0..n).collect();
+    }
+    let (n0, n1, n2) = (n.div_ceil(3), (n + 1) / 3, n / 3);
+    let n02 = n0 + n2;
+    let t = |i: usize| text.get(i).copied().unwrap_or(0);
+
+    // Sort the suffixes at positions 1 and 2 modulo 3 by their first three characters, with a
+    // dummy suffix at `n` if `n % 3 == 1`
+    let mut s12 = (0..n + n0 - n1)
+        .filter(|i| i % 3 != 0)
+        .chain([0; 3])
+        .collect::<Vec<_>>();
+    let mut sa12 = vec![0; n02 + 3];
+    radix_pass(&s12[..n02], &mut sa12, |i| t(i + 2), max);
+    radix_pass(&sa12[..n02], &mut s12, |i| t(i + 1), max);
+    radix_pass(&s12[..n02], &mut sa12, t, max);
+
+    // Name the triples, the names of the suffixes at 1 modulo 3 precede the ones at 2 modulo 3
+    let mut name = 0;
+    let mut last = None;
+    for &i in &sa12[..n02] {
+        let triple = (t(i), t(i + 1), t(i + 2));
+        if last != Some(triple) {
+            name += 1;
+            last = Some(triple);
+        }
+        let index = if i % 3 == 1 { i / 3 } else { i / 3 + n0 };
+        s12[index] = name;
+    }
+
+    if name < n02 {
+        let sorted = dc3(&s12[..n02], name);
+        sa12[..n02].copy_from_slice(&sorted);
+        for (i, &suffix) in sorted.iter().enumerate() {
+            s12[suffix] = i + 1;
+        }
+    } else {
+        for i in 0..n02 {
+            sa12[s12[i] - 1] = i;
+        }
+    }
+
+    // Sort the suffixes at 0 modulo 3 by their first character and the rank of the next suffix
+    let s0 = sa12[..n02]
+        .iter()
+        .filter(|&&i| i < n0)
+        .map(|&i| 3 * i)
+        .collect::<Vec<_>>();
+    let mut sa0 = vec![0; n0];
+    radix_pass(&s0, &mut sa0, t, max);
+
+    // Merge both, skipping the dummy suffix
+    let position = |i: usize| {
+        if sa12[i] < n0 {
+            sa12[i] * 3 + 1
+        } else {
+            (sa12[i] - n0) * 3 + 2
+        }
+    };
+    let mut suffix_array = Vec::with_capacity(n);
+    let (mut p, mut q) = (0, n0 - n1);
+    while p < n0 && q < n02 {
+        let (i, j) = (position(q), sa0[p]);
+        let smaller = if sa12[q] < n0 {
+            (t(i), s12[sa12[q] + n0]) <= (t(j), s12[j / 3])
+        } else {
+            (t(i), t(i + 1), s12[sa12[q] - n0 + 1]) <= (t(j), t(j + 1), s12[j / 3 + n0])
+        };
+        if smaller {
+            suffix_array.push(i);
+            q += 1;
+        } else {
+            suffix_array.push(j);
+            p += 1;
+        }
+    }
+    suffix_array.extend_from_slice(&sa0[p..]);
+    suffix_array.extend((q..n02).map(position));
+    suffix_array
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::doubling::PrefixDoubling;
+
+    #[test]
+    fn test_dc3() {
+        assert_eq!(Dc3.suffix_array::<_, u32>(b"banana"), [5, 3, 1, 0, 4, 2]);
+        assert_eq!(Dc3.suffix_array::<u8, u32>(b""), []);
+        let mut state = 13u32;
+        for len in 1..200 {
+            let text = (0..len)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 17;
+                    state ^= state << 5;
+                    state % 4 * 1_000_000
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                Dc3.suffix_array::<_, u32>(&text),
+                PrefixDoubling.suffix_array::<_, u32>(&text)
+            );
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use crate::generalized::GeneralizedSuffixArray;
+use crate::suffix_index::SuffixIndex;
+
+/// A distinct k-mer of the indexed documents
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Node {
+    /// The position of one occurrence
+    pub position: usize,
+    /// The number of occurrences
+    pub count: usize,
+    /// The documents containing the k-mer in ascending order
+    pub colors: Vec<usize>,
+}
+
+/// A distinct (k+1)-mer connecting its prefix and suffix k-mers
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    /// The number of occurrences
+    pub count: usize,
+}
+
+/// Colored de Bruijn graph of order `k` over the documents of a generalized suffix array.
+///
+/// Nodes are numbered in lexicographic order of their k-mers, k-mers never span documents.
+#[derive(Debug, Clone)]
+pub struct DeBruijnGraph<'a, I> {
+    index: &'a GeneralizedSuffixArray<I>,
+    k: usize,
+    nodes: Vec<Node>,
+    /// Sorted by source node
+    edges: Vec<Edge>,
+    /// `edges[edge_starts[node]..edge_starts[node + 1]]` are the outgoing edges of `node`
+    edge_starts: Vec<usize>,
+}
+
+impl<'a, I: SuffixIndex> DeBruijnGraph<'a, I> {
+    pub fn new(index: &'a GeneralizedSuffixArray<I>, k: usize) -> Self {
+        assert_ne!(k, 0);
+        let suffix_array = index.suffix_array();
+        let lcp = index.lcp_array();
+
+        // The k-mers are the groups of suffixes with at least k characters sharing k characters
+        let mut node_of = vec![usize::MAX; index.text().len()];
+        let mut nodes: Vec<Node> = Vec::new();
+        let mut open = false;
+        for (i, suffix) in suffix_array.iter().enumerate() {
+            let position = suffix.as_index();
+            if index.suffix(position).len() < k {
+                open = false;
+                continue;
+            }
+            if !open || lcp[i].as_index() < k {
+                nodes.push(Node {
+                    position,
+                    count: 0,
+                    colors: Vec::new(),
+                });
+                open = true;
+            }
+            let node = nodes.last_mut().unwrap();
+            node.count += 1;
+            node.colors.push(index.document_position(position).0);
+            node_of[position] = nodes.len() - 1;
+        }
+        for node in nodes.iter_mut() {
+            node.colors.sort_unstable();
+            node.colors.dedup();
+        }
+
+        let mut pairs = (0..index.text().len())
+            .filter(|&position| index.suffix(position).len() > k)
+            .map(|position| (node_of[position], node_of[position + 1]))
+            .collect::<Vec<_>>();
+        pairs.sort_unstable();
+        let mut edges: Vec<Edge> = Vec::new();
+        for (from, to) in pairs {
+            match edges.last_mut() {
+                Some(edge) if edge.from == from && edge.to == to => edge.count += 1,
+                _ => edges.push(Edge { from, to, count: 1 }),
+            }
+        }
+
+        let mut edge_starts = vec![0; nodes.len() + 1];
+        for edge in &edges {
+            edge_starts[edge.from + 1] += 1;
+        }
+        for i in 1..edge_starts.len() {
+            edge_starts[i] += edge_starts[i - 1];
+        }
+
+        Self {
+            index,
+            k,
+            nodes,
+            edges,
+            edge_starts,
+        }
+    }
+
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    /// The k-mer of `node`
+    pub fn label(&self, node: usize) -> &'a [u8] {
+        let position = self.nodes[node].position;
+        &self.index.text()[position..position + self.k]
+    }
+
+    pub fn successors(&self, node: usize) -> &[Edge] {
+        &self.edges[self.edge_starts[node]..self.edge_starts[node + 1]]
+    }
+
+    /// Returns the node of `kmer` if it occurs
+    pub fn find(&self, kmer: &[u8]) -> Option<usize> {
+        if kmer.len() != self.k {
+            return None;
+        }
+        self.nodes
+            .binary_search_by(|node| {
+                self.index.text()[node.position..node.position + self.k].cmp(kmer)
+            })
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_de_bruijn_graph() {
+        let documents: &[&[u8]] = &[b"ACGTAC", b"GTACC"];
+        let index = GeneralizedSuffixArray::<u32>::new(documents);
+        let graph = DeBruijnGraph::new(&index, 2);
+
+        let labels = (0..graph.nodes().len())
+            .map(|node| graph.label(node))
+            .collect::<Vec<_>>();
+        assert_eq!(labels, vec![&b"AC"[..], b"CC", b"CG", b"GT", b"TA"]);
+
+        let ac = graph.find(b"AC").unwrap();
+        assert_eq!(graph.nodes()[ac].count, 3);
+        assert_eq!(graph.nodes()[ac].colors, vec![0, 1]);
+        assert_eq!(graph.nodes()[graph.find(b"CG").unwrap()].colors, vec![0]);
+        assert_eq!(graph.find(b"AA"), None);
+
+        let successors = graph
+            .successors(ac)
+            .iter()
+            .map(|edge| (graph.label(edge.to), edge.count))
+            .collect::<Vec<_>>();
+        assert_eq!(successors, vec![(&b"CC"[..], 1), (b"CG", 1)]);
+        let edge_count = graph.edges().iter().map(|edge| edge.count).sum::<usize>();
+        assert_eq!(edge_count, (6 - 2) + (5 - 2));
+    }
+}
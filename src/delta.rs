@@ -0,0 +1,354 @@
+use std::convert::TryFrom;
+use std::io;
+
+use crate::sais::suffix_array;
+
+/// Approximate matches are only left for a new exact match that is this much better
+const MIN_IMPROVEMENT: usize = 8;
+
+/// A step of a [`Patch`].
+///
+/// `add` bytes of the old file are copied with the diff bytes added, `copy` extra bytes are
+/// inserted, then the old file position is moved by `seek`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Control {
+    pub add: usize,
+    pub copy: usize,
+    pub seek: isize,
+}
+
+/// A bsdiff-style binary delta from an old file to a new file
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Patch {
+    controls: Vec<Control>,
+    diff: Vec<u8>,
+    extra: Vec<u8>,
+}
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+/// The position and length of the longest prefix of `pattern` occurring in `text`
+fn longest_match(text: &[u8], suffix_array: &[usize], pattern: &[u8]) -> (usize, usize) {
+    let i = suffix_array.partition_point(|&suffix| &text[suffix..] < pattern);
+    [i.checked_sub(1), (i < suffix_array.len()).then_some(i)]
+        .iter()
+        .flatten()
+        .map(|&i| {
+            let suffix = suffix_array[i];
+            (suffix, common_prefix(&text[suffix..], pattern))
+        })
+        .max_by_key(|&(_, len)| len)
+        .unwrap_or((0, 0))
+}
+
+/// Computes the delta from `old` to `new`.
+///
+/// Long matches of the new file are searched in the suffix array of the old file and extended to
+/// approximate matches, the differences of approximate matches are mostly zero bytes.
+pub fn diff(old: &[u8], new: &[u8]) -> Patch {
+    let suffix_array = suffix_array::<usize>(old);
+    let matches_at = |old_position: isize, new_position: usize| {
+        old_position >= 0
+            && (old_position as usize) < old.len()
+            && old[old_position as usize] == new[new_position]
+    };
+
+    let mut patch = Patch {
+        controls: Vec::new(),
+        diff: Vec::new(),
+        extra: Vec::new(),
+    };
+    let (mut scan, mut len, mut position) = (0, 0, 0);
+    let (mut last_scan, mut last_position, mut last_offset) = (0, 0, 0isize);
+    while scan < new.len() {
+        // Find the next exact match that is considerably better than continuing the last one
+        let mut old_score = 0;
+        scan += len;
+        let mut scored = scan;
+        while scan < new.len() {
+            (position, len) = longest_match(old, &suffix_array, &new[scan..]);
+            while scored < scan + len {
+                if matches_at(scored as isize + last_offset, scored) {
+                    old_score += 1;
+                }
+                scored += 1;
+            }
+            if (len == old_score && len != 0) || len > old_score + MIN_IMPROVEMENT {
+                break;
+            }
+            if matches_at(scan as isize + last_offset, scan) {
+                old_score -= 1;
+            }
+            scan += 1;
+        }
+
+        if len == old_score && scan != new.len() {
+            continue;
+        }
+
+        // Extend the last match forwards and the new match backwards as long as most bytes match
+        let (mut score, mut best, mut forward) = (0isize, 0isize, 0);
+        let mut i = 0;
+        while last_scan + i < scan && last_position + i < old.len() {
+            if old[last_position + i] == new[last_scan + i] {
+                score += 1;
+            }
+            i += 1;
+            if score * 2 - i as isize > best * 2 - forward as isize {
+                best = score;
+                forward = i;
+            }
+        }
+
+        let mut backward = 0;
+        if scan < new.len() {
+            let (mut score, mut best) = (0isize, 0isize);
+            let mut i = 1;
+            while scan >= last_scan + i && position >= i {
+                if old[position - i] == new[scan - i] {
+                    score += 1;
+                }
+                if score * 2 - i as isize > best * 2 - backward as isize {
+                    best = score;
+                    backward = i;
+                }
+                i += 1;
+            }
+        }
+
+        // Split overlapping extensions where the most bytes match
+        if last_scan + forward > scan - backward {
+            let overlap = (last_scan + forward) - (scan - backward);
+            let (mut score, mut best, mut split) = (0isize, 0isize, 0);
+            for i in 0..overlap {
+                if new[last_scan + forward - overlap + i]
+                    == old[last_position + forward - overlap + i]
+                {
+                    score += 1;
+                }
+                if new[scan - backward + i] == old[position - backward + i] {
+                    score -= 1;
+                }
+                if score > best {
+                    best = score;
+                    split = i + 1;
+                }
+            }
+            forward = forward + split - overlap;
+            backward -= split;
+        }
+
+        patch
+            .diff
+            .extend((0..forward).map(|i| new[last_scan + i].wrapping_sub(old[last_position + i])));
+        patch
+            .extra
+            .extend_from_slice(&new[last_scan + forward..scan - backward]);
+        patch.controls.push(Control {
+            add: forward,
+            copy: (scan - backward) - (last_scan + forward),
+            seek: (position - backward) as isize - (last_position + forward) as isize,
+        });
+
+        last_scan = scan - backward;
+        last_position = position - backward;
+        last_offset = position as isize - scan as isize;
+    }
+    patch
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes
+            .split_first()
+            .ok_or_else(|| invalid_data("truncated patch"))?;
+        *bytes = rest;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("varint overflow"))
+}
+
+fn read_len(bytes: &mut &[u8]) -> io::Result<usize> {
+    usize::try_from(read_varint(bytes)?).map_err(|_| invalid_data("length overflow"))
+}
+
+fn read_bytes<'a>(bytes: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    if bytes.len() < len {
+        return Err(invalid_data("truncated patch"));
+    }
+    let (read, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(read)
+}
+
+const MAGIC: &[u8] = b"SAISDIFF";
+
+impl Patch {
+    pub fn controls(&self) -> &[Control] {
+        &self.controls
+    }
+
+    /// The length of the new file
+    pub fn new_len(&self) -> usize {
+        self.diff.len() + self.extra.len()
+    }
+
+    /// Reconstructs the new file from `old`, fails if the patch does not fit `old`
+    pub fn apply(&self, old: &[u8]) -> io::Result<Vec<u8>> {
+        let mut new = Vec::with_capacity(self.new_len());
+        let (mut diff, mut extra) = (&self.diff[..], &self.extra[..]);
+        let mut position = 0isize;
+        for control in &self.controls {
+            let start = usize::try_from(position)
+                .ok()
+                .filter(|&start| start + control.add <= old.len())
+                .ok_or_else(|| invalid_data("patch does not match the old file"))?;
+            let (added, rest) = diff.split_at(control.add);
+            diff = rest;
+            new.extend(
+                old[start..start + control.add]
+                    .iter()
+                    .zip(added)
+                    .map(|(&old, &diff)| old.wrapping_add(diff)),
+            );
+            let (copied, rest) = extra.split_at(control.copy);
+            extra = rest;
+            new.extend_from_slice(copied);
+            position += control.add as isize + control.seek;
+        }
+        Ok(new)
+    }
+
+    /// Serializes the patch.
+    ///
+    /// Integers are stored as varints and the diff bytes as runs of zeros and literals.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        write_varint(&mut out, self.controls.len() as u64);
+        for control in &self.controls {
+            write_varint(&mut out, control.add as u64);
+            write_varint(&mut out, control.copy as u64);
+            // zigzag encoding
+            write_varint(
+                &mut out,
+                ((control.seek << 1) ^ (control.seek >> (isize::BITS - 1))) as u64,
+            );
+        }
+
+        let mut diff = &self.diff[..];
+        while !diff.is_empty() {
+            let zeros = diff.iter().take_while(|&&c| c == 0).count();
+            let literals = diff[zeros..].iter().take_while(|&&c| c != 0).count();
+            write_varint(&mut out, zeros as u64);
+            write_varint(&mut out, literals as u64);
+            out.extend_from_slice(&diff[zeros..zeros + literals]);
+            diff = &diff[zeros + literals..];
+        }
+        out.extend_from_slice(&self.extra);
+        out
+    }
+
+    /// Deserializes a patch written by [`Self::encode`]
+    pub fn decode(mut bytes: &[u8]) -> io::Result<Self> {
+        if read_bytes(&mut bytes, MAGIC.len())? != MAGIC {
+            return Err(invalid_data("not a patch"));
+        }
+        let count = read_len(&mut bytes)?;
+        let mut controls = Vec::new();
+        let (mut diff_len, mut extra_len) = (0usize, 0usize);
+        for _ in 0..count {
+            let add = read_len(&mut bytes)?;
+            let copy = read_len(&mut bytes)?;
+            let seek = read_varint(&mut bytes)?;
+            let seek = ((seek >> 1) as isize) ^ -((seek & 1) as isize);
+            diff_len = diff_len
+                .checked_add(add)
+                .ok_or_else(|| invalid_data("length overflow"))?;
+            extra_len = extra_len
+                .checked_add(copy)
+                .ok_or_else(|| invalid_data("length overflow"))?;
+            controls.push(Control { add, copy, seek });
+        }
+
+        let mut diff = Vec::new();
+        while diff.len() < diff_len {
+            let zeros = read_len(&mut bytes)?;
+            let literals = read_len(&mut bytes)?;
+            if zeros.saturating_add(literals) > diff_len - diff.len() {
+                return Err(invalid_data("diff bytes exceed controls"));
+            }
+            diff.resize(diff.len() + zeros, 0);
+            diff.extend_from_slice(read_bytes(&mut bytes, literals)?);
+        }
+        let extra = read_bytes(&mut bytes, extra_len)?.to_vec();
+        if !bytes.is_empty() {
+            return Err(invalid_data("trailing bytes after patch"));
+        }
+        Ok(Self {
+            controls,
+            diff,
+            extra,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn round_trip(old: &[u8], new: &[u8]) -> Vec<u8> {
+        let encoded = diff(old, new).encode();
+        let patch = Patch::decode(&encoded).unwrap();
+        assert_eq!(patch.apply(old).unwrap(), new);
+        encoded
+    }
+
+    #[test]
+    fn test_round_trip() {
+        round_trip(b"", b"");
+        round_trip(b"", b"new file");
+        round_trip(b"old file", b"");
+        round_trip(b"abcabcabc", b"abcabcabc");
+        round_trip(b"the quick brown fox", b"the quick brown dog jumps");
+        round_trip(b"aaaaaaaaaa", b"bbbbbaaaaabbbbb");
+    }
+
+    #[test]
+    fn test_compact() {
+        // an executable-like file with shifted offsets
+        let old = (0..4000u32)
+            .flat_map(|i| (i * 7).to_le_bytes())
+            .collect::<Vec<_>>();
+        let mut new = b"header".to_vec();
+        new.extend((0..4000u32).flat_map(|i| (i * 7 + (i % 64 == 0) as u32).to_le_bytes()));
+        let encoded = round_trip(&old, &new);
+        assert!(encoded.len() < new.len() / 10);
+    }
+
+    #[test]
+    fn test_corrupt() {
+        let encoded = diff(b"abcdefgh", b"abcxefgh").encode();
+        assert!(Patch::decode(&encoded[..encoded.len() - 1]).is_err());
+        assert!(Patch::decode(b"garbage").is_err());
+        let patch = Patch::decode(&encoded).unwrap();
+        assert!(patch.apply(b"abc").is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use std::cmp::Ordering;
+
+use crate::doubling::prefix_doubling;
+use crate::sais::{induce_from_lms, Type, Types};
+use crate::sorter::SuffixSorter;
+use crate::suffix_index::{AsIndex, SuffixIndex};
+
+/// Two-stage sorting like divsufsort (Mori, 2008): the LMS substrings are sorted by comparison,
+/// the suffixes of their names by prefix doubling instead of recursion, all other suffixes are
+/// induced from the sorted LMS suffixes.
+///
+/// Fast for texts without long repeats, whose LMS substrings differ after a few characters.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct DivSufSort;
+
+impl SuffixSorter for DivSufSort {
+    fn sort<C: AsIndex + Ord, I: SuffixIndex>(&self, text: &[C], suffix_array: &mut [I]) {
+        assert_eq!(text.len(), suffix_array.len());
+        assert!(text.len() < I::MAX, "text is too large for the index type");
+        if text.is_empty() {
+            return;
+        }
+        let types = Types::classify(text);
+        let lms = (1..text.len())
+            .filter(|&i| types.get(i - 1) == Type::L && types.get(i) == Type::S)
+            .collect::<Vec<_>>();
+
+        let mut order = (0..lms.len()).collect::<Vec<_>>();
+        let compare = |&a: &usize, &b: &usize| compare_lms_substrings(text, &lms, a, b);
+        order.sort_unstable_by(compare);
+        let mut names = vec![0; lms.len()];
+        let mut name = 0;
+        for i in 1..order.len() {
+            if compare(&order[i - 1], &order[i]) != Ordering::Equal {
+                name += 1;
+            }
+            names[order[i]] = name;
+        }
+        if name + 1 < lms.len() {
+            order = prefix_doubling(&names);
+        }
+
+        for (suffix, &i) in suffix_array.iter_mut().zip(&order) {
+            *suffix = I::from_index(lms[i]);
+        }
+        let sigma = text.iter().map(AsIndex::as_index).max().unwrap() + 1;
+        let mut buckets = vec![I::from_index(0); sigma];
+        induce_from_lms(text, &types, suffix_array, lms.len(), &mut buckets);
+    }
+}
+
+/// Compares the LMS substrings starting at `lms[a]` and `lms[b]` including the next LMS
+/// character, the last one ends with the text.
+///
+/// A substring that is a proper prefix of another one ends with an S suffix where the other one
+/// continues with an L suffix and is larger, unless it ends with the text.
+fn compare_lms_substrings<C: Ord>(text: &[C], lms: &[usize], a: usize, b: usize) -> Ordering {
+    let substring = |i: usize| match lms.get(i + 1) {
+        Some(&end) => (&text[lms[i]..=end], false),
+        None => (&text[lms[i]..], true),
+    };
+    let ((left, left_last), (right, right_last)) = (substring(a), substring(b));
+    let common = left.len().min(right.len());
+    left[..common]
+        .cmp(&right[..common])
+        .then_with(|| match left.len().cmp(&right.len()) {
+            Ordering::Equal if left_last => Ordering::Less,
+            Ordering::Equal if right_last => Ordering::Greater,
+            Ordering::Equal => Ordering::Equal,
+            Ordering::Less if left_last => Ordering::Less,
+            Ordering::Less => Ordering::Greater,
+            Ordering::Greater if right_last => Ordering::Greater,
+            Ordering::Greater => Ordering::Less,
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sais::suffix_array;
+    use crate::sorter::Sais;
+
+    #[test]
+    fn test_div_suf_sort() {
+        let mut state = 5u32;
+        let random = (0..3000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                b"abc"[state as usize % 3]
+            })
+            .collect::<Vec<_>>();
+        let mut repeats = random[..500].repeat(4);
+        repeats.push(b'a');
+        for text in [
+            &b"mississippi"[..],
+            b"a",
+            b"aaaaaa",
+            b"abab",
+            b"cba",
+            &random,
+            &repeats,
+        ]
+        .iter()
+        {
+            let sorted: Vec<u32> = DivSufSort.suffix_array(text);
+            assert_eq!(sorted, suffix_array::<u32>(text));
+        }
+        let text = [5u32, 1_000_000, 5, 1_000_000, 3];
+        assert_eq!(
+            DivSufSort.suffix_array::<_, u32>(&text),
+            Sais.suffix_array::<_, u32>(&text)
+        );
+    }
+}
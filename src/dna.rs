@@ -0,0 +1,313 @@
+use std::ops::Range;
+
+use crate::generalized::{Builder, GeneralizedSuffixArray};
+use crate::suffix_index::SuffixIndex;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Strand {
+    Forward,
+    Reverse,
+}
+
+/// An occurrence of a pattern.
+///
+/// `position` is always the forward strand start of the matched region, a reverse hit means the
+/// reverse complement of the pattern occurs there.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Hit {
+    pub position: usize,
+    pub strand: Strand,
+}
+
+/// How ambiguity codes (everything but `ACGTU`) are handled, fixed when the index is built
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Ambiguity {
+    /// Ambiguity codes are ordinary symbols that only match themselves
+    Symbol,
+    /// Runs of ambiguity codes split the sequences, no match spans them.
+    /// Patterns containing ambiguity codes never match.
+    Separator,
+    /// Ambiguity codes in patterns match every base they stand for and themselves, every
+    /// character of a pattern matches in both cases.
+    ///
+    /// The search branches into every alternative of a position, ten for `N` (`ACGTN` in both
+    /// cases), and takes time exponential in the number of ambiguity codes in the pattern.
+    Wildcard,
+}
+
+pub fn is_ambiguous(c: u8) -> bool {
+    !matches!(c.to_ascii_uppercase(), b'A' | b'C' | b'G' | b'T' | b'U')
+}
+
+/// The bases represented by a IUPAC code, unknown characters represent only themselves
+pub fn bases(code: u8) -> &'static [u8] {
+    match code.to_ascii_uppercase() {
+        b'A' => b"A",
+        b'C' => b"C",
+        b'G' => b"G",
+        b'T' => b"T",
+        b'U' => b"U",
+        b'R' => b"AG",
+        b'Y' => b"CT",
+        b'S' => b"CG",
+        b'W' => b"AT",
+        b'K' => b"GT",
+        b'M' => b"AC",
+        b'B' => b"CGT",
+        b'D' => b"AGT",
+        b'H' => b"ACT",
+        b'V' => b"ACG",
+        b'N' => b"ACGT",
+        _ => &[],
+    }
+}
+
+/// Index over nucleotide sequences searching both strands with a fixed [`Ambiguity`] policy.
+///
+/// Positions refer to the concatenation of the indexed sequences. Patterns are case sensitive
+/// unless ambiguity codes are wildcards.
+#[derive(Debug, Clone)]
+pub struct DnaIndex<I> {
+    index: GeneralizedSuffixArray<I>,
+    /// The position of the first character of every document of `index`
+    origins: Vec<usize>,
+    ambiguity: Ambiguity,
+}
+
+impl<I: SuffixIndex> DnaIndex<I> {
+    pub fn new<D: AsRef<[u8]>>(sequences: &[D], ambiguity: Ambiguity) -> Self {
+        let mut builder = Builder::new();
+        let mut origins = Vec::new();
+        let mut offset = 0;
+        for sequence in sequences {
+            let sequence = sequence.as_ref();
+            if ambiguity == Ambiguity::Separator {
+                let mut start = 0;
+                for fragment in sequence.split(|&c| is_ambiguous(c)) {
+                    if !fragment.is_empty() {
+                        builder.push(fragment);
+                        origins.push(offset + start);
+                    }
+                    start += fragment.len() + 1;
+                }
+            } else {
+                builder.push(sequence);
+                origins.push(offset);
+            }
+            offset += sequence.len();
+        }
+
+        Self {
+            index: builder.build(),
+            origins,
+            ambiguity,
+        }
+    }
+
+    pub fn index(&self) -> &GeneralizedSuffixArray<I> {
+        &self.index
+    }
+
+    pub fn ambiguity(&self) -> Ambiguity {
+        self.ambiguity
+    }
+
+    fn original_position(&self, position: usize) -> usize {
+        let (document, offset) = self.index.document_position(position);
+        self.origins[document] + offset
+    }
+
+    fn wildcard_intervals(
+        &self,
+        pattern: &[u8],
+        depth: usize,
+        interval: Range<usize>,
+        intervals: &mut Vec<Range<usize>>,
+    ) {
+        if interval.is_empty() {
+            return;
+        }
+        let c = match pattern.get(depth) {
+            Some(&c) => c,
+            None => {
+                intervals.push(interval);
+                return;
+            }
+        };
+        let mut alternatives = bases(c)
+            .iter()
+            .chain(Some(&c))
+            .flat_map(|&c| [c.to_ascii_uppercase(), c.to_ascii_lowercase()])
+            .collect::<Vec<_>>();
+        alternatives.sort_unstable();
+        alternatives.dedup();
+        for alternative in alternatives {
+            let refined = self.index.refine(interval.clone(), depth, alternative);
+            self.wildcard_intervals(pattern, depth + 1, refined, intervals);
+        }
+    }
+
+    fn positions(&self, pattern: &[u8]) -> Vec<usize> {
+        let intervals = match self.ambiguity {
+            Ambiguity::Symbol => vec![self.index.find(pattern)],
+            Ambiguity::Separator if pattern.iter().any(|&c| is_ambiguous(c)) => vec![],
+            Ambiguity::Separator => vec![self.index.find(pattern)],
+            Ambiguity::Wildcard => {
+                let mut intervals = Vec::new();
+                let all = 0..self.index.suffix_array().len();
+                self.wildcard_intervals(pattern, 0, all, &mut intervals);
+                intervals
+            }
+        };
+        intervals
+            .into_iter()
+            .flat_map(|interval| self.index.suffix_array()[interval].iter())
+            .map(|suffix| self.original_position(suffix.as_index()))
+            .collect()
+    }
+
+    /// Locates `pattern` on both strands, hits are sorted by position, forward before reverse
+    pub fn locate(&self, pattern: &[u8]) -> Vec<Hit> {
+        locate_strands(pattern, |pattern| self.positions(pattern))
+    }
+}
+
+/// The hits of `pattern` and of its reverse complement with their positions from `positions`,
+/// sorted by position, forward before reverse
+fn locate_strands<F: FnMut(&[u8]) -> Vec<usize>>(pattern: &[u8], mut positions: F) -> Vec<Hit> {
+    let forward = positions(pattern).into_iter().map(|position| Hit {
+        position,
+        strand: Strand::Forward,
+    });
+    let reverse = positions(&reverse_complement(pattern))
+        .into_iter()
+        .map(|position| Hit {
+            position,
+            strand: Strand::Reverse,
+        });
+    let mut hits = forward.chain(reverse).collect::<Vec<_>>();
+    hits.sort_unstable();
+    hits
+}
+
+/// Complements a nucleotide including IUPAC ambiguity codes, preserving case.
+/// Unknown characters are returned unchanged.
+pub fn complement(c: u8) -> u8 {
+    let complement = match c.to_ascii_uppercase() {
+        b'A' => b'T',
+        b'T' | b'U' => b'A',
+        b'C' => b'G',
+        b'G' => b'C',
+        b'R' => b'Y',
+        b'Y' => b'R',
+        b'K' => b'M',
+        b'M' => b'K',
+        b'B' => b'V',
+        b'V' => b'B',
+        b'D' => b'H',
+        b'H' => b'D',
+        other => other,
+    };
+    if c.is_ascii_lowercase() {
+        complement.to_ascii_lowercase()
+    } else {
+        complement
+    }
+}
+
+pub fn reverse_complement(sequence: &[u8]) -> Vec<u8> {
+    sequence.iter().rev().map(|&c| complement(c)).collect()
+}
+
+/// Locates `pattern` on both strands of an index built over forward sequences only.
+///
+/// The reverse strand is searched virtually by looking up the reverse complement of the pattern.
+/// Hits are sorted by position, forward before reverse.
+pub fn locate<I: SuffixIndex>(index: &GeneralizedSuffixArray<I>, pattern: &[u8]) -> Vec<Hit> {
+    locate_strands(pattern, |pattern| index.locate(pattern).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reverse_complement() {
+        assert_eq!(reverse_complement(b"ACGTN"), b"NACGT");
+        assert_eq!(reverse_complement(b"aRyK"), b"MrYt");
+    }
+
+    fn forward_positions<I: SuffixIndex>(index: &DnaIndex<I>, pattern: &[u8]) -> Vec<usize> {
+        index
+            .locate(pattern)
+            .into_iter()
+            .filter(|hit| hit.strand == Strand::Forward)
+            .map(|hit| hit.position)
+            .collect()
+    }
+
+    #[test]
+    fn test_ambiguity() {
+        let sequences: &[&[u8]] = &[b"ACGNNGTAC", b"TTANCG"];
+
+        let symbol = DnaIndex::<u32>::new(sequences, Ambiguity::Symbol);
+        assert_eq!(forward_positions(&symbol, b"GNNG"), vec![2]);
+        assert_eq!(forward_positions(&symbol, b"ACGAAG"), vec![]);
+
+        let separator = DnaIndex::<u32>::new(sequences, Ambiguity::Separator);
+        assert_eq!(forward_positions(&separator, b"GNNG"), vec![]);
+        assert_eq!(forward_positions(&separator, b"CG"), vec![1, 13]);
+        assert_eq!(forward_positions(&separator, b"GTAC"), vec![5]);
+        assert_eq!(forward_positions(&separator, b"TAC"), vec![6]);
+
+        let wildcard = DnaIndex::<u32>::new(sequences, Ambiguity::Wildcard);
+        assert_eq!(forward_positions(&wildcard, b"GNNG"), vec![2]);
+        assert_eq!(forward_positions(&wildcard, b"TRC"), vec![6]);
+        assert_eq!(forward_positions(&wildcard, b"NCG"), vec![0, 12]);
+        let mixed = DnaIndex::<u32>::new(&[b"acgtACGTnN"], Ambiguity::Wildcard);
+        assert_eq!(forward_positions(&mixed, b"ANGT"), vec![0, 4]);
+        assert_eq!(forward_positions(&mixed, b"n"), (0..10).collect::<Vec<_>>());
+        assert_eq!(forward_positions(&mixed, b"tAc"), vec![3]);
+        assert_eq!(
+            wildcard.locate(b"GTR"),
+            vec![
+                Hit {
+                    position: 5,
+                    strand: Strand::Forward
+                },
+                Hit {
+                    position: 6,
+                    strand: Strand::Reverse
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_locate() {
+        let documents: &[&[u8]] = &[b"AACCGT", b"TTGG"];
+        let index = GeneralizedSuffixArray::<u32>::new(documents);
+        let hits = locate(&index, b"CCG");
+        assert_eq!(
+            hits,
+            vec![Hit {
+                position: 2,
+                strand: Strand::Forward
+            }]
+        );
+
+        let hits = locate(&index, b"CCAA");
+        assert_eq!(
+            hits,
+            vec![Hit {
+                position: 6,
+                strand: Strand::Reverse
+            }]
+        );
+
+        // palindromic sites match on both strands
+        let hits = locate(&index, b"CG");
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().all(|hit| hit.position == 3));
+    }
+}
@@ -0,0 +1,110 @@
+use crate::sorter::SuffixSorter;
+use crate::suffix_index::{AsIndex, SuffixIndex};
+
+/// Prefix doubling, sorts by comparisons only and needs no buckets for the alphabet, see
+/// [`prefix_doubling`]
+#[derive(Debug, Copy, Clone, Default)]
+pub struct PrefixDoubling;
+
+impl SuffixSorter for PrefixDoubling {
+    fn sort<C: AsIndex + Ord, I: SuffixIndex>(&self, text: &[C], suffix_array: &mut [I]) {
+        assert_eq!(text.len(), suffix_array.len());
+        assert!(text.len() < I::MAX, "text is too large for the index type");
+        for (suffix, sorted) in suffix_array.iter_mut().zip(prefix_doubling(text)) {
+            *suffix = I::from_index(sorted);
+        }
+    }
+}
+
+/// The suffix array of `text` by prefix doubling (Manber and Myers, 1993), only groups of suffixes
+/// with equal prefixes are sorted again (Larsson and Sadakane, 2007)
+pub(crate) fn prefix_doubling<C: Ord>(text: &[C]) -> Vec<usize> {
+    let n = text.len();
+    let mut suffixes = (0..n).collect::<Vec<_>>();
+    suffixes.sort_unstable_by(|&a, &b| text[a].cmp(&text[b]));
+    // The rank of a suffix is the start of its group of suffixes with an equal prefix
+    let mut rank = vec![0; n];
+    let mut groups = Vec::new();
+    let mut start = 0;
+    for i in 1..=n {
+        if i == n || text[suffixes[i]] != text[suffixes[start]] {
+            for &suffix in &suffixes[start..i] {
+                rank[suffix] = start;
+            }
+            if i - start > 1 {
+                groups.push(start..i);
+            }
+            start = i;
+        }
+    }
+
+    let mut len = 1;
+    let mut updates = Vec::new();
+    while !groups.is_empty() {
+        // Suffixes shorter than `len` are the smallest of their group
+        let key = |rank: &[usize], suffix: usize| rank.get(suffix + len).map(|&rank| rank + 1);
+        let mut next_groups = Vec::new();
+        for group in groups {
+            let group_suffixes = &mut suffixes[group.clone()];
+            group_suffixes.sort_unstable_by_key(|&suffix| key(&rank, suffix));
+            let mut start = 0;
+            for i in 1..=group_suffixes.len() {
+                if i == group_suffixes.len()
+                    || key(&rank, group_suffixes[i]) != key(&rank, group_suffixes[start])
+                {
+                    updates.extend(
+                        group_suffixes[start..i]
+                            .iter()
+                            .map(|&suffix| (suffix, group.start + start)),
+                    );
+                    if i - start > 1 {
+                        next_groups.push(group.start + start..group.start + i);
+                    }
+                    start = i;
+                }
+            }
+        }
+        // The keys of all groups are ranks of the previous length
+        for (suffix, new_rank) in updates.drain(..) {
+            rank[suffix] = new_rank;
+        }
+        groups = next_groups;
+        len *= 2;
+    }
+    suffixes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::divsufsort::DivSufSort;
+    use crate::sorter::Sais;
+
+    #[test]
+    fn test_prefix_doubling() {
+        let text = [2, 1, 3, 1, 3, 1, 0];
+        let mut expected = (0..text.len()).collect::<Vec<_>>();
+        expected.sort_by_key(|&i| &text[i..]);
+        assert_eq!(prefix_doubling(&text), expected);
+        assert_eq!(prefix_doubling::<u8>(&[]), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_backends() {
+        let mut state = 11u32;
+        for len in 1..300 {
+            let text = (0..len)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 17;
+                    state ^= state << 5;
+                    // Few characters far apart
+                    (state % 3) * 1000
+                })
+                .collect::<Vec<_>>();
+            let expected: Vec<u32> = PrefixDoubling.suffix_array(&text);
+            assert_eq!(Sais.suffix_array::<_, u32>(&text), expected);
+            assert_eq!(DivSufSort.suffix_array::<_, u32>(&text), expected);
+        }
+    }
+}
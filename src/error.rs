@@ -0,0 +1,55 @@
+use std::error::Error;
+use std::fmt;
+
+/// Invalid input to a fallible construction
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SaisError {
+    /// The buffers do not have the length of the text
+    LengthMismatch {
+        text: usize,
+        suffix_array: usize,
+        types: usize,
+    },
+    /// The text contains a character without a bucket
+    AlphabetTooLarge { buckets: usize, required: usize },
+    /// The text has more characters than the index type can address
+    TextTooLargeForIndex { len: usize, max: usize },
+    /// The text is empty, it has no suffixes to sort
+    EmptyInput,
+    /// No construction fits the memory budget, `required` is the least memory of all of them
+    MemoryBudgetExceeded { required: usize, budget: usize },
+}
+
+impl fmt::Display for SaisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaisError::LengthMismatch {
+                text,
+                suffix_array,
+                types,
+            } => write!(
+                f,
+                "buffer lengths do not match the text length {}: suffix array {}, types {}",
+                text, suffix_array, types
+            ),
+            SaisError::AlphabetTooLarge { buckets, required } => write!(
+                f,
+                "{} buckets are too few for the alphabet, {} are required",
+                buckets, required
+            ),
+            SaisError::TextTooLargeForIndex { len, max } => write!(
+                f,
+                "text of length {} is too large for the index type, the maximum is {}",
+                len, max
+            ),
+            SaisError::EmptyInput => f.write_str("text is empty"),
+            SaisError::MemoryBudgetExceeded { required, budget } => write!(
+                f,
+                "the construction needs {} bytes, the budget is {} bytes",
+                required, budget
+            ),
+        }
+    }
+}
+
+impl Error for SaisError {}
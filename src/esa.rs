@@ -0,0 +1,439 @@
+use std::ops::Range;
+
+use crate::lcp::lcp_array;
+use crate::rmq::{Rmq, SparseTable};
+use crate::sais::{inverse, suffix_array};
+use crate::suffix_index::SuffixIndex;
+
+/// Suffix array with lcp array and child table, supports the top-down traversal of a suffix tree
+/// (Abouelhoda, Kurtz and Ohlebusch, 2004).
+///
+/// Nodes are lcp intervals, the intervals of the suffix array sharing a prefix of the length of
+/// the minimum lcp inside, leaves are intervals containing one suffix.
+#[derive(Debug, Clone)]
+pub struct EnhancedSuffixArray<'a, I> {
+    text: &'a [u8],
+    suffix_array: Vec<I>,
+    lcp: Vec<I>,
+    /// The first l-index of the interval ending in front of every index if it is a right bound
+    up: Vec<I>,
+    /// The first l-index of the child interval starting at every index if it is a left bound
+    down: Vec<I>,
+    /// The next l-index of the same interval for every l-index
+    next: Vec<I>,
+}
+
+impl<'a, I: SuffixIndex> EnhancedSuffixArray<'a, I> {
+    pub fn new(text: &'a [u8]) -> Self {
+        let suffix_array = suffix_array(text);
+        let lcp = lcp_array(text, &suffix_array);
+        Self::from_parts(text, suffix_array, lcp)
+    }
+
+    /// Computes the child table, `suffix_array` and `lcp` must belong to `text`
+    pub fn from_parts(text: &'a [u8], suffix_array: Vec<I>, lcp: Vec<I>) -> Self {
+        assert_eq!(text.len(), suffix_array.len());
+        assert_eq!(text.len(), lcp.len());
+        let n = text.len();
+        // The lcp array with -1 at both ends
+        let extended = |i: usize| match i {
+            0 => -1,
+            i if i == n => -1,
+            i => lcp[i].as_index() as isize,
+        };
+
+        let undefined = I::from_index(0);
+        let (mut up, mut down, mut next) = (
+            vec![undefined; n + 1],
+            vec![undefined; n + 1],
+            vec![undefined; n + 1],
+        );
+        let mut stack = vec![0];
+        let mut last = None;
+        for (i, up) in up.iter_mut().enumerate().skip(1) {
+            while extended(i) < extended(*stack.last().unwrap()) {
+                let popped = stack.pop().unwrap();
+                last = Some(popped);
+                let top = *stack.last().unwrap();
+                if extended(i) <= extended(top) && extended(top) != extended(popped) {
+                    down[top] = I::from_index(popped);
+                }
+            }
+            if let Some(last) = last.take() {
+                *up = I::from_index(last);
+            }
+            stack.push(i);
+        }
+
+        stack.clear();
+        stack.push(0);
+        for i in 1..=n {
+            while extended(i) < extended(*stack.last().unwrap()) {
+                stack.pop();
+            }
+            if extended(i) == extended(*stack.last().unwrap()) {
+                let last = stack.pop().unwrap();
+                next[last] = I::from_index(i);
+            }
+            stack.push(i);
+        }
+
+        Self {
+            text,
+            suffix_array,
+            lcp,
+            up,
+            down,
+            next,
+        }
+    }
+
+    pub fn text(&self) -> &'a [u8] {
+        self.text
+    }
+
+    pub fn suffix_array(&self) -> &[I] {
+        &self.suffix_array
+    }
+
+    pub fn lcp(&self) -> &[I] {
+        &self.lcp
+    }
+
+    /// The interval of all suffixes
+    pub fn root(&self) -> Range<usize> {
+        0..self.suffix_array.len()
+    }
+
+    /// The first l-index of a non-leaf `interval`, where the lcp is minimal
+    fn first_l_index(&self, interval: &Range<usize>) -> usize {
+        let up = self.up[interval.end].as_index();
+        if interval.start < up && up < interval.end {
+            up
+        } else {
+            self.down[interval.start].as_index()
+        }
+    }
+
+    /// The length of the prefix shared by all suffixes of `interval`, the suffix length for leaves
+    pub fn depth(&self, interval: &Range<usize>) -> usize {
+        match interval.len() {
+            0 => 0,
+            1 => self.text.len() - self.suffix_array[interval.start].as_index(),
+            _ => self.lcp[self.first_l_index(interval)].as_index(),
+        }
+    }
+
+    /// The child intervals of `interval` in lexicographic order, leaves have no children
+    pub fn children(&self, interval: Range<usize>) -> impl Iterator<Item = Range<usize>> + '_ {
+        let mut start = interval.start;
+        let mut l_index = (interval.len() > 1).then(|| self.first_l_index(&interval));
+        let mut done = interval.len() <= 1;
+        std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            match l_index {
+                Some(index) => {
+                    let child = start..index;
+                    start = index;
+                    // The next l-index of the same interval has the same lcp
+                    let next = self.next[index].as_index();
+                    l_index =
+                        (next > index && next < interval.end && self.lcp[next] == self.lcp[index])
+                            .then_some(next);
+                    Some(child)
+                }
+                None => {
+                    done = true;
+                    Some(start..interval.end)
+                }
+            }
+        })
+    }
+
+    /// The child of `interval` whose suffixes continue with `c` after the shared prefix
+    pub fn child(&self, interval: Range<usize>, c: u8) -> Option<Range<usize>> {
+        let depth = self.depth(&interval);
+        self.children(interval).find(|child| {
+            let suffix = self.suffix_array[child.start].as_index();
+            self.text.get(suffix + depth) == Some(&c)
+        })
+    }
+
+    /// Narrows `interval` of the suffixes prefixed by a word of length `len` to the suffixes
+    /// continuing with `c`
+    fn extend(&self, interval: Range<usize>, len: usize, c: u8) -> Option<Range<usize>> {
+        if interval.is_empty() {
+            return None;
+        }
+        if len < self.depth(&interval) {
+            let suffix = self.suffix_array[interval.start].as_index();
+            return (self.text[suffix + len] == c).then_some(interval);
+        }
+        self.child(interval, c)
+    }
+
+    /// Returns the interval of the suffix array containing all suffixes prefixed by `pattern`
+    pub fn find(&self, pattern: &[u8]) -> Range<usize> {
+        let mut interval = self.root();
+        let mut matched = 0;
+        while !interval.is_empty() {
+            let suffix = &self.text[self.suffix_array[interval.start].as_index()..];
+            let depth = self.depth(&interval).min(pattern.len());
+            if suffix.len() < depth || suffix[matched..depth] != pattern[matched..depth] {
+                break;
+            }
+            if depth == pattern.len() {
+                return interval;
+            }
+            if interval.len() == 1 {
+                break;
+            }
+            matched = depth;
+            match self.child(interval, pattern[depth]) {
+                Some(child) => interval = child,
+                None => break,
+            }
+        }
+        0..0
+    }
+}
+
+/// Suffix links of the intervals of an [`EnhancedSuffixArray`], each in `O(log n)` time.
+///
+/// The link of an interval is the interval of its shared prefix without the first character, it
+/// allows streaming a pattern by dropping characters from the front.
+#[derive(Debug, Clone)]
+pub struct SuffixLinks<'a, I, R = SparseTable<I>> {
+    esa: &'a EnhancedSuffixArray<'a, I>,
+    inverse: Vec<I>,
+    lcp: R,
+}
+
+impl<'a, I: SuffixIndex> SuffixLinks<'a, I> {
+    pub fn new(esa: &'a EnhancedSuffixArray<'a, I>) -> Self {
+        Self::with_backend(esa)
+    }
+}
+
+impl<'a, I: SuffixIndex, R: Rmq<I>> SuffixLinks<'a, I, R> {
+    /// Builds the links with `R` as the range minimum backend over the lcp array
+    pub fn with_backend(esa: &'a EnhancedSuffixArray<'a, I>) -> Self {
+        Self {
+            esa,
+            inverse: inverse(&esa.suffix_array),
+            lcp: R::new(esa.lcp.clone()),
+        }
+    }
+
+    /// The interval of all suffixes prefixed by the shared prefix of `interval` without its first
+    /// character, `interval` must not be the root or empty
+    pub fn link(&self, interval: Range<usize>) -> Range<usize> {
+        assert!(!interval.is_empty(), "the interval must not be empty");
+        let depth = self.esa.depth(&interval);
+        assert!(depth > 0, "the root has no suffix link");
+        if depth == 1 {
+            return self.esa.root();
+        }
+        let suffix = self.esa.suffix_array[interval.start].as_index() + 1;
+        let row = self.inverse[suffix].as_index();
+        self.enclosing(row, depth - 1)
+    }
+
+    /// Computes the matching statistics of `query`, the length of the longest prefix of every
+    /// suffix of `query` occurring in the text, in `O(m log n)` time
+    pub fn matching_statistics(&self, query: &[u8]) -> Vec<usize> {
+        let mut statistics = Vec::with_capacity(query.len());
+        self.for_each_longest_match(query, |_, len, _| statistics.push(len));
+        statistics
+    }
+
+    /// Finds all maximal exact matches of `query` and the text with a length of at least `min_len`.
+    ///
+    /// Returns `(query_position, text_position, len)` triples sorted by position in `query`.
+    pub fn maximal_exact_matches(
+        &self,
+        query: &[u8],
+        min_len: usize,
+    ) -> Vec<(usize, usize, usize)> {
+        let min_len = min_len.max(1);
+        let text = self.esa.text;
+        let mut matches = Vec::new();
+        self.for_each_longest_match(query, |position, len, mut interval| {
+            // Occurrences only in an enclosing interval continue differently from the query
+            let mut inner = 0..0;
+            let mut len = len;
+            while len >= min_len {
+                for index in interval.clone().filter(|index| !inner.contains(index)) {
+                    let suffix = self.esa.suffix_array[index].as_index();
+                    if position == 0 || suffix == 0 || query[position - 1] != text[suffix - 1] {
+                        matches.push((position, suffix, len));
+                    }
+                }
+                let before = self.esa.lcp[interval.start].as_index();
+                let after = self
+                    .esa
+                    .lcp
+                    .get(interval.end)
+                    .map_or(0, |lcp| lcp.as_index());
+                len = before.max(after);
+                inner = interval;
+                interval = self.enclosing(inner.start, len);
+            }
+        });
+        matches.sort_unstable();
+        matches
+    }
+
+    /// Calls `f` with every position of `query`, the length of the longest prefix of the suffix
+    /// starting there that occurs in the text and the interval of its occurrences
+    fn for_each_longest_match(&self, query: &[u8], mut f: impl FnMut(usize, usize, Range<usize>)) {
+        let mut interval = self.esa.root();
+        let mut len = 0;
+        for position in 0..query.len() {
+            while let Some(&c) = query.get(position + len) {
+                match self.esa.extend(interval.clone(), len, c) {
+                    Some(extended) => interval = extended,
+                    None => break,
+                }
+                len += 1;
+            }
+            f(position, len, interval.clone());
+            // Drop the first character
+            if len > 0 {
+                let suffix = self.esa.suffix_array[interval.start].as_index() + 1;
+                len -= 1;
+                interval = match self.inverse.get(suffix) {
+                    Some(row) => self.enclosing(row.as_index(), len),
+                    None => self.esa.root(),
+                };
+            }
+        }
+    }
+
+    /// The maximal interval around `row` of suffixes sharing at least `depth` characters
+    fn enclosing(&self, row: usize, depth: usize) -> Range<usize> {
+        let shares =
+            |range: Range<usize>| range.is_empty() || self.lcp.min(range).as_index() >= depth;
+        // The lcp at an index belongs to the suffix and its predecessor
+        let start = partition_point(0..row, |start| !shares(start + 1..row + 1));
+        let end = partition_point(row + 1..self.inverse.len(), |end| shares(row + 1..end + 1));
+        start..end
+    }
+}
+
+/// The first index in `range` for which `predicate` is false, `predicate` must be true for a
+/// prefix of `range`
+fn partition_point(range: Range<usize>, predicate: impl Fn(usize) -> bool) -> usize {
+    let (mut low, mut high) = (range.start, range.end);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if predicate(middle) {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+    low
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::analysis::maximal_exact_matches;
+    use crate::rmq::BlockRmq;
+    use crate::search::search;
+
+    #[test]
+    fn test_children() {
+        let text = b"acaaacatat";
+        let esa = EnhancedSuffixArray::<u32>::new(text);
+        let children = esa.children(esa.root()).collect::<Vec<_>>();
+        assert_eq!(children, vec![0..6, 6..8, 8..10]);
+        assert_eq!(esa.depth(&(0..6)), 1);
+        assert_eq!(
+            esa.children(0..6).collect::<Vec<_>>(),
+            vec![0..2, 2..4, 4..6]
+        );
+        assert_eq!(esa.child(esa.root(), b't'), Some(8..10));
+        assert_eq!(esa.child(esa.root(), b'g'), None);
+        assert_eq!(esa.children(3..4).count(), 0);
+    }
+
+    #[test]
+    fn test_find() {
+        let text = b"abracadabra mississippi abracadabra";
+        let esa = EnhancedSuffixArray::<u32>::new(text);
+        for start in 0..text.len() {
+            for end in start..=text.len().min(start + 12) {
+                let pattern = &text[start..end];
+                assert_eq!(esa.find(pattern), search(text, esa.suffix_array(), pattern));
+            }
+        }
+        for pattern in [&b"abrab"[..], b"x", b"ississippi!", b"a mis"] {
+            let expected = search(text, esa.suffix_array(), pattern);
+            assert_eq!(esa.find(pattern).len(), expected.len());
+        }
+        assert_eq!(EnhancedSuffixArray::<u32>::new(b"").find(b"a"), 0..0);
+    }
+
+    #[test]
+    fn test_suffix_links() {
+        let text = b"abracadabra mississippi abracadabra";
+        let esa = EnhancedSuffixArray::<u32>::new(text);
+        let links = SuffixLinks::new(&esa);
+        let block = SuffixLinks::<u32, BlockRmq<u32>>::with_backend(&esa);
+        let mut stack = esa.children(esa.root()).collect::<Vec<_>>();
+        while let Some(interval) = stack.pop() {
+            let suffix = esa.suffix_array()[interval.start] as usize;
+            let depth = esa.depth(&interval);
+            let expected = esa.find(&text[suffix + 1..suffix + depth]);
+            assert_eq!(links.link(interval.clone()), expected);
+            assert_eq!(block.link(interval.clone()), expected);
+            stack.extend(esa.children(interval));
+        }
+        let interval = esa.find(b"abra");
+        assert_eq!(links.link(interval), esa.find(b"bra"));
+    }
+
+    #[test]
+    fn test_maximal_exact_matches() {
+        let text = b"abracadabra mississippi abracadabra";
+        let esa = EnhancedSuffixArray::<u32>::new(text);
+        let links = SuffixLinks::new(&esa);
+        for query in [
+            &b"cadabra miss"[..],
+            b"xabrax",
+            b"",
+            b"ississ",
+            b"abracadabra",
+        ] {
+            for min_len in [0, 2, 4] {
+                assert_eq!(
+                    links.maximal_exact_matches(query, min_len),
+                    maximal_exact_matches(query, text, min_len)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_matching_statistics() {
+        let text = b"abracadabra mississippi";
+        let esa = EnhancedSuffixArray::<u32>::new(text);
+        let links = SuffixLinks::new(&esa);
+        let query = b"cabrxississippix";
+        let expected = (0..query.len())
+            .map(|i| {
+                (0..=query.len() - i)
+                    .rev()
+                    .find(|&len| !esa.find(&query[i..i + len]).is_empty())
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(links.matching_statistics(query), expected);
+        assert_eq!(&expected[..5], [2, 3, 2, 1, 0]);
+        assert!(links.matching_statistics(b"").is_empty());
+    }
+}
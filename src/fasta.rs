@@ -0,0 +1,212 @@
+use std::io::{self, BufRead};
+
+use crate::generalized::{Builder, GeneralizedSuffixArray};
+use crate::suffix_index::SuffixIndex;
+
+/// A FASTA or FASTQ record, line wrapping and qualities removed
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Record {
+    /// The header line without the leading `>` or `@`
+    pub header: String,
+    pub sequence: Vec<u8>,
+}
+
+/// Streams records from FASTA or FASTQ input, the format is detected from the first record and
+/// records of the other format are an error
+pub struct Reader<R> {
+    reader: R,
+    line: Vec<u8>,
+    pending: bool,
+    /// The header marker of the first record
+    marker: Option<u8>,
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn trim_end(line: &mut Vec<u8>) {
+    while matches!(line.last(), Some(b'\n' | b'\r')) {
+        line.pop();
+    }
+}
+
+impl<R: BufRead> Reader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: Vec::new(),
+            pending: false,
+            marker: None,
+        }
+    }
+
+    /// Reads the next line into `self.line`, returns false at the end of the input
+    fn next_line(&mut self) -> io::Result<bool> {
+        if self.pending {
+            self.pending = false;
+            return Ok(true);
+        }
+        self.line.clear();
+        if self.reader.read_until(b'\n', &mut self.line)? == 0 {
+            return Ok(false);
+        }
+        trim_end(&mut self.line);
+        Ok(true)
+    }
+
+    fn header(&self) -> io::Result<String> {
+        String::from_utf8(self.line[1..].to_vec()).map_err(|_| invalid("header is not utf-8"))
+    }
+
+    fn read_fasta(&mut self) -> io::Result<Record> {
+        let header = self.header()?;
+        let mut sequence = Vec::new();
+        while self.next_line()? {
+            match self.line.first() {
+                Some(b'>') => {
+                    self.pending = true;
+                    break;
+                }
+                Some(b'@') => return Err(invalid("mixed fasta and fastq records")),
+                _ => {}
+            }
+            sequence.extend(self.line.iter().filter(|c| !c.is_ascii_whitespace()));
+        }
+        Ok(Record { header, sequence })
+    }
+
+    fn read_fastq(&mut self) -> io::Result<Record> {
+        let header = self.header()?;
+        let mut sequence = Vec::new();
+        loop {
+            if !self.next_line()? {
+                return Err(invalid("missing fastq separator line"));
+            }
+            if self.line.first() == Some(&b'+') {
+                break;
+            }
+            sequence.extend(self.line.iter().filter(|c| !c.is_ascii_whitespace()));
+        }
+
+        let mut qualities = 0;
+        while qualities < sequence.len() {
+            if !self.next_line()? {
+                return Err(invalid("truncated fastq qualities"));
+            }
+            qualities += self.line.len();
+        }
+        if qualities != sequence.len() {
+            return Err(invalid("fastq quality length does not match the sequence"));
+        }
+        Ok(Record { header, sequence })
+    }
+
+    fn read_record(&mut self) -> io::Result<Option<Record>> {
+        loop {
+            if !self.next_line()? {
+                return Ok(None);
+            }
+            let marker = match self.line.first() {
+                None => continue,
+                Some(&marker) => marker,
+            };
+            if !matches!(marker, b'>' | b'@') {
+                return Err(invalid("expected a fasta or fastq header"));
+            }
+            if *self.marker.get_or_insert(marker) != marker {
+                return Err(invalid("mixed fasta and fastq records"));
+            }
+            return if marker == b'>' {
+                self.read_fasta().map(Some)
+            } else {
+                self.read_fastq().map(Some)
+            };
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Reader<R> {
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+/// Appends every record of `reader` as a document, returns the headers
+pub fn push_records<R: BufRead>(builder: &mut Builder, reader: R) -> io::Result<Vec<String>> {
+    let mut headers = Vec::new();
+    for record in Reader::new(reader) {
+        let record = record?;
+        builder.push(&record.sequence);
+        headers.push(record.header);
+    }
+    Ok(headers)
+}
+
+/// Builds an index over all records of all `sources`, returns it with the header of every document
+pub fn build_index<I: SuffixIndex, R: BufRead>(
+    sources: impl IntoIterator<Item = R>,
+) -> io::Result<(GeneralizedSuffixArray<I>, Vec<String>)> {
+    let mut builder = Builder::new();
+    let mut headers = Vec::new();
+    for source in sources {
+        headers.extend(push_records(&mut builder, source)?);
+    }
+    Ok((builder.build(), headers))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fasta() {
+        let input = b">chr1 reference\nACGT\r\nAC\n\n>chr2\nTTA\n" as &[u8];
+        let records = Reader::new(input).collect::<io::Result<Vec<_>>>().unwrap();
+        assert_eq!(
+            records,
+            vec![
+                Record {
+                    header: "chr1 reference".into(),
+                    sequence: b"ACGTAC".to_vec()
+                },
+                Record {
+                    header: "chr2".into(),
+                    sequence: b"TTA".to_vec()
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fastq() {
+        let input = b"@read1\nACG\nT\n+\n@@I\nI\n@read2\nGG\n+read2\n!!\n" as &[u8];
+        let records = Reader::new(input).collect::<io::Result<Vec<_>>>().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].sequence, b"ACGT");
+        assert_eq!(records[1].header, "read2");
+        assert_eq!(records[1].sequence, b"GG");
+
+        let truncated = b"@read\nACGT\n+\nII\n" as &[u8];
+        assert!(Reader::new(truncated).next().unwrap().is_err());
+        let mixed = b"@read\nACGT\n+\nIIII\n>chr1\nACGT\n" as &[u8];
+        let mut records = Reader::new(mixed);
+        assert!(records.next().unwrap().is_ok());
+        assert!(records.next().unwrap().is_err());
+        let mixed = b">chr1\nACGT\n@read\nACGT\n+\nIIII\n" as &[u8];
+        assert!(Reader::new(mixed).next().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_build_index() {
+        let reference = b">ref\nACGTACGT\n" as &[u8];
+        let reads = b"@r1\nGTA\n+\nIII\n" as &[u8];
+        let (index, headers) = build_index::<u32, _>(vec![reference, reads]).unwrap();
+        assert_eq!(headers, vec!["ref", "r1"]);
+        assert_eq!(index.document_count(), 2);
+        assert_eq!(index.document(1), b"GTA");
+        assert_eq!(index.suffix_array().len(), 11);
+    }
+}
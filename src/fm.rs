@@ -0,0 +1,546 @@
+use std::ops::Range;
+
+use crate::bwt::bwt_from_suffix_array;
+use crate::sais::suffix_array;
+use crate::suffix_index::SuffixIndex;
+use crate::wavelet::BitVector;
+
+const ALPHABET: usize = 0x100;
+
+/// Rows between two stored rank checkpoints
+const CHECKPOINT_RATE: usize = 64;
+
+const DEFAULT_SAMPLE_RATE: usize = 32;
+
+/// Rank queries over a byte sequence, the occurrence backend of an [`FmIndex`]
+pub trait Rank {
+    fn new(bytes: Vec<u8>) -> Self;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The byte at `index`
+    fn get(&self, index: usize) -> u8;
+
+    /// The number of occurrences of `c` in the first `index` bytes
+    fn rank(&self, c: u8, index: usize) -> usize;
+}
+
+/// Bytes with the occurrence counts of every character stored at regular checkpoints.
+///
+/// Fast for any alphabet, but takes `256` counts per checkpoint.
+#[derive(Debug, Clone)]
+pub struct RankTable {
+    bytes: Vec<u8>,
+    checkpoints: Vec<[usize; ALPHABET]>,
+}
+
+impl Rank for RankTable {
+    fn new(bytes: Vec<u8>) -> Self {
+        let mut counts = [0; ALPHABET];
+        let mut checkpoints = Vec::with_capacity(bytes.len() / CHECKPOINT_RATE + 1);
+        for (index, &c) in bytes.iter().enumerate() {
+            if index.is_multiple_of(CHECKPOINT_RATE) {
+                checkpoints.push(counts);
+            }
+            counts[c as usize] += 1;
+        }
+        if bytes.len().is_multiple_of(CHECKPOINT_RATE) {
+            checkpoints.push(counts);
+        }
+        Self { bytes, checkpoints }
+    }
+
+    fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    fn get(&self, index: usize) -> u8 {
+        self.bytes[index]
+    }
+
+    fn rank(&self, c: u8, index: usize) -> usize {
+        let checkpoint = index / CHECKPOINT_RATE;
+        let start = checkpoint * CHECKPOINT_RATE;
+        let count = self.bytes[start..index].iter().filter(|&&x| x == c).count();
+        self.checkpoints[checkpoint][c as usize] + count
+    }
+}
+
+/// Full-text index over the Burrows-Wheeler transform of a text.
+///
+/// Patterns are matched by backward search, positions are recovered from a sample of the suffix
+/// array by walking backwards in the text, see [`Builder`] for the sampling.
+#[derive(Debug, Clone)]
+pub struct FmIndex<I, R = RankTable> {
+    /// The transform including a placeholder for the end marker in row `primary`
+    ranks: R,
+    primary: usize,
+    /// The first row starting with every character, the row of the end marker comes first
+    starts: [usize; ALPHABET],
+    /// The sampled suffixes in row order
+    samples: Vec<I>,
+    /// The sampled rows if they are not every `rate`-th row
+    sampled_rows: Option<BitVector>,
+    rate: usize,
+}
+
+/// Which suffixes an [`FmIndex`] stores to locate occurrences
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum Sampling {
+    /// The suffix of every `rate`-th row, locating takes `rate` steps on average but is unbounded
+    #[default]
+    SuffixArrayOrder,
+    /// Every suffix starting at a multiple of `rate`, locating takes less than `rate` steps but
+    /// the sampled rows are marked in an additional bit vector
+    TextOrder,
+}
+
+/// Construction settings of an [`FmIndex`]
+#[derive(Debug, Clone)]
+pub struct Builder {
+    sampling: Sampling,
+    rate: usize,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self {
+            sampling: Sampling::default(),
+            rate: DEFAULT_SAMPLE_RATE,
+        }
+    }
+
+    pub fn sampling(&mut self, sampling: Sampling) -> &mut Self {
+        self.sampling = sampling;
+        self
+    }
+
+    /// Samples one in `rate` suffixes, larger rates take less memory and locate slower
+    pub fn rate(&mut self, rate: usize) -> &mut Self {
+        assert!(rate > 0, "the sampling rate must be positive");
+        self.rate = rate;
+        self
+    }
+
+    pub fn build<I: SuffixIndex, R: Rank>(&self, text: &[u8]) -> FmIndex<I, R> {
+        self.build_from_suffix_array(text, &suffix_array(text))
+    }
+
+    /// Builds the index of `text` from its suffix array
+    pub fn build_from_suffix_array<I: SuffixIndex, R: Rank>(
+        &self,
+        text: &[u8],
+        suffix_array: &[I],
+    ) -> FmIndex<I, R> {
+        assert_eq!(text.len(), suffix_array.len());
+        let (transformed, primary) =
+            bwt_from_suffix_array(text, suffix_array.iter().map(|suffix| suffix.as_index()));
+        // Rows include the row of the end marker, which sorts first
+        let mut rows = transformed;
+        rows.insert(primary, 0);
+
+        let mut starts = [0; ALPHABET];
+        for &c in text {
+            starts[c as usize] += 1;
+        }
+        let mut sum = 1;
+        for start in starts.iter_mut() {
+            sum += *start;
+            *start = sum - *start;
+        }
+
+        let rate = self.rate;
+        let suffixes = (0..rows.len()).map(|row| match row {
+            0 => I::from_index(text.len()),
+            row => suffix_array[row - 1],
+        });
+        let (samples, sampled_rows) = match self.sampling {
+            Sampling::SuffixArrayOrder => (suffixes.step_by(rate).collect(), None),
+            Sampling::TextOrder => {
+                let is_sampled = |suffix: &I| suffix.as_index().is_multiple_of(rate);
+                let sampled_rows =
+                    BitVector::new(suffixes.clone().map(|suffix| is_sampled(&suffix)));
+                (suffixes.filter(is_sampled).collect(), Some(sampled_rows))
+            }
+        };
+        FmIndex {
+            ranks: R::new(rows),
+            primary,
+            starts,
+            samples,
+            sampled_rows,
+            rate,
+        }
+    }
+}
+
+impl<I: SuffixIndex> FmIndex<I> {
+    pub fn new(text: &[u8]) -> Self {
+        Self::with_backend(text)
+    }
+}
+
+impl<I: SuffixIndex, R: Rank> FmIndex<I, R> {
+    /// Builds the index with `R` as the occurrence backend and the default sampling
+    pub fn with_backend(text: &[u8]) -> Self {
+        Builder::new().build(text)
+    }
+
+    /// The length of the indexed text
+    pub fn len(&self) -> usize {
+        self.ranks.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of occurrences of `c` in front of the first `row` rows
+    fn rank(&self, c: u8, row: usize) -> usize {
+        let end_marker = c == 0 && self.primary < row;
+        self.ranks.rank(c, row) - end_marker as usize
+    }
+
+    /// The rows of the suffixes prefixed by `pattern`, the row of the empty suffix is excluded
+    fn backward_search(&self, pattern: &[u8]) -> Range<usize> {
+        let (mut start, mut end) = (0, self.ranks.len());
+        for &c in pattern.iter().rev() {
+            if start == end {
+                break;
+            }
+            start = self.starts[c as usize] + self.rank(c, start);
+            end = self.starts[c as usize] + self.rank(c, end);
+        }
+        start.max(1)..end.max(1)
+    }
+
+    /// The rows of the suffixes in `rows` preceded by `c` and the number of suffixes in `rows`
+    /// preceded by a smaller character or the end marker
+    fn extend(&self, rows: Range<usize>, c: u8) -> (Range<usize>, usize) {
+        let count = |c: u8| self.rank(c, rows.end) - self.rank(c, rows.start);
+        let end_marker = rows.contains(&self.primary) as usize;
+        let smaller = end_marker + (0..c).map(count).sum::<usize>();
+        let start = self.starts[c as usize] + self.rank(c, rows.start);
+        (start..start + count(c), smaller)
+    }
+
+    /// The number of occurrences of `pattern`
+    pub fn count(&self, pattern: &[u8]) -> usize {
+        self.backward_search(pattern).len()
+    }
+
+    /// The row of the suffix starting one character before the suffix of `row`
+    fn lf(&self, row: usize) -> usize {
+        let c = self.ranks.get(row);
+        self.starts[c as usize] + self.rank(c, row)
+    }
+
+    /// The position of the suffix of `row`
+    fn locate_row(&self, mut row: usize) -> usize {
+        let mut steps = 0;
+        loop {
+            let sample = match &self.sampled_rows {
+                None => row.is_multiple_of(self.rate).then(|| row / self.rate),
+                Some(sampled_rows) => sampled_rows.get(row).then(|| sampled_rows.rank1(row)),
+            };
+            if let Some(sample) = sample {
+                return self.samples[sample].as_index() + steps;
+            }
+            if row == self.primary {
+                return steps;
+            }
+            row = self.lf(row);
+            steps += 1;
+        }
+    }
+
+    /// Returns the positions of all substrings of the text that differ from `pattern` in at most
+    /// `k` characters with the number of mismatches, in ascending order.
+    ///
+    /// All substitutions are tried by backtracking in the backward search, the running time grows
+    /// exponentially with `k`.
+    pub fn locate_with_mismatches(&self, pattern: &[u8], k: usize) -> Vec<(usize, usize)> {
+        let rows = self.ranks.len();
+        let alphabet = (0..=u8::MAX)
+            .filter(|&c| {
+                let end = self.starts.get(c as usize + 1).copied().unwrap_or(rows);
+                end > self.starts[c as usize]
+            })
+            .collect::<Vec<_>>();
+
+        let mut hits = Vec::new();
+        let mut stack = vec![(0..rows, pattern.len(), 0)];
+        while let Some((rows, remaining, mismatches)) = stack.pop() {
+            if remaining == 0 {
+                let rows = rows.filter(|&row| row != 0);
+                hits.extend(rows.map(|row| (self.locate_row(row), mismatches)));
+                continue;
+            }
+            let expected = pattern[remaining - 1];
+            for &c in &alphabet {
+                let mismatches = mismatches + (c != expected) as usize;
+                let start = self.starts[c as usize] + self.rank(c, rows.start);
+                let end = self.starts[c as usize] + self.rank(c, rows.end);
+                if mismatches <= k && start < end {
+                    stack.push((start..end, remaining - 1, mismatches));
+                }
+            }
+        }
+        hits.sort_unstable();
+        hits
+    }
+
+    /// Returns the positions of all occurrences of `pattern` in suffix array order
+    pub fn locate(&self, pattern: &[u8]) -> impl Iterator<Item = usize> + '_ {
+        self.backward_search(pattern)
+            .map(move |row| self.locate_row(row))
+    }
+}
+
+/// The rows of the suffixes prefixed by a pattern in the forward index and by the reversed pattern
+/// in the reverse index of a [`BidirectionalFmIndex`], the `(k, l, s)` bi-interval.
+///
+/// Both ranges have the same length. Seeding heuristics drive the index with
+/// [`BidirectionalFmIndex::extend_left`] and [`BidirectionalFmIndex::extend_right`] starting at
+/// [`BidirectionalFmIndex::interval`], an empty interval stays empty.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct BiInterval {
+    /// The first row in the forward index, `k`
+    pub forward: usize,
+    /// The first row in the reverse index, `l`
+    pub reverse: usize,
+    /// The number of rows, `s`
+    pub len: usize,
+}
+
+impl BiInterval {
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The rows in the forward index, row `0` is the empty suffix
+    pub fn forward_rows(&self) -> Range<usize> {
+        self.forward..self.forward + self.len
+    }
+
+    /// The rows in the reverse index, row `0` is the empty suffix
+    pub fn reverse_rows(&self) -> Range<usize> {
+        self.reverse..self.reverse + self.len
+    }
+}
+
+/// FM-indices of a text and of its reverse with synchronized intervals, patterns can be extended
+/// in both directions
+#[derive(Debug, Clone)]
+pub struct BidirectionalFmIndex<I, R = RankTable> {
+    forward: FmIndex<I, R>,
+    reverse: FmIndex<I, R>,
+}
+
+impl<I: SuffixIndex> BidirectionalFmIndex<I> {
+    pub fn new(text: &[u8]) -> Self {
+        Self::with_backend(text)
+    }
+}
+
+impl<I: SuffixIndex, R: Rank> BidirectionalFmIndex<I, R> {
+    pub fn with_backend(text: &[u8]) -> Self {
+        let reversed = text.iter().rev().copied().collect::<Vec<_>>();
+        // Only the forward index locates occurrences
+        let unsampled = Builder {
+            sampling: Sampling::SuffixArrayOrder,
+            rate: usize::MAX,
+        };
+        Self {
+            forward: Builder::new().build(text),
+            reverse: unsampled.build(&reversed),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.forward.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.forward.is_empty()
+    }
+
+    /// The interval of the empty pattern, it includes the empty suffix
+    pub fn interval(&self) -> BiInterval {
+        BiInterval {
+            forward: 0,
+            reverse: 0,
+            len: self.len() + 1,
+        }
+    }
+
+    /// The interval of `pattern`
+    pub fn find(&self, pattern: &[u8]) -> BiInterval {
+        pattern.iter().fold(self.interval(), |interval, &c| {
+            self.extend_right(interval, c)
+        })
+    }
+
+    /// Steps from the interval of a pattern to the interval of `c` followed by the pattern
+    pub fn extend_left(&self, interval: BiInterval, c: u8) -> BiInterval {
+        let (rows, smaller) = self.forward.extend(interval.forward_rows(), c);
+        BiInterval {
+            forward: rows.start,
+            reverse: interval.reverse + smaller,
+            len: rows.len(),
+        }
+    }
+
+    /// Steps from the interval of a pattern to the interval of the pattern followed by `c`
+    pub fn extend_right(&self, interval: BiInterval, c: u8) -> BiInterval {
+        let (rows, smaller) = self.reverse.extend(interval.reverse_rows(), c);
+        BiInterval {
+            forward: interval.forward + smaller,
+            reverse: rows.start,
+            len: rows.len(),
+        }
+    }
+
+    /// The number of occurrences of the pattern of `interval`
+    pub fn count(&self, interval: BiInterval) -> usize {
+        interval.forward_rows().filter(|&row| row != 0).count()
+    }
+
+    /// The positions of the occurrences of the pattern of `interval` in suffix array order
+    pub fn locate(&self, interval: BiInterval) -> impl Iterator<Item = usize> + '_ {
+        interval
+            .forward_rows()
+            .filter(|&row| row != 0)
+            .map(move |row| self.forward.locate_row(row))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::suffix_array::SuffixArray;
+    use crate::wavelet::WaveletMatrix;
+
+    #[test]
+    fn test_bidirectional() {
+        let text = b"abracadabra mississippi banana".repeat(3);
+        let index = BidirectionalFmIndex::<u32>::new(&text);
+        let suffix_array = SuffixArray::<u32>::new(&text);
+        for pattern in [&b"a"[..], b"ab", b"issi", b"ana", b"i b", b"xyz", b"ra m"] {
+            let interval = index.find(pattern);
+            assert_eq!(index.count(interval), suffix_array.count(pattern));
+            assert!(index.locate(interval).eq(suffix_array.locate(pattern)));
+            // every split of extensions reaches the same interval
+            for split in 0..=pattern.len() {
+                let middle = index.find(&pattern[split..]);
+                let extended = pattern[..split]
+                    .iter()
+                    .rev()
+                    .fold(middle, |interval, &c| index.extend_left(interval, c));
+                if interval.is_empty() {
+                    assert!(extended.is_empty());
+                } else {
+                    assert_eq!(extended, interval);
+                }
+            }
+        }
+        assert_eq!(index.count(index.interval()), text.len());
+    }
+
+    #[test]
+    fn test_bi_interval() {
+        let text = b"abracadabra";
+        let index = BidirectionalFmIndex::<u32>::new(text);
+        assert_eq!(
+            index.interval(),
+            BiInterval {
+                forward: 0,
+                reverse: 0,
+                len: 12
+            }
+        );
+        // Extends "c" to the right and then to the left like a seeding heuristic would
+        let c = index.extend_right(index.interval(), b'c');
+        let ca = index.extend_right(c, b'a');
+        let aca = index.extend_left(ca, b'a');
+        assert_eq!(aca, index.find(b"aca"));
+        assert_eq!(aca.forward_rows().len(), 1);
+        assert_eq!(index.locate(aca).collect::<Vec<_>>(), vec![3]);
+        // "abra" occurs twice, its reverse "arba" in the reverse index as well
+        let abra = index.find(b"abra");
+        assert_eq!((abra.len, abra.reverse_rows().len()), (2, 2));
+        assert!(index.extend_left(abra, b'x').is_empty());
+        assert!(index.extend_right(BiInterval::default(), b'a').is_empty());
+    }
+
+    #[test]
+    fn test_mismatches() {
+        let text = b"the cat sat on the mat with a hat and a bat".to_vec();
+        let index = FmIndex::<u32>::new(&text);
+        for (pattern, k) in [
+            (&b"cat"[..], 0),
+            (b"cat", 1),
+            (b"tha", 2),
+            (b"xyz", 2),
+            (b"", 1),
+        ] {
+            let expected = (0..=text.len() - pattern.len())
+                .map(|start| {
+                    let window = &text[start..start + pattern.len()];
+                    let mismatches = window.iter().zip(pattern).filter(|(a, b)| a != b).count();
+                    (start, mismatches)
+                })
+                .filter(|&(start, mismatches)| mismatches <= k && start < text.len())
+                .collect::<Vec<_>>();
+            assert_eq!(index.locate_with_mismatches(pattern, k), expected);
+        }
+        let hits = index.locate_with_mismatches(b"cat", 1);
+        assert_eq!(hits.iter().filter(|&&(_, m)| m == 0).count(), 1);
+        assert_eq!(hits.len(), 5);
+    }
+
+    #[test]
+    fn test_fm_index() {
+        let text = b"mississippi".repeat(10);
+        check_index(&FmIndex::<u32>::new(&text), &text);
+        check_index(&FmIndex::<u32, WaveletMatrix>::with_backend(&text), &text);
+        for sampling in [Sampling::SuffixArrayOrder, Sampling::TextOrder] {
+            for rate in [1, 3, 200] {
+                let index: FmIndex<u32> = Builder::new().sampling(sampling).rate(rate).build(&text);
+                check_index(&index, &text);
+            }
+        }
+
+        let empty = FmIndex::<u32>::new(b"");
+        assert!(empty.is_empty());
+        assert_eq!(empty.count(b"a"), 0);
+        assert_eq!(empty.locate(b"").count(), 0);
+    }
+
+    fn check_index<R: Rank>(index: &FmIndex<u32, R>, text: &[u8]) {
+        let suffix_array = SuffixArray::<u32>::new(text);
+        assert_eq!(index.len(), text.len());
+        for pattern in [
+            &b""[..],
+            b"i",
+            b"ssi",
+            b"pi",
+            b"ppim",
+            b"x",
+            b"mississippim",
+        ] {
+            assert_eq!(index.count(pattern), suffix_array.count(pattern));
+            assert!(index.locate(pattern).eq(suffix_array.locate(pattern)));
+        }
+    }
+}
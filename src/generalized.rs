@@ -0,0 +1,394 @@
+use std::cmp::Ordering;
+use std::mem::replace;
+use std::ops::Range;
+
+use crate::rmq::{Rmq, SparseTable};
+use crate::sais::{induced_sort, Types};
+use crate::suffix_index::SuffixIndex;
+
+const ALPHABET: usize = 0x100;
+
+/// Suffix array over a collection of documents.
+///
+/// Suffixes never extend past the end of their document. Equal suffixes of different documents
+/// are ordered by document index. Positions refer to the concatenation of all documents.
+#[derive(Debug, Clone)]
+pub struct GeneralizedSuffixArray<I> {
+    text: Vec<u8>,
+    starts: Vec<usize>,
+    suffix_array: Vec<I>,
+}
+
+impl<I: SuffixIndex> GeneralizedSuffixArray<I> {
+    pub fn new<D: AsRef<[u8]>>(documents: &[D]) -> Self {
+        let mut builder = Builder::new();
+        for document in documents {
+            builder.push(document.as_ref());
+        }
+        builder.build()
+    }
+
+    /// The concatenation of all documents
+    pub fn text(&self) -> &[u8] {
+        &self.text
+    }
+
+    pub fn suffix_array(&self) -> &[I] {
+        &self.suffix_array
+    }
+
+    pub fn document_count(&self) -> usize {
+        self.starts.len() - 1
+    }
+
+    pub fn document_range(&self, document: usize) -> Range<usize> {
+        self.starts[document]..self.starts[document + 1]
+    }
+
+    pub fn document(&self, document: usize) -> &[u8] {
+        &self.text[self.document_range(document)]
+    }
+
+    /// Returns the document containing `position` and the offset of `position` in that document
+    pub fn document_position(&self, position: usize) -> (usize, usize) {
+        assert!(position < self.text.len());
+        let document = self.starts.partition_point(|&start| start <= position) - 1;
+        (document, position - self.starts[document])
+    }
+
+    /// The suffix starting at `position`, ending at the end of its document
+    pub fn suffix(&self, position: usize) -> &[u8] {
+        let (document, _) = self.document_position(position);
+        &self.text[position..self.starts[document + 1]]
+    }
+
+    /// Longest common prefix of every suffix with its predecessor in the suffix array, the first
+    /// entry is 0
+    pub fn lcp_array(&self) -> Vec<I> {
+        let len = self.suffix_array.len();
+        let mut rank = vec![0usize; len];
+        for (index, suffix) in self.suffix_array.iter().enumerate() {
+            rank[suffix.as_index()] = index;
+        }
+
+        let mut lcp = vec![I::from_index(0); len];
+        for document in 0..self.document_count() {
+            let end = self.starts[document + 1];
+            // Kasai: the lcp decreases by at most one from one suffix to the next
+            let mut h = 0;
+            for position in self.document_range(document) {
+                let index = rank[position];
+                if index == 0 {
+                    h = 0;
+                    continue;
+                }
+                let previous = self.suffix_array[index - 1].as_index();
+                let previous_end = self.starts[self.document_position(previous).0 + 1];
+                while position + h < end
+                    && previous + h < previous_end
+                    && self.text[position + h] == self.text[previous + h]
+                {
+                    h += 1;
+                }
+                lcp[index] = I::from_index(h);
+                h = h.saturating_sub(1);
+            }
+        }
+        lcp
+    }
+
+    /// The document of every suffix in suffix array order
+    pub fn document_array(&self) -> Vec<I> {
+        let mut position_documents = vec![I::from_index(0); self.text.len()];
+        for document in 0..self.document_count() {
+            position_documents[self.document_range(document)].fill(I::from_index(document));
+        }
+        self.suffix_array
+            .iter()
+            .map(|suffix| position_documents[suffix.as_index()])
+            .collect()
+    }
+
+    /// Returns the interval of the suffix array containing all suffixes prefixed by `pattern`
+    pub fn find(&self, pattern: &[u8]) -> Range<usize> {
+        let prefix_cmp = |suffix: &I| {
+            let suffix = self.suffix(suffix.as_index());
+            let len = suffix.len().min(pattern.len());
+            match suffix[..len].cmp(&pattern[..len]) {
+                Ordering::Equal if suffix.len() < pattern.len() => Ordering::Less,
+                ordering => ordering,
+            }
+        };
+        let start = self
+            .suffix_array
+            .partition_point(|suffix| prefix_cmp(suffix) == Ordering::Less);
+        let end = start
+            + self.suffix_array[start..]
+                .partition_point(|suffix| prefix_cmp(suffix) == Ordering::Equal);
+        start..end
+    }
+
+    /// Narrows `interval`, whose suffixes share a prefix of length `depth`, to the suffixes
+    /// continuing with `c`
+    pub fn refine(&self, interval: Range<usize>, depth: usize, c: u8) -> Range<usize> {
+        let suffixes = &self.suffix_array[interval.clone()];
+        let next = |suffix: &I| self.suffix(suffix.as_index()).get(depth).copied();
+        let start = suffixes.partition_point(|suffix| next(suffix) < Some(c));
+        let end = suffixes.partition_point(|suffix| next(suffix) <= Some(c));
+        interval.start + start..interval.start + end
+    }
+
+    /// Returns the positions of all occurrences of `pattern` in suffix array order
+    pub fn locate(&self, pattern: &[u8]) -> impl Iterator<Item = usize> + '_ {
+        self.suffix_array[self.find(pattern)]
+            .iter()
+            .map(|suffix| suffix.as_index())
+    }
+}
+
+/// Lists the distinct documents of intervals of a [`GeneralizedSuffixArray`] in time proportional
+/// to their number (Muthukrishnan, 2002)
+#[derive(Debug, Clone)]
+pub struct DocumentListing<I, R = SparseTable<usize>> {
+    documents: Vec<I>,
+    /// One after the previous index of the same document in the document array, 0 for the first
+    previous: R,
+}
+
+impl<I: SuffixIndex> DocumentListing<I> {
+    pub fn new(index: &GeneralizedSuffixArray<I>) -> Self {
+        Self::with_backend(index)
+    }
+}
+
+impl<I: SuffixIndex, R: Rmq<usize>> DocumentListing<I, R> {
+    /// Builds the listing with `R` as the range minimum backend
+    pub fn with_backend(index: &GeneralizedSuffixArray<I>) -> Self {
+        let documents = index.document_array();
+        let mut last = vec![0; index.document_count()];
+        let previous = documents
+            .iter()
+            .enumerate()
+            .map(|(i, document)| replace(&mut last[document.as_index()], i + 1))
+            .collect();
+        Self {
+            documents,
+            previous: R::new(previous),
+        }
+    }
+
+    /// The distinct documents of the suffixes in `interval` in ascending order
+    pub fn list(&self, interval: Range<usize>) -> Vec<usize> {
+        let start = interval.start;
+        let mut documents = Vec::new();
+        let mut stack = vec![interval];
+        while let Some(range) = stack.pop() {
+            if range.is_empty() {
+                continue;
+            }
+            // The leftmost occurrence of a document has no previous occurrence in the interval
+            let index = self.previous.min_index(range.clone());
+            if self.previous.values()[index] > start {
+                continue;
+            }
+            documents.push(self.documents[index].as_index());
+            stack.push(range.start..index);
+            stack.push(index + 1..range.end);
+        }
+        documents.sort_unstable();
+        documents
+    }
+}
+
+/// Collects documents for a [`GeneralizedSuffixArray`]
+#[derive(Debug, Clone)]
+pub struct Builder {
+    text: Vec<u8>,
+    starts: Vec<usize>,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self {
+            text: Vec::new(),
+            starts: vec![0],
+        }
+    }
+
+    /// Appends a document, returns its index
+    pub fn push(&mut self, document: &[u8]) -> usize {
+        self.text.extend_from_slice(document);
+        self.starts.push(self.text.len());
+        self.starts.len() - 2
+    }
+
+    pub fn document_count(&self) -> usize {
+        self.starts.len() - 1
+    }
+
+    pub fn build<I: SuffixIndex>(self) -> GeneralizedSuffixArray<I> {
+        let Self { text, starts } = self;
+        let documents = starts.len() - 1;
+        let len = text.len() + documents;
+        assert!(documents + ALPHABET <= I::MAX);
+        assert!(len < I::MAX);
+
+        // Every document is terminated by a unique separator smaller than all characters
+        let mut symbols = Vec::with_capacity(len);
+        for document in 0..documents {
+            let range = starts[document]..starts[document + 1];
+            symbols.extend(
+                text[range]
+                    .iter()
+                    .map(|&c| I::from_index(documents + c as usize)),
+            );
+            symbols.push(I::from_index(document));
+        }
+
+        let mut suffix_array = vec![I::from_index(0); len];
+        if len != 0 {
+            let mut types = Types::new(len);
+            let mut buckets = vec![I::from_index(0); documents + ALPHABET];
+            induced_sort(&symbols, &mut suffix_array, &mut types, &mut buckets);
+        }
+
+        // Reuse the symbols as a map from separated to concatenated positions
+        let mut position = 0;
+        for symbol in symbols.iter_mut() {
+            if symbol.as_index() < documents {
+                *symbol = I::from_index(I::MAX);
+            } else {
+                *symbol = I::from_index(position);
+                position += 1;
+            }
+        }
+
+        let mut write = 0;
+        for read in 0..len {
+            let mapped = symbols[suffix_array[read].as_index()];
+            if mapped != I::from_index(I::MAX) {
+                suffix_array[write] = mapped;
+                write += 1;
+            }
+        }
+        suffix_array.truncate(write);
+
+        GeneralizedSuffixArray {
+            text,
+            starts,
+            suffix_array,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rmq::BlockRmq;
+
+    fn naive<I: SuffixIndex>(documents: &[&[u8]]) -> Vec<I> {
+        let mut suffixes = Vec::new();
+        let mut offset = 0;
+        for (index, document) in documents.iter().enumerate() {
+            for start in 0..document.len() {
+                suffixes.push((&document[start..], index, offset + start));
+            }
+            offset += document.len();
+        }
+        suffixes.sort();
+        suffixes
+            .into_iter()
+            .map(|(_, _, position)| I::from_index(position))
+            .collect()
+    }
+
+    #[test]
+    fn test_generalized() {
+        let documents: &[&[u8]] = &[b"banana", b"", b"ananas", b"nan", b"banana"];
+        let gsa = GeneralizedSuffixArray::<u32>::new(documents);
+        assert_eq!(gsa.document_count(), 5);
+        assert_eq!(gsa.suffix_array(), &naive::<u32>(documents)[..]);
+        assert_eq!(gsa.document(2), b"ananas");
+        assert_eq!(gsa.document_position(6), (2, 0));
+        assert_eq!(gsa.document_position(13), (3, 1));
+        assert_eq!(gsa.suffix(13), b"an");
+
+        let document_array = gsa.document_array();
+        for (&suffix, &document) in gsa.suffix_array().iter().zip(&document_array) {
+            assert_eq!(gsa.document_position(suffix as usize).0, document as usize);
+        }
+    }
+
+    #[test]
+    fn test_lcp_array() {
+        let documents: &[&[u8]] = &[b"banana", b"anan", b"", b"nan"];
+        let gsa = GeneralizedSuffixArray::<u32>::new(documents);
+        let lcp = gsa.lcp_array();
+        assert_eq!(lcp[0], 0);
+        for (window, &lcp) in gsa.suffix_array().windows(2).zip(&lcp[1..]) {
+            let previous = gsa.suffix(window[0] as usize);
+            let current = gsa.suffix(window[1] as usize);
+            let expected = previous
+                .iter()
+                .zip(current)
+                .take_while(|(a, b)| a == b)
+                .count();
+            assert_eq!(lcp as usize, expected);
+        }
+    }
+
+    #[test]
+    fn test_locate() {
+        let documents: &[&[u8]] = &[b"banana", b"ananas", b"an"];
+        let gsa = GeneralizedSuffixArray::<u32>::new(documents);
+        let mut positions = gsa.locate(b"an").collect::<Vec<_>>();
+        positions.sort_unstable();
+        assert_eq!(positions, vec![1, 3, 6, 8, 12]);
+        assert_eq!(gsa.find(b"nas").len(), 1);
+        // occurrences never cross document boundaries
+        assert_eq!(gsa.find(b"aa").len(), 0);
+        assert_eq!(gsa.find(b"").len(), gsa.suffix_array().len());
+
+        let all = 0..gsa.suffix_array().len();
+        let a = gsa.refine(all, 0, b'a');
+        assert_eq!(a, gsa.find(b"a"));
+        assert_eq!(gsa.refine(a.clone(), 1, b'n'), gsa.find(b"an"));
+        assert_eq!(gsa.refine(a, 1, b'x').len(), 0);
+    }
+
+    #[test]
+    fn test_document_listing() {
+        let documents: &[&[u8]] = &[b"banana", b"ananas", b"an", b"nab", b"", b"bandana"];
+        let gsa = GeneralizedSuffixArray::<u32>::new(documents);
+        let listing = DocumentListing::new(&gsa);
+        let block = DocumentListing::<u32, BlockRmq<usize>>::with_backend(&gsa);
+        for pattern in [&b"an"[..], b"na", b"ba", b"nab", b"x", b"", b"dan"] {
+            let expected = (0..documents.len())
+                .filter(|&document| {
+                    documents[document]
+                        .windows(pattern.len().max(1))
+                        .any(|window| window.starts_with(pattern))
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(listing.list(gsa.find(pattern)), expected);
+            assert_eq!(block.list(gsa.find(pattern)), expected);
+        }
+    }
+
+    #[test]
+    fn test_generalized_empty() {
+        let gsa = GeneralizedSuffixArray::<u32>::new::<&[u8]>(&[]);
+        assert_eq!(gsa.document_count(), 0);
+        assert!(gsa.suffix_array().is_empty());
+
+        let gsa = GeneralizedSuffixArray::<u32>::new(&[b""]);
+        assert_eq!(gsa.document_count(), 1);
+        assert!(gsa.suffix_array().is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+use std::ops::Range;
+
+use crate::lcp::lcp_array;
+use crate::rmq::{Rmq, SparseTable};
+use crate::sais::{inverse, suffix_array};
+use crate::suffix_index::SuffixIndex;
+
+/// Longest common extension queries in constant time, the lcp of two suffixes is the minimum of
+/// the lcp array between their ranks
+#[derive(Debug, Clone)]
+pub struct LceIndex<I, R = SparseTable<I>> {
+    inverse: Vec<I>,
+    lcp: R,
+}
+
+impl<I: SuffixIndex> LceIndex<I> {
+    pub fn new(text: &[u8]) -> Self {
+        Self::with_backend(text)
+    }
+}
+
+impl<I: SuffixIndex, R: Rmq<I>> LceIndex<I, R> {
+    /// Builds the index with `R` as the range minimum backend
+    pub fn with_backend(text: &[u8]) -> Self {
+        let suffix_array = suffix_array(text);
+        let lcp = lcp_array(text, &suffix_array);
+        Self::from_parts(&suffix_array, lcp)
+    }
+
+    /// Builds the index from a suffix array and its lcp array
+    pub fn from_parts(suffix_array: &[I], lcp: Vec<I>) -> Self {
+        assert_eq!(suffix_array.len(), lcp.len());
+        Self {
+            inverse: inverse(suffix_array),
+            lcp: R::new(lcp),
+        }
+    }
+
+    /// The length of the indexed text
+    pub fn len(&self) -> usize {
+        self.inverse.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inverse.is_empty()
+    }
+
+    /// The rank of every suffix in the suffix array
+    pub fn inverse(&self) -> &[I] {
+        &self.inverse
+    }
+
+    /// The length of the longest common prefix of the suffixes starting at `i` and `j`
+    pub fn lce(&self, i: usize, j: usize) -> usize {
+        assert!(i < self.len() && j < self.len());
+        if i == j {
+            return self.len() - i;
+        }
+        let (i, j) = (self.inverse[i].as_index(), self.inverse[j].as_index());
+        self.lcp.min(i.min(j) + 1..i.max(j) + 1).as_index()
+    }
+
+    /// Whether `text[range]` has period `period`, every character equals the one `period`
+    /// positions later
+    pub fn has_period(&self, range: Range<usize>, period: usize) -> bool {
+        assert!(range.start <= range.end && range.end <= self.len());
+        period >= range.len() || self.lce(range.start, range.start + period) >= range.len() - period
+    }
+
+    /// The smallest period of `text[range]`, its length if it has no shorter period
+    pub fn smallest_period(&self, range: Range<usize>) -> usize {
+        (1..range.len())
+            .find(|&period| self.has_period(range.clone(), period))
+            .unwrap_or(range.len())
+    }
+
+    /// Whether `text[range]` is no power of a shorter word, the empty word is not primitive
+    pub fn is_primitive(&self, range: Range<usize>) -> bool {
+        // A period dividing the length is a multiple of the smallest period
+        let period = self.smallest_period(range.clone());
+        !range.is_empty() && (period == range.len() || !range.len().is_multiple_of(period))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rmq::BlockRmq;
+
+    #[test]
+    fn test_lce() {
+        let text = b"abracadabra mississippi abracadabra";
+        let index = LceIndex::<u32>::new(text);
+        let block = LceIndex::<u32, BlockRmq<u32>>::with_backend(text);
+        assert_eq!(index.len(), text.len());
+        for i in 0..text.len() {
+            for j in 0..text.len() {
+                let expected = text[i..]
+                    .iter()
+                    .zip(&text[j..])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                assert_eq!(index.lce(i, j), expected);
+                assert_eq!(block.lce(i, j), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_periods() {
+        let text = b"abaababaabaab";
+        let index = LceIndex::<u32>::new(text);
+        assert_eq!(index.smallest_period(0..text.len()), 8);
+        assert_eq!(index.smallest_period(0..3), 2);
+        assert_eq!(index.smallest_period(2..2), 0);
+        assert!(index.has_period(0..text.len(), 11));
+        assert!(!index.has_period(0..text.len(), 5));
+        assert!(index.is_primitive(0..text.len()));
+        assert!(!index.is_primitive(3..7));
+        assert!(!index.is_primitive(4..4));
+
+        let index = LceIndex::<u32>::new(b"abcabcabc");
+        assert_eq!(index.smallest_period(0..9), 3);
+        assert!(!index.is_primitive(0..9));
+        assert!(index.is_primitive(0..8));
+    }
+}
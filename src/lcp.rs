@@ -0,0 +1,184 @@
+use crate::sais::inverse;
+use crate::suffix_index::SuffixIndex;
+
+/// Computes the lcp array of `text` with Kasai's algorithm, the longest common prefix of every
+/// suffix with its predecessor in `suffix_array`. The first entry is 0.
+pub fn lcp_array<C: Eq, I: SuffixIndex>(text: &[C], suffix_array: &[I]) -> Vec<I> {
+    assert_eq!(text.len(), suffix_array.len());
+    let rank = inverse(suffix_array);
+
+    let mut lcp = vec![I::from_index(0); text.len()];
+    // The lcp decreases by at most one from one suffix to the next in text order
+    let mut h = 0;
+    for (position, index) in rank.iter().enumerate() {
+        let index = index.as_index();
+        if index == 0 {
+            h = 0;
+            continue;
+        }
+        let previous = suffix_array[index - 1].as_index();
+        h += text[position + h..]
+            .iter()
+            .zip(&text[previous + h..])
+            .take_while(|(a, b)| a == b)
+            .count();
+        lcp[index] = I::from_index(h);
+        h = h.saturating_sub(1);
+    }
+    lcp
+}
+
+/// Computes the permuted lcp array of `text` with the Φ algorithm, the lcp of every suffix with its
+/// predecessor in `suffix_array` in text order. The entry of the smallest suffix is 0.
+///
+/// Only `text`, `suffix_array` and one array of the index type are accessed, mostly sequentially.
+pub fn plcp_array<C: Eq, I: SuffixIndex>(text: &[C], suffix_array: &[I]) -> Vec<I> {
+    assert_eq!(text.len(), suffix_array.len());
+    // The predecessor of every suffix, replaced by its lcp in place
+    let mut plcp = vec![I::from_index(I::MAX); text.len()];
+    for window in suffix_array.windows(2) {
+        plcp[window[1].as_index()] = window[0];
+    }
+
+    let mut h = 0;
+    for position in 0..text.len() {
+        let previous = plcp[position];
+        if previous == I::from_index(I::MAX) {
+            plcp[position] = I::from_index(0);
+            h = 0;
+            continue;
+        }
+        let previous = previous.as_index();
+        h += text[position + h..]
+            .iter()
+            .zip(&text[previous + h..])
+            .take_while(|(a, b)| a == b)
+            .count();
+        plcp[position] = I::from_index(h);
+        h = h.saturating_sub(1);
+    }
+    plcp
+}
+
+/// The lcp array in suffix array order, computed on the fly from the permuted lcp array
+pub fn plcp_to_lcp<'a, I: SuffixIndex>(
+    plcp: &'a [I],
+    suffix_array: &'a [I],
+) -> impl ExactSizeIterator<Item = I> + 'a {
+    assert_eq!(plcp.len(), suffix_array.len());
+    suffix_array
+        .iter()
+        .map(move |suffix| plcp[suffix.as_index()])
+}
+
+/// Iterates the lcp intervals of `lcp` bottom-up, the internal nodes of the suffix tree in
+/// postorder (Kasai et al., 2001).
+///
+/// Yields `(lcp, begin, end)` for every maximal range `begin..end` of at least two suffixes
+/// sharing a prefix of length `lcp`, the root `(0, 0, n)` comes last.
+pub fn lcp_intervals<I: SuffixIndex>(lcp: &[I]) -> LcpIntervals<'_, I> {
+    LcpIntervals {
+        lcp,
+        stack: vec![(0, 0)],
+        index: 1,
+        begin: 0,
+    }
+}
+
+/// The iterator returned by [`lcp_intervals`]
+#[derive(Debug, Clone)]
+pub struct LcpIntervals<'a, I> {
+    lcp: &'a [I],
+    /// The lcp and begin of the open intervals, increasing in both
+    stack: Vec<(usize, usize)>,
+    index: usize,
+    /// The begin of an interval opened at `index`
+    begin: usize,
+}
+
+impl<I: SuffixIndex> Iterator for LcpIntervals<'_, I> {
+    type Item = (usize, usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.lcp.is_empty() {
+            return None;
+        }
+        loop {
+            let &(top, begin) = self.stack.last()?;
+            // All intervals are closed after the last suffix
+            let lcp = self.lcp.get(self.index).map(|lcp| lcp.as_index());
+            if lcp.is_none_or(|lcp| lcp < top) {
+                self.stack.pop();
+                self.begin = begin;
+                return Some((top, begin, self.index));
+            }
+            let lcp = lcp.unwrap();
+            if lcp > top {
+                self.stack.push((lcp, self.begin));
+            }
+            self.index += 1;
+            self.begin = self.index - 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sais::suffix_array;
+
+    #[test]
+    fn test_lcp_array() {
+        for text in [
+            &b""[..],
+            b"a",
+            b"banana",
+            b"mississippi",
+            b"aaaaaa",
+            b"abcabcabd",
+        ] {
+            let suffix_array = suffix_array::<u32>(text);
+            let expected = (0..text.len())
+                .map(|index| {
+                    if index == 0 {
+                        return 0;
+                    }
+                    let a = &text[suffix_array[index - 1] as usize..];
+                    let b = &text[suffix_array[index] as usize..];
+                    a.iter().zip(b).take_while(|(a, b)| a == b).count() as u32
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(lcp_array(text, &suffix_array), expected);
+            let plcp = plcp_array(text, &suffix_array);
+            assert_eq!(
+                plcp_to_lcp(&plcp, &suffix_array).collect::<Vec<_>>(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_lcp_intervals() {
+        let text = b"acaaacatat";
+        let suffix_array = suffix_array::<u32>(text);
+        let lcp = lcp_array(text, &suffix_array);
+        let intervals = lcp_intervals(&lcp).collect::<Vec<_>>();
+        assert_eq!(
+            intervals,
+            vec![
+                (2, 0, 2),
+                (3, 2, 4),
+                (2, 4, 6),
+                (1, 0, 6),
+                (2, 6, 8),
+                (1, 8, 10),
+                (0, 0, 10)
+            ]
+        );
+        assert_eq!(lcp_intervals::<u32>(&[]).count(), 0);
+        assert_eq!(
+            lcp_intervals::<u32>(&[0]).collect::<Vec<_>>(),
+            vec![(0, 0, 1)]
+        );
+    }
+}
@@ -1,7 +1,46 @@
-pub use sais::sort;
+pub use error::SaisError;
+pub use sais::{
+    inverse, inverse_into, sort, sort_cancellable, sort_packed, sort_with_inverse, sort_with_lcp,
+    sort_with_progress, sort_with_stats, sort_without_types, str_suffix_array, suffix_array,
+    suffix_array_with_lcp, try_sort, try_suffix_array, u16_suffix_array, Type, Types, Workspace,
+};
+pub use suffix_array::SuffixArray;
 pub use suffix_index::*;
 
+pub mod alphabet;
+pub mod analysis;
+pub mod bwt;
+pub mod collection;
+pub mod dc3;
+pub mod debruijn;
+pub mod delta;
+pub mod divsufsort;
+pub mod dna;
+pub mod doubling;
+pub mod esa;
+#[cfg(feature = "fasta")]
+pub mod fasta;
+pub mod fm;
+pub mod generalized;
+pub mod lce;
+pub mod lcp;
+pub mod merge;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+pub mod profile;
+pub mod progress;
+pub mod protein;
 pub mod radix_sort;
+pub mod rindex;
+pub mod rmq;
+pub mod runlength;
+pub mod search;
+pub mod sorter;
+pub mod sparse;
+pub mod suffix_tree;
+pub mod wavelet;
 
+mod error;
 mod sais;
+mod suffix_array;
 mod suffix_index;
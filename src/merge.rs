@@ -0,0 +1,193 @@
+use std::cmp::Ordering;
+
+use crate::lce::LceIndex;
+use crate::lcp::lcp_array;
+use crate::sorter::{Sais, SuffixSorter};
+use crate::suffix_index::{AsIndex, SuffixIndex};
+
+/// Computes the suffix array of `text` from the suffix arrays of its shards of `shard_len`
+/// characters, which are built independently and merged pairwise in a balanced tree, in parallel
+/// with the `parallel` feature
+pub fn sharded_suffix_array<C: AsIndex + Ord, I: SuffixIndex>(
+    text: &[C],
+    shard_len: usize,
+) -> Vec<I> {
+    assert!(shard_len > 0);
+    assert!(text.len() < I::MAX, "text is too large for the index type");
+    if text.is_empty() {
+        return Vec::new();
+    }
+    merge_shards(text, shard_len)
+}
+
+/// The suffix array of `text` from the shards of its left and right half
+fn merge_shards<C: AsIndex + Ord, I: SuffixIndex>(text: &[C], shard_len: usize) -> Vec<I> {
+    let shards = text.len().div_ceil(shard_len);
+    if shards == 1 {
+        return Sais.suffix_array(text);
+    }
+    let (left, right) = text.split_at(shards / 2 * shard_len);
+    #[cfg(feature = "parallel")]
+    let (left_sorted, right_sorted) = rayon::join(
+        || merge_shards::<_, I>(left, shard_len),
+        || merge_shards::<_, I>(right, shard_len),
+    );
+    #[cfg(not(feature = "parallel"))]
+    let (left_sorted, right_sorted) = (
+        merge_shards::<_, I>(left, shard_len),
+        merge_shards::<_, I>(right, shard_len),
+    );
+    merge(left, &left_sorted, right, &right_sorted)
+}
+
+/// Merges the suffix arrays of `a` and `b` into the suffix array of their concatenation.
+///
+/// The number of suffixes of `b` smaller than every suffix of `a` in the concatenation is found
+/// by backward search over `b` from right to left. The suffixes of `a` keep their order in
+/// `suffix_array_a` unless one is a prefix of the other, which is then decided by the position of
+/// its continuation relative to `b`.
+///
+/// Appending a block to an indexed text only sorts the block and reuses the order of the existing
+/// suffix array instead of rebuilding it.
+pub fn merge<C: AsIndex + Ord, I: SuffixIndex>(
+    a: &[C],
+    suffix_array_a: &[I],
+    b: &[C],
+    suffix_array_b: &[I],
+) -> Vec<I> {
+    assert_eq!(a.len(), suffix_array_a.len());
+    assert_eq!(b.len(), suffix_array_b.len());
+    assert!(
+        a.len() + b.len() < I::MAX,
+        "text is too large for the index type"
+    );
+    if a.is_empty() || b.is_empty() {
+        return suffix_array_a
+            .iter()
+            .copied()
+            .chain(
+                suffix_array_b
+                    .iter()
+                    .map(|&suffix| suffix + I::from_index(a.len())),
+            )
+            .collect();
+    }
+    let rank_b = suffix_array_b
+        .iter()
+        .position(|suffix| suffix.as_index() == 0)
+        .unwrap();
+    let gaps = gaps(a, b, suffix_array_b, rank_b);
+
+    let lce = LceIndex::<I>::from_parts(suffix_array_a, lcp_array(a, suffix_array_a));
+    let mut order = suffix_array_a.to_vec();
+    // Mostly sorted already, which the merge sort detects
+    order.sort_by(|i, j| {
+        let (i, j) = (i.as_index(), j.as_index());
+        gaps[i].cmp(&gaps[j]).then_with(|| {
+            let common = lce.lce(i, j);
+            if common < a.len() - i.max(j) {
+                return a[i + common].cmp(&a[j + common]);
+            }
+            // The shorter suffix continues with `b`, the longer one with a suffix of `a`
+            let (shorter, longer) = if i > j { (i, j) } else { (j, i) };
+            let continuation = longer + a.len() - shorter;
+            let shorter_first = if gaps[continuation] > rank_b {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            };
+            if shorter == i {
+                shorter_first
+            } else {
+                shorter_first.reverse()
+            }
+        })
+    });
+
+    let mut merged = Vec::with_capacity(a.len() + b.len());
+    let mut order = order.into_iter().peekable();
+    for (rank, &suffix) in suffix_array_b.iter().enumerate() {
+        while let Some(suffix) = order.next_if(|suffix| gaps[suffix.as_index()] <= rank) {
+            merged.push(suffix);
+        }
+        merged.push(suffix + I::from_index(a.len()));
+    }
+    merged.extend(order);
+    merged
+}
+
+/// The number of suffixes of `b` smaller than every suffix of `a` followed by `b`, where `b` has
+/// the rank `rank_b` among its suffixes
+fn gaps<C: AsIndex + Ord, I: SuffixIndex>(
+    a: &[C],
+    b: &[C],
+    suffix_array_b: &[I],
+    rank_b: usize,
+) -> Vec<usize> {
+    let mut sorted = b.iter().collect::<Vec<_>>();
+    sorted.sort_unstable();
+    // The character preceding every suffix of `b` in sorted order, after the empty suffix
+    let mut preceding = std::iter::once(b.last())
+        .chain(
+            suffix_array_b
+                .iter()
+                .map(|suffix| suffix.as_index().checked_sub(1).map(|i| &b[i])),
+        )
+        .enumerate()
+        .filter_map(|(index, c)| Some((c?, index)))
+        .collect::<Vec<_>>();
+    preceding.sort_unstable();
+
+    let mut gaps = vec![0; a.len()];
+    let mut gap = rank_b;
+    for (i, c) in a.iter().enumerate().rev() {
+        let occurrences = preceding.partition_point(|&(d, index)| (d, index) < (c, gap + 1))
+            - preceding.partition_point(|&(d, _)| d < c);
+        gap = sorted.partition_point(|&d| d < c) + occurrences;
+        gaps[i] = gap;
+    }
+    gaps
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sais::suffix_array;
+
+    #[test]
+    fn test_sharded_suffix_array() {
+        let mut state = 9u32;
+        let random = (0..500)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                b"ab"[state as usize % 2]
+            })
+            .collect::<Vec<_>>();
+        let repeats = b"abaab".repeat(100);
+        for text in [&b"mississippi"[..], b"aaaaaaa", b"aab", &random, &repeats].iter() {
+            for &shard_len in [1, 2, 3, 7, 64, 5000].iter() {
+                let sorted: Vec<u32> = sharded_suffix_array(text, shard_len);
+                assert_eq!(sorted, suffix_array::<u32>(text));
+            }
+        }
+        assert!(sharded_suffix_array::<u8, u32>(b"", 3).is_empty());
+    }
+
+    #[test]
+    fn test_merge() {
+        // The suffixes of `a` change their order across the boundary
+        let merged = merge(b"aa", &[1u32, 0], b"b", &[0]);
+        assert_eq!(merged, suffix_array::<u32>(b"aab"));
+
+        let blocks = [&b"abracadabra"[..], b"abra", b"", b"cadabra", b"a"];
+        let mut text = Vec::new();
+        let mut indexed: Vec<u32> = Vec::new();
+        for block in blocks.iter() {
+            indexed = merge(&text, &indexed, block, &suffix_array::<u32>(block));
+            text.extend_from_slice(block);
+            assert_eq!(indexed, suffix_array::<u32>(&text));
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+#[cfg(unix)]
+use memmap2::Advice;
+pub use memmap2::Mmap;
+
+use crate::sais::{induced_sort, Types};
+use crate::suffix_index::SuffixIndex;
+
+/// Maps the file at `path` read-only, the mapping derefs to `&[u8]` and is indexed like any other
+/// text without reading it into memory first.
+///
+/// The mapping is advised for sequential access, which suits the passes that read the text from
+/// front to back, see [`suffix_array`] for a construction that advises every phase.
+///
+/// # Safety
+/// The file must not be modified or truncated while it is mapped, see [`Mmap::map`]
+pub unsafe fn map_text<P: AsRef<Path>>(path: P) -> io::Result<Mmap> {
+    let file = File::open(path)?;
+    let mmap = Mmap::map(&file)?;
+    #[cfg(unix)]
+    advise(&mmap, Advice::Sequential)?;
+    Ok(mmap)
+}
+
+/// The number of occurrences of every byte of `text`, in one pass from front to back
+pub fn histogram(text: &[u8]) -> [usize; 0x100] {
+    let mut counts = [0; 0x100];
+    for &c in text {
+        counts[c as usize] += 1;
+    }
+    counts
+}
+
+/// Computes the suffix array of a mapped text.
+///
+/// The pages are faulted in order by a [`histogram`] pass with sequential read-ahead, then the
+/// mapping is advised for random access, which is how the induction reads the text. The histogram
+/// limits the buckets to the largest byte that occurs.
+pub fn suffix_array<I: SuffixIndex>(text: &Mmap) -> io::Result<Vec<I>> {
+    assert!(text.len() < I::MAX, "text is too large for the index type");
+    if text.is_empty() {
+        return Ok(Vec::new());
+    }
+    #[cfg(unix)]
+    advise(text, Advice::Sequential)?;
+    let counts = histogram(text);
+    #[cfg(unix)]
+    advise(text, Advice::Random)?;
+
+    let alphabet = counts.iter().rposition(|&count| count != 0).unwrap() + 1;
+    let mut suffix_array = vec![I::from_index(0); text.len()];
+    let mut buckets = vec![I::from_index(0); alphabet];
+    induced_sort(
+        text,
+        &mut suffix_array,
+        &mut Types::new(text.len()),
+        &mut buckets,
+    );
+    Ok(suffix_array)
+}
+
+/// Advises the kernel on how `mmap` is accessed, empty mappings can not be advised
+#[cfg(unix)]
+fn advise(mmap: &Mmap, advice: Advice) -> io::Result<()> {
+    if mmap.is_empty() {
+        return Ok(());
+    }
+    mmap.advise(advice)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sais::suffix_array;
+
+    #[test]
+    fn test_map_text() {
+        let path = std::env::temp_dir().join(format!("sais-mmap-{}", std::process::id()));
+        std::fs::write(&path, b"mississippi").unwrap();
+        // Safety: the file is private to this test
+        let text = unsafe { map_text(&path) }.unwrap();
+        assert_eq!(
+            suffix_array::<u32>(&text),
+            suffix_array::<u32>(b"mississippi")
+        );
+        assert_eq!(
+            super::suffix_array::<u32>(&text).unwrap(),
+            suffix_array::<u32>(b"mississippi")
+        );
+        assert_eq!(histogram(&text)[b's' as usize], 4);
+        drop(text);
+        std::fs::remove_file(path).unwrap();
+    }
+}
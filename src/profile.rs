@@ -0,0 +1,359 @@
+use std::num::NonZeroUsize;
+use std::thread::available_parallelism;
+
+use crate::error::SaisError;
+use crate::sais::{sort_without_types, suffix_array};
+use crate::suffix_index::{AsIndex, SuffixIndex};
+
+/// The width of the indices of a suffix array
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum IndexWidth {
+    U8,
+    U32,
+    U64,
+}
+
+impl IndexWidth {
+    /// The smallest width that can index a text of `len` characters
+    pub fn smallest(len: usize) -> Self {
+        if len < u8::MAX as usize {
+            IndexWidth::U8
+        } else if len < u32::MAX as usize {
+            IndexWidth::U32
+        } else {
+            IndexWidth::U64
+        }
+    }
+
+    /// The size of one index in bytes
+    pub fn bytes(&self) -> usize {
+        match self {
+            IndexWidth::U8 => 1,
+            IndexWidth::U32 => 4,
+            IndexWidth::U64 => 8,
+        }
+    }
+
+    /// The largest index
+    fn max_index(&self) -> usize {
+        match self {
+            IndexWidth::U8 => u8::MAX as usize,
+            IndexWidth::U32 => u32::MAX as usize,
+            IndexWidth::U64 => u64::MAX as usize,
+        }
+    }
+}
+
+/// How the types of the suffixes are kept during the construction
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Variant {
+    /// One bit per character in a separate buffer, see [`sort`](crate::sort)
+    Types,
+    /// In the highest bit of the indices like SACA-K, see
+    /// [`sort_without_types`](crate::sort_without_types)
+    WithoutTypes,
+}
+
+impl Variant {
+    /// Whether the variant can sort a byte text of `len` characters with `width`
+    pub fn supports(&self, len: usize, width: IndexWidth) -> bool {
+        match self {
+            Variant::Types => len < width.max_index(),
+            Variant::WithoutTypes => len < width.max_index() / 2 + 1,
+        }
+    }
+
+    /// The peak memory in bytes of the sequential construction of the suffix array of a byte text
+    /// of `text_len` characters, including the suffix array but not the text.
+    ///
+    /// The buckets hold one index per name of the reduced text in the recursion, at most half of
+    /// the text.
+    pub fn memory(&self, text_len: usize, width: IndexWidth) -> usize {
+        let buckets = (u8::MAX as usize + 1).max(text_len / 2) * width.bytes();
+        let types = match self {
+            Variant::Types => text_len.div_ceil(64) * 8,
+            Variant::WithoutTypes => 0,
+        };
+        text_len * width.bytes() + buckets + types
+    }
+}
+
+/// The peak memory in bytes of [`suffix_array`](crate::suffix_array) for a byte text of
+/// `text_len` characters and indices of `index_width`, see [`Variant::memory`]
+pub fn estimate_memory(text_len: usize, index_width: IndexWidth) -> usize {
+    Variant::Types.memory(text_len, index_width)
+}
+
+/// A suffix array with an index width chosen at runtime
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Indices {
+    U8(Vec<u8>),
+    U32(Vec<u32>),
+    U64(Vec<u64>),
+}
+
+impl Indices {
+    pub fn width(&self) -> IndexWidth {
+        match self {
+            Indices::U8(_) => IndexWidth::U8,
+            Indices::U32(_) => IndexWidth::U32,
+            Indices::U64(_) => IndexWidth::U64,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Indices::U8(indices) => indices.len(),
+            Indices::U32(indices) => indices.len(),
+            Indices::U64(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: usize) -> usize {
+        match self {
+            Indices::U8(indices) => indices[index].as_index(),
+            Indices::U32(indices) => indices[index].as_index(),
+            Indices::U64(indices) => indices[index].as_index(),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len()).map(move |index| self.get(index))
+    }
+}
+
+/// Ready-made construction settings for common constraints
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum Profile {
+    /// 32 bit indices where possible, types and all available threads
+    Fastest,
+    /// The smallest index width that fits the text, no types where possible and a single thread
+    LowestMemory,
+    /// 32 bit indices where possible, types and a single thread
+    #[default]
+    Balanced,
+}
+
+impl Profile {
+    pub fn index_width(&self, len: usize) -> IndexWidth {
+        match self {
+            Profile::LowestMemory => IndexWidth::smallest(len),
+            Profile::Fastest | Profile::Balanced => IndexWidth::smallest(len).max(IndexWidth::U32),
+        }
+    }
+
+    /// How the types are kept: without types for [`Profile::LowestMemory`] if the index width
+    /// allows it, with types otherwise
+    pub fn variant(&self, len: usize) -> Variant {
+        match self {
+            Profile::LowestMemory if Variant::WithoutTypes.supports(len, self.index_width(len)) => {
+                Variant::WithoutTypes
+            }
+            _ => Variant::Types,
+        }
+    }
+
+    /// The number of threads a construction may use with the `parallel` feature
+    pub fn threads(&self) -> NonZeroUsize {
+        match self {
+            Profile::Fastest => available_parallelism().unwrap_or(NonZeroUsize::MIN),
+            Profile::LowestMemory | Profile::Balanced => NonZeroUsize::MIN,
+        }
+    }
+
+    /// Computes the suffix array of `text` with the settings of this profile
+    pub fn suffix_array(&self, text: &[u8]) -> Indices {
+        let (width, variant) = (self.index_width(text.len()), self.variant(text.len()));
+        with_threads(self.threads(), || build_with(text, width, variant))
+    }
+}
+
+/// Construction settings that respect a memory budget
+#[derive(Debug, Clone, Default)]
+pub struct Builder {
+    profile: Profile,
+    max_memory: Option<usize>,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn profile(&mut self, profile: Profile) -> &mut Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Limits the peak memory of the construction to `bytes`, see [`Variant::memory`]. With the
+    /// `parallel` feature a construction with a budget runs on a single thread, the buffers of the
+    /// parallel passes are not part of the estimate.
+    pub fn max_memory(&mut self, bytes: usize) -> &mut Self {
+        self.max_memory = Some(bytes);
+        self
+    }
+
+    /// The index width and variant for a text of `len` characters: the width and variant of the
+    /// profile if it fits the budget, otherwise the other variant and then the smallest width
+    pub fn plan(&self, len: usize) -> Result<(IndexWidth, Variant), SaisError> {
+        let budget = self.max_memory.unwrap_or(usize::MAX);
+        let (width, smallest) = (self.profile.index_width(len), IndexWidth::smallest(len));
+        let (preferred, other) = match self.profile.variant(len) {
+            Variant::Types => (Variant::Types, Variant::WithoutTypes),
+            Variant::WithoutTypes => (Variant::WithoutTypes, Variant::Types),
+        };
+        let candidates = [
+            (width, preferred),
+            (width, other),
+            (smallest, preferred),
+            (smallest, other),
+        ];
+        let supported = candidates
+            .iter()
+            .copied()
+            .filter(|(width, variant)| variant.supports(len, *width));
+        supported
+            .clone()
+            .find(|(width, variant)| variant.memory(len, *width) <= budget)
+            .ok_or_else(|| SaisError::MemoryBudgetExceeded {
+                required: supported
+                    .map(|(width, variant)| variant.memory(len, width))
+                    .min()
+                    .unwrap_or(usize::MAX),
+                budget,
+            })
+    }
+
+    /// Computes the suffix array of `text` within the memory budget
+    pub fn build(&self, text: &[u8]) -> Result<Indices, SaisError> {
+        let (width, variant) = self.plan(text.len())?;
+        // The estimate only covers the sequential construction
+        let threads = match self.max_memory {
+            Some(_) => NonZeroUsize::MIN,
+            None => self.profile.threads(),
+        };
+        Ok(with_threads(threads, || build_with(text, width, variant)))
+    }
+}
+
+/// Runs `f` in a thread pool of `threads` threads with the `parallel` feature
+#[cfg(feature = "parallel")]
+fn with_threads<T: Send>(threads: NonZeroUsize, f: impl FnOnce() -> T + Send) -> T {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads.get())
+        .build()
+        .expect("failed to create the thread pool")
+        .install(f)
+}
+
+#[cfg(not(feature = "parallel"))]
+fn with_threads<T>(_threads: NonZeroUsize, f: impl FnOnce() -> T) -> T {
+    f()
+}
+
+fn build_with(text: &[u8], width: IndexWidth, variant: Variant) -> Indices {
+    match width {
+        IndexWidth::U8 => Indices::U8(sort_with(text, variant)),
+        IndexWidth::U32 => Indices::U32(sort_with(text, variant)),
+        IndexWidth::U64 => Indices::U64(sort_with(text, variant)),
+    }
+}
+
+fn sort_with<I: SuffixIndex>(text: &[u8], variant: Variant) -> Vec<I> {
+    match variant {
+        Variant::Types => suffix_array(text),
+        Variant::WithoutTypes => {
+            let mut suffix_array = vec![I::from_index(0); text.len()];
+            let mut buckets = vec![I::from_index(0); u8::MAX as usize + 1];
+            sort_without_types(text, &mut suffix_array, &mut buckets);
+            suffix_array
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_profiles() {
+        let text = b"mississippi";
+        let expected = suffix_array::<usize>(text);
+        for profile in [Profile::Fastest, Profile::LowestMemory, Profile::Balanced] {
+            let indices = profile.suffix_array(text);
+            assert_eq!(indices.iter().collect::<Vec<_>>(), expected);
+        }
+        assert_eq!(
+            Profile::LowestMemory.suffix_array(text).width(),
+            IndexWidth::U8
+        );
+        assert_eq!(
+            Profile::default().suffix_array(text).width(),
+            IndexWidth::U32
+        );
+        assert_eq!(Profile::Balanced.index_width(1 << 40), IndexWidth::U64);
+        assert_eq!(Profile::LowestMemory.variant(100), Variant::WithoutTypes);
+        assert_eq!(Profile::LowestMemory.variant(200), Variant::Types);
+        assert_eq!(Profile::Fastest.variant(100), Variant::Types);
+        assert_eq!(Profile::Balanced.threads(), NonZeroUsize::MIN);
+        assert_eq!(IndexWidth::smallest(300), IndexWidth::U32);
+    }
+
+    #[test]
+    fn test_max_memory() {
+        let text = b"abracadabra".repeat(30);
+        let len = text.len();
+        let expected = suffix_array::<usize>(&text);
+        assert_eq!(
+            estimate_memory(len, IndexWidth::U32),
+            330 * 4 + 256 * 4 + 48
+        );
+        let types = estimate_memory(len, IndexWidth::U32);
+        let without_types = Variant::WithoutTypes.memory(len, IndexWidth::U32);
+        for (budget, plan) in [
+            (types, (IndexWidth::U32, Variant::Types)),
+            (without_types, (IndexWidth::U32, Variant::WithoutTypes)),
+        ] {
+            let mut builder = Builder::new();
+            builder.max_memory(budget);
+            assert_eq!(builder.plan(len), Ok(plan));
+            let indices = builder.build(&text).unwrap();
+            assert_eq!(indices.iter().collect::<Vec<_>>(), expected);
+        }
+        assert_eq!(
+            Builder::new().max_memory(1000).build(&text),
+            Err(SaisError::MemoryBudgetExceeded {
+                required: Variant::WithoutTypes.memory(len, IndexWidth::U32),
+                budget: 1000
+            })
+        );
+        // Too long for the smallest width without types
+        let text = &text[..200];
+        let required = match Builder::new().max_memory(0).plan(text.len()) {
+            Err(SaisError::MemoryBudgetExceeded { required, .. }) => required,
+            plan => panic!("unexpected plan {:?}", plan),
+        };
+        assert_eq!(required, Variant::Types.memory(text.len(), IndexWidth::U8));
+        let indices = Builder::new().max_memory(required).build(text).unwrap();
+        assert_eq!(
+            indices.iter().collect::<Vec<_>>(),
+            suffix_array::<usize>(text)
+        );
+        let short = b"mississippi";
+        let mut builder = Builder::new();
+        builder.max_memory(Variant::WithoutTypes.memory(short.len(), IndexWidth::U8));
+        assert_eq!(
+            builder.plan(short.len()),
+            Ok((IndexWidth::U8, Variant::WithoutTypes))
+        );
+        let indices = builder.build(short).unwrap();
+        assert_eq!(
+            indices.iter().collect::<Vec<_>>(),
+            suffix_array::<usize>(short)
+        );
+    }
+}
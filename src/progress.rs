@@ -0,0 +1,130 @@
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// A phase of a suffix array construction
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Phase {
+    /// Classifying suffixes into L and S types
+    Classify,
+    /// Sorting and naming the LMS substrings
+    SortLms,
+    /// Sorting the reduced string, reported once it is done
+    Recurse,
+    /// Inducing the order of all suffixes from the sorted LMS suffixes
+    Induce,
+}
+
+impl Phase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Classify => "classify",
+            Phase::SortLms => "sort lms",
+            Phase::Recurse => "recurse",
+            Phase::Induce => "induce",
+        }
+    }
+}
+
+/// A checkpoint reported after a phase has finished and periodically while inducing
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Progress {
+    pub phase: Phase,
+    /// The recursion depth of the phase, 0 for the input text
+    pub depth: usize,
+    /// Estimated fraction of the whole construction that is complete, in `0.0..=1.0`
+    pub fraction: f64,
+    /// The length of the text processed by the phase, the input or a reduced string
+    pub bytes: usize,
+}
+
+/// The text of one recursion level and the time spent in its phases, excluding deeper levels
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct Level {
+    /// The length of the input or reduced string
+    pub len: usize,
+    pub classify: Duration,
+    pub sort_lms: Duration,
+    /// Mapping the sorted reduced string back to the LMS suffixes
+    pub recurse: Duration,
+    pub induce: Duration,
+}
+
+impl Level {
+    pub fn duration(&self, phase: Phase) -> Duration {
+        match phase {
+            Phase::Classify => self.classify,
+            Phase::SortLms => self.sort_lms,
+            Phase::Recurse => self.recurse,
+            Phase::Induce => self.induce,
+        }
+    }
+
+    pub(crate) fn duration_mut(&mut self, phase: Phase) -> &mut Duration {
+        match phase {
+            Phase::Classify => &mut self.classify,
+            Phase::SortLms => &mut self.sort_lms,
+            Phase::Recurse => &mut self.recurse,
+            Phase::Induce => &mut self.induce,
+        }
+    }
+}
+
+/// Statistics of a construction to diagnose slow inputs
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct Stats {
+    /// Every recursion level from the input text down to the deepest reduced string
+    pub levels: Vec<Level>,
+    /// The number of LMS suffixes of the input text
+    pub lms_suffixes: usize,
+    /// The capacity of the types and buckets in bytes after the construction. The buckets keep the
+    /// capacity of the largest alphabet of all levels. Temporary buffers such as the lcp of the
+    /// LMS suffixes or those of the parallel construction are not included.
+    pub scratch_capacity: usize,
+}
+
+impl Stats {
+    /// The deepest recursion level, 0 without recursion
+    pub fn depth(&self) -> usize {
+        self.levels.len().saturating_sub(1)
+    }
+
+    /// The time spent in all levels
+    pub fn total(&self) -> Duration {
+        self.levels
+            .iter()
+            .map(|level| level.classify + level.sort_lms + level.recurse + level.induce)
+            .sum()
+    }
+}
+
+/// The construction was aborted by a [`CancellationToken`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("suffix array construction was cancelled")
+    }
+}
+
+impl Error for Cancelled {}
+
+/// Polled between the phases of a construction and periodically while inducing, the construction
+/// stops once it is cancelled
+pub trait CancellationToken {
+    fn is_cancelled(&self) -> bool;
+}
+
+impl CancellationToken for AtomicBool {
+    fn is_cancelled(&self) -> bool {
+        self.load(Ordering::Relaxed)
+    }
+}
+
+impl<F: Fn() -> bool> CancellationToken for F {
+    fn is_cancelled(&self) -> bool {
+        self()
+    }
+}
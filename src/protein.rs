@@ -0,0 +1,292 @@
+use std::ops::Range;
+
+use crate::dna::{reverse_complement, Strand};
+use crate::generalized::{Builder, GeneralizedSuffixArray};
+use crate::suffix_index::SuffixIndex;
+
+/// The standard genetic code, codons in `TCAG` order
+const GENETIC_CODE: &[u8; 64] = b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG";
+
+fn base_index(c: u8) -> Option<usize> {
+    match c.to_ascii_uppercase() {
+        b'T' | b'U' => Some(0),
+        b'C' => Some(1),
+        b'A' => Some(2),
+        b'G' => Some(3),
+        _ => None,
+    }
+}
+
+/// Translates a codon with the standard genetic code, stops are `*` and unknown codons `X`
+pub fn translate_codon(codon: &[u8]) -> u8 {
+    debug_assert_eq!(codon.len(), 3);
+    codon
+        .iter()
+        .try_fold(0, |index, &c| base_index(c).map(|c| index * 4 + c))
+        .map_or(b'X', |index| GENETIC_CODE[index])
+}
+
+/// Translates all complete codons of `sequence`
+pub fn translate(sequence: &[u8]) -> Vec<u8> {
+    sequence.chunks_exact(3).map(translate_codon).collect()
+}
+
+/// A mapping of amino acids to representatives of their group.
+///
+/// Characters not contained in any group are mapped to their uppercase form.
+#[derive(Debug, Clone)]
+pub struct ReducedAlphabet {
+    map: [u8; 0x100],
+}
+
+impl ReducedAlphabet {
+    /// The unreduced alphabet, only normalizes case
+    pub fn identity() -> Self {
+        Self::from_groups(&[])
+    }
+
+    /// Every group is represented by its first character, matching is case insensitive
+    pub fn from_groups(groups: &[&[u8]]) -> Self {
+        let mut map = [0u8; 0x100];
+        for (c, value) in map.iter_mut().enumerate() {
+            *value = (c as u8).to_ascii_uppercase();
+        }
+        for group in groups {
+            let representative = group[0].to_ascii_uppercase();
+            for &c in group.iter() {
+                map[c.to_ascii_uppercase() as usize] = representative;
+                map[c.to_ascii_lowercase() as usize] = representative;
+            }
+        }
+        Self { map }
+    }
+
+    /// Murphy et al. (2000) reduction to 10 groups
+    pub fn murphy10() -> Self {
+        Self::from_groups(&[
+            b"LVIM", b"C", b"A", b"G", b"ST", b"P", b"FYW", b"EDNQ", b"KR", b"H",
+        ])
+    }
+
+    /// Murphy et al. (2000) reduction to 8 groups
+    pub fn murphy8() -> Self {
+        Self::from_groups(&[b"LVIMC", b"AG", b"ST", b"P", b"FYW", b"EDNQ", b"KR", b"H"])
+    }
+
+    /// Murphy et al. (2000) reduction to 4 groups
+    pub fn murphy4() -> Self {
+        Self::from_groups(&[b"LVIMC", b"AGSTP", b"FYW", b"EDNQKRH"])
+    }
+
+    pub fn map(&self, c: u8) -> u8 {
+        self.map[c as usize]
+    }
+
+    pub fn apply(&self, sequence: &[u8]) -> Vec<u8> {
+        sequence.iter().map(|&c| self.map(c)).collect()
+    }
+}
+
+/// Index over protein sequences in a reduced alphabet.
+///
+/// Reduction maps every character on its own, so positions are the same in the original and
+/// the reduced sequences. Positions refer to the concatenation of the indexed sequences.
+#[derive(Debug, Clone)]
+pub struct ProteinIndex<I> {
+    original: Vec<u8>,
+    index: GeneralizedSuffixArray<I>,
+    alphabet: ReducedAlphabet,
+}
+
+impl<I: SuffixIndex> ProteinIndex<I> {
+    pub fn new<D: AsRef<[u8]>>(sequences: &[D], alphabet: ReducedAlphabet) -> Self {
+        let mut builder = Builder::new();
+        let mut original = Vec::new();
+        for sequence in sequences {
+            let sequence = sequence.as_ref();
+            builder.push(&alphabet.apply(sequence));
+            original.extend_from_slice(sequence);
+        }
+        Self {
+            original,
+            index: builder.build(),
+            alphabet,
+        }
+    }
+
+    /// The concatenation of the original sequences
+    pub fn original(&self) -> &[u8] {
+        &self.original
+    }
+
+    /// The index over the reduced sequences
+    pub fn index(&self) -> &GeneralizedSuffixArray<I> {
+        &self.index
+    }
+
+    pub fn alphabet(&self) -> &ReducedAlphabet {
+        &self.alphabet
+    }
+
+    /// Returns the positions of all occurrences of `pattern` in the reduced alphabet in ascending
+    /// order
+    pub fn locate(&self, pattern: &[u8]) -> Vec<usize> {
+        let pattern = self.alphabet.apply(pattern);
+        let mut positions = self.index.locate(&pattern).collect::<Vec<_>>();
+        positions.sort_unstable();
+        positions
+    }
+
+    /// The original characters of a hit of `pattern` at `position`
+    pub fn hit(&self, position: usize, pattern: &[u8]) -> &[u8] {
+        &self.original[position..position + pattern.len()]
+    }
+}
+
+/// A reading frame, `offset` is the number of skipped nucleotides at the start of the strand
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Frame {
+    pub strand: Strand,
+    pub offset: usize,
+}
+
+/// A protein pattern hit in a translated frame
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TranslatedHit {
+    /// The forward strand nucleotides encoding the hit
+    pub nucleotides: Range<usize>,
+    pub frame: Frame,
+}
+
+/// Index over the six translation frames of nucleotide sequences.
+///
+/// Nucleotide positions refer to the concatenation of the indexed sequences.
+#[derive(Debug, Clone)]
+pub struct SixFrameIndex<I> {
+    index: GeneralizedSuffixArray<I>,
+    /// The nucleotide range and frame of every translated document
+    frames: Vec<(Range<usize>, Frame)>,
+}
+
+impl<I: SuffixIndex> SixFrameIndex<I> {
+    pub fn new<D: AsRef<[u8]>>(sequences: &[D]) -> Self {
+        let mut builder = Builder::new();
+        let mut frames = Vec::new();
+        let mut start = 0;
+        for sequence in sequences {
+            let sequence = sequence.as_ref();
+            let nucleotides = start..start + sequence.len();
+            let reverse = reverse_complement(sequence);
+            for (strand, strand_sequence) in
+                [(Strand::Forward, sequence), (Strand::Reverse, &reverse)]
+            {
+                for offset in 0..3 {
+                    builder.push(&translate(
+                        strand_sequence.get(offset..).unwrap_or_default(),
+                    ));
+                    frames.push((nucleotides.clone(), Frame { strand, offset }));
+                }
+            }
+            start = nucleotides.end;
+        }
+        Self {
+            index: builder.build(),
+            frames,
+        }
+    }
+
+    /// The index over all translated frames, six consecutive documents per sequence
+    pub fn index(&self) -> &GeneralizedSuffixArray<I> {
+        &self.index
+    }
+
+    /// Locates a protein pattern in all frames, hits are sorted by nucleotide position
+    pub fn locate(&self, pattern: &[u8]) -> Vec<TranslatedHit> {
+        let codons = 3 * pattern.len();
+        let mut hits = self
+            .index
+            .locate(pattern)
+            .map(|position| {
+                let (document, amino_acid) = self.index.document_position(position);
+                let (nucleotides, frame) = self.frames[document].clone();
+                let start = frame.offset + 3 * amino_acid;
+                let nucleotides = match frame.strand {
+                    Strand::Forward => {
+                        nucleotides.start + start..nucleotides.start + start + codons
+                    }
+                    Strand::Reverse => nucleotides.end - start - codons..nucleotides.end - start,
+                };
+                TranslatedHit { nucleotides, frame }
+            })
+            .collect::<Vec<_>>();
+        hits.sort_unstable_by_key(|hit| (hit.nucleotides.start, hit.frame));
+        hits
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reduced_alphabet() {
+        let alphabet = ReducedAlphabet::murphy10();
+        assert_eq!(alphabet.apply(b"MIVLkrDEqnsT"), b"LLLLKKEEEESS");
+        assert_eq!(alphabet.apply(b"xC"), b"XC");
+        assert_eq!(ReducedAlphabet::identity().apply(b"ac"), b"AC");
+    }
+
+    #[test]
+    fn test_translate() {
+        assert_eq!(translate(b"ATGGCCTAAGG"), b"MA*");
+        assert_eq!(translate(b"augNNN"), b"MX");
+    }
+
+    #[test]
+    fn test_six_frames() {
+        // MAK on the forward strand at 1, MW on the reverse strand
+        let sequence = b"GATGGCAAAGCCCCAT";
+        let index = SixFrameIndex::<u32>::new(&[sequence]);
+        assert_eq!(
+            index.locate(b"MAK"),
+            vec![TranslatedHit {
+                nucleotides: 1..10,
+                frame: Frame {
+                    strand: Strand::Forward,
+                    offset: 1
+                }
+            }]
+        );
+
+        let hits = index.locate(b"MG");
+        assert_eq!(
+            hits,
+            vec![TranslatedHit {
+                nucleotides: 10..16,
+                frame: Frame {
+                    strand: Strand::Reverse,
+                    offset: 0
+                }
+            }]
+        );
+        assert_eq!(
+            translate(&reverse_complement(&sequence[hits[0].nucleotides.clone()])),
+            b"MG"
+        );
+    }
+
+    #[test]
+    fn test_locate() {
+        let sequences: &[&[u8]] = &[b"MKTAYIAKQR", b"GSHMLEDPVR"];
+        let index = ProteinIndex::<u32>::new(sequences, ReducedAlphabet::murphy10());
+        assert_eq!(index.locate(b"KTA"), vec![1]);
+        // I ~ V, K ~ R under murphy10
+        assert_eq!(index.locate(b"YVAR"), vec![4]);
+        assert_eq!(index.hit(4, b"YVAR"), b"YIAK");
+        assert_eq!(index.locate(b"ILEE"), vec![13]);
+        assert_eq!(index.locate(b"R"), vec![1, 7, 9, 19]);
+
+        let exact = ProteinIndex::<u32>::new(sequences, ReducedAlphabet::identity());
+        assert!(exact.locate(b"YVAR").is_empty());
+    }
+}
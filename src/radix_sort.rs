@@ -2,6 +2,10 @@ use crate::suffix_index::SuffixIndex;
 
 const BUCKETS: usize = 0x100;
 
+/// Sorts with at least this many indices are split into parallel sub-problems
+#[cfg(feature = "parallel")]
+const PARALLEL_MIN_LEN: usize = 1 << 16;
+
 fn exclusive_sum(values: &mut [usize]) {
     let mut sum = 0;
     for value in values {
@@ -45,22 +49,30 @@ unsafe fn move_elements_in_place<T: SuffixIndex>(
     }
 }
 
+/// Partitions `indices` by their first character after skipping the common prefix, returns the
+/// remaining indices and text or `None` if they are already sorted.
+/// `buckets` contains the end of every bucket afterwards.
+///
 /// # Safety
 /// Invariants
 /// - >= 2 indices
 /// - `indices` contains only valid unique indices
 /// - there is at most one `index == text.len()`
-unsafe fn suffix_sort<T: SuffixIndex>(mut indices: &mut [T], mut text: &[u8]) {
+/// - `buckets` is clear
+unsafe fn partition<'i, 't, T: SuffixIndex>(
+    mut indices: &'i mut [T],
+    mut text: &'t [u8],
+    buckets: &mut [usize; BUCKETS],
+) -> Option<(&'i mut [T], &'t [u8])> {
     // `indices` contains only unique indices is always maintained
     // since we only remove or swap indices
 
-    let mut buckets = [0usize; BUCKETS];
     // Invariants:
     // - buckets is clear
     // - remaining function invariants
     loop {
         if text.is_empty() {
-            return;
+            return None;
         }
 
         let mut empty = None;
@@ -84,14 +96,14 @@ unsafe fn suffix_sort<T: SuffixIndex>(mut indices: &mut [T], mut text: &[u8]) {
         }
 
         // Safety: indices.len() is >= 1
-        debug_assert!(indices.len() >= 1);
+        debug_assert!(!indices.is_empty());
 
         let first_bucket_index = *text.get_unchecked(indices.get_unchecked(0).as_index()) as usize;
         if *buckets.get_unchecked(first_bucket_index) == indices.len() {
             if indices.len() >= 2 {
                 text = &text[1..];
             } else {
-                return;
+                return None;
             }
         } else {
             break;
@@ -103,8 +115,22 @@ unsafe fn suffix_sort<T: SuffixIndex>(mut indices: &mut [T], mut text: &[u8]) {
     // Safety:
     // - all indices are valid for `text` (we only removed at most one)
     // - buckets contain element count
-    move_elements_in_place(indices, text, &mut buckets);
+    move_elements_in_place(indices, text, buckets);
     // bucket contains array offset of one plus the last item in the bucket
+    Some((indices, text))
+}
+
+/// # Safety
+/// Invariants
+/// - >= 2 indices
+/// - `indices` contains only valid unique indices
+/// - there is at most one `index == text.len()`
+unsafe fn suffix_sort<T: SuffixIndex>(indices: &mut [T], text: &[u8]) {
+    let mut buckets = [0usize; BUCKETS];
+    let (indices, text) = match partition(indices, text, &mut buckets) {
+        Some(partitioned) => partitioned,
+        None => return,
+    };
 
     let mut last_end = 0;
     for &bucket_end in buckets.iter() {
@@ -125,12 +151,53 @@ unsafe fn suffix_sort<T: SuffixIndex>(mut indices: &mut [T], mut text: &[u8]) {
     }
 }
 
+/// [`suffix_sort`] that sorts the buckets after the first partition as independent parallel tasks
+///
+/// # Safety
+/// Same invariants as [`suffix_sort`]
+#[cfg(feature = "parallel")]
+unsafe fn suffix_sort_parallel<T: SuffixIndex>(indices: &mut [T], text: &[u8]) {
+    use rayon::prelude::*;
+
+    let mut buckets = [0usize; BUCKETS];
+    let (mut indices, text) = match partition(indices, text, &mut buckets) {
+        Some(partitioned) => partitioned,
+        None => return,
+    };
+
+    let mut parts = Vec::new();
+    let mut last_end = 0;
+    for &bucket_end in buckets.iter() {
+        let (part, rest) = std::mem::take(&mut indices).split_at_mut(bucket_end - last_end);
+        if part.len() >= 2 {
+            parts.push(part);
+        }
+        indices = rest;
+        last_end = bucket_end;
+    }
+    // Safety: text was non empty before
+    let text = text.get_unchecked(1..);
+
+    // Safety: every bucket satisfies the invariants of `suffix_sort`, see there
+    parts.into_par_iter().for_each(|indices| unsafe {
+        if indices.len() >= PARALLEL_MIN_LEN {
+            suffix_sort_parallel(indices, text)
+        } else {
+            suffix_sort(indices, text)
+        }
+    });
+}
+
 /// # Safety
 /// the caller must ensure `indices` contains all valid indices exactly once
 pub unsafe fn sort<T: SuffixIndex>(indices: &mut [T], text: &[u8]) {
     if indices.len() <= 1 {
         return;
     }
+    #[cfg(feature = "parallel")]
+    if indices.len() >= PARALLEL_MIN_LEN {
+        return suffix_sort_parallel(indices, text);
+    }
     suffix_sort(indices, text);
 }
 
@@ -174,11 +241,11 @@ mod test {
     }
 
     fn is_sorted(indices: &[usize], text: &[u8]) -> Option<usize> {
-        let compare = |&a, &b| (&text[a..]).cmp(&text[b..]);
+        let compare = |&a, &b| text[a..].cmp(&text[b..]);
         indices
             .windows(2)
             .enumerate()
-            .find_map(|(index, w)| (compare(&&w[0], &&w[1]) == Ordering::Greater).then(|| index))
+            .find_map(|(index, w)| (compare(&w[0], &w[1]) == Ordering::Greater).then_some(index))
     }
 
     #[test]
@@ -201,6 +268,27 @@ mod test {
         }
     }
 
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_sort_parallel() {
+        let mut state = 0x2545f491u32;
+        let mut text = (0..PARALLEL_MIN_LEN * 2)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                b"acgt"[state as usize % 4]
+            })
+            .collect::<Vec<_>>();
+        text.extend_from_within(..1024);
+        let mut indices = (0..=text.len()).collect::<Vec<_>>();
+        unsafe {
+            sort(&mut indices, &text);
+        }
+        assert_eq!(is_sorted(&indices, &text), None);
+        assert_eq!(indices[0], text.len());
+    }
+
     #[test]
     fn test_sort_file() {
         let mut text = String::new();
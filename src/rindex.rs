@@ -0,0 +1,291 @@
+use std::ops::Range;
+
+use crate::bwt::bwt_from_suffix_array;
+use crate::fm::Rank;
+use crate::sais::suffix_array;
+use crate::suffix_index::SuffixIndex;
+
+const ALPHABET: usize = 0x100;
+
+/// Bytes stored as runs of equal bytes, takes space proportional to the number of runs
+#[derive(Debug, Clone)]
+pub struct RunLengthBwt {
+    heads: Vec<u8>,
+    /// The start of every run and the length
+    starts: Vec<usize>,
+    /// The runs of every character
+    char_runs: Vec<Vec<usize>>,
+    /// The occurrences of every character before each of its runs
+    char_ranks: Vec<Vec<usize>>,
+}
+
+impl RunLengthBwt {
+    /// Stores `bytes` with a run starting at every index of `boundaries`
+    fn with_boundaries(bytes: &[u8], boundaries: &[usize]) -> Self {
+        let mut rle = Self {
+            heads: Vec::new(),
+            starts: Vec::new(),
+            char_runs: vec![Vec::new(); ALPHABET],
+            char_ranks: vec![Vec::new(); ALPHABET],
+        };
+        let mut counts = [0; ALPHABET];
+        for (index, &c) in bytes.iter().enumerate() {
+            let new_run = rle.heads.last() != Some(&c)
+                || boundaries.contains(&index)
+                || index
+                    .checked_sub(1)
+                    .is_some_and(|last| boundaries.contains(&last));
+            if new_run {
+                rle.char_runs[c as usize].push(rle.heads.len());
+                rle.char_ranks[c as usize].push(counts[c as usize]);
+                rle.heads.push(c);
+                rle.starts.push(index);
+            }
+            counts[c as usize] += 1;
+        }
+        rle.starts.push(bytes.len());
+        rle
+    }
+
+    /// The number of runs
+    pub fn runs(&self) -> usize {
+        self.heads.len()
+    }
+
+    fn run_range(&self, run: usize) -> Range<usize> {
+        self.starts[run]..self.starts[run + 1]
+    }
+
+    /// The run containing `index`
+    fn run(&self, index: usize) -> usize {
+        self.starts.partition_point(|&start| start <= index) - 1
+    }
+
+    /// The index into the runs of `c` of the last run of `c` starting before `index`
+    fn last_char_run(&self, c: u8, index: usize) -> Option<usize> {
+        let runs = &self.char_runs[c as usize];
+        runs.partition_point(|&run| self.starts[run] < index)
+            .checked_sub(1)
+    }
+}
+
+impl Rank for RunLengthBwt {
+    fn new(bytes: Vec<u8>) -> Self {
+        Self::with_boundaries(&bytes, &[])
+    }
+
+    fn len(&self) -> usize {
+        *self.starts.last().unwrap()
+    }
+
+    fn get(&self, index: usize) -> u8 {
+        assert!(index < self.len());
+        self.heads[self.run(index)]
+    }
+
+    fn rank(&self, c: u8, index: usize) -> usize {
+        match self.last_char_run(c, index) {
+            None => 0,
+            Some(k) => {
+                let run = self.run_range(self.char_runs[c as usize][k]);
+                self.char_ranks[c as usize][k] + index.min(run.end) - run.start
+            }
+        }
+    }
+}
+
+/// Full-text index of highly repetitive texts in space proportional to the number of runs of the
+/// Burrows-Wheeler transform.
+///
+/// Occurrences are located from the suffix at the end of the match interval with the φ function,
+/// which maps a suffix to its predecessor in the suffix array using predecessor queries over the
+/// suffixes at run boundaries.
+#[derive(Debug, Clone)]
+pub struct RIndex<I> {
+    /// The transform including a placeholder for the end marker in row `primary`, which is a run
+    /// on its own
+    bwt: RunLengthBwt,
+    primary: usize,
+    /// The first row starting with every character, the row of the end marker comes first
+    starts: [usize; ALPHABET],
+    /// The suffix in the last row of every run
+    run_ends: Vec<I>,
+    /// The suffix at the start of every run but the first with the suffix in the row before,
+    /// sorted
+    phi: Vec<(I, I)>,
+}
+
+impl<I: SuffixIndex> RIndex<I> {
+    pub fn new(text: &[u8]) -> Self {
+        Self::from_suffix_array(text, &suffix_array(text))
+    }
+
+    /// Builds the index of `text` from its suffix array
+    pub fn from_suffix_array(text: &[u8], suffix_array: &[I]) -> Self {
+        assert_eq!(text.len(), suffix_array.len());
+        let (transformed, primary) =
+            bwt_from_suffix_array(text, suffix_array.iter().map(|suffix| suffix.as_index()));
+        let mut rows = transformed;
+        rows.insert(primary, 0);
+        let bwt = RunLengthBwt::with_boundaries(&rows, &[primary]);
+
+        let mut starts = [0; ALPHABET];
+        for &c in text {
+            starts[c as usize] += 1;
+        }
+        let mut sum = 1;
+        for start in starts.iter_mut() {
+            sum += *start;
+            *start = sum - *start;
+        }
+
+        let suffix = |row: usize| match row {
+            0 => I::from_index(text.len()),
+            row => suffix_array[row - 1],
+        };
+        let run_ends = (0..bwt.runs())
+            .map(|run| suffix(bwt.run_range(run).end - 1))
+            .collect();
+        let mut phi = (1..bwt.runs())
+            .map(|run| {
+                let start = bwt.run_range(run).start;
+                (suffix(start), suffix(start - 1))
+            })
+            .collect::<Vec<_>>();
+        phi.sort_unstable();
+        Self {
+            bwt,
+            primary,
+            starts,
+            run_ends,
+            phi,
+        }
+    }
+
+    /// The length of the indexed text
+    pub fn len(&self) -> usize {
+        self.bwt.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of runs of the transform
+    pub fn runs(&self) -> usize {
+        self.bwt.runs()
+    }
+
+    /// The number of occurrences of `c` in front of the first `row` rows
+    fn rank(&self, c: u8, row: usize) -> usize {
+        let end_marker = c == 0 && self.primary < row;
+        self.bwt.rank(c, row) - end_marker as usize
+    }
+
+    /// The rows of the suffixes prefixed by `pattern` and the suffix in the last row
+    fn backward_search(&self, pattern: &[u8]) -> (Range<usize>, usize) {
+        let (mut start, mut end) = (0, self.bwt.len());
+        let mut last = self.run_ends[self.runs() - 1].as_index();
+        for &c in pattern.iter().rev() {
+            let c_start = self.starts[c as usize];
+            let (next_start, next_end) =
+                (c_start + self.rank(c, start), c_start + self.rank(c, end));
+            if next_start == next_end {
+                return (next_start..next_end, 0);
+            }
+            let last_run = self.bwt.run(end - 1);
+            if self.bwt.heads[last_run] != c || last_run == self.bwt.run(self.primary) {
+                // The last row preceded by c ends a run of c
+                let runs = &self.bwt.char_runs[c as usize];
+                let mut k = self.bwt.last_char_run(c, end).unwrap();
+                if self.bwt.run_range(runs[k]).contains(&self.primary) {
+                    k -= 1;
+                }
+                last = self.run_ends[runs[k]].as_index();
+            }
+            last -= 1;
+            (start, end) = (next_start, next_end);
+        }
+        ((start.max(1))..end.max(1), last)
+    }
+
+    /// The predecessor of the suffix at `position` in the suffix array
+    fn phi(&self, position: usize) -> usize {
+        let index = self
+            .phi
+            .partition_point(|&(suffix, _)| suffix.as_index() <= position);
+        let (suffix, previous) = self.phi[index - 1];
+        previous.as_index() + (position - suffix.as_index())
+    }
+
+    /// The number of occurrences of `pattern`
+    pub fn count(&self, pattern: &[u8]) -> usize {
+        self.backward_search(pattern).0.len()
+    }
+
+    /// Returns the positions of all occurrences of `pattern` in suffix array order
+    pub fn locate(&self, pattern: &[u8]) -> Vec<usize> {
+        let (rows, last) = self.backward_search(pattern);
+        let mut positions = Vec::with_capacity(rows.len());
+        if !rows.is_empty() {
+            positions.push(last);
+            for _ in 1..rows.len() {
+                positions.push(self.phi(*positions.last().unwrap()));
+            }
+        }
+        positions.reverse();
+        positions
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::suffix_array::SuffixArray;
+
+    #[test]
+    fn test_run_length_bwt() {
+        let bytes = b"aaabbbbaaccca".to_vec();
+        let rle = RunLengthBwt::new(bytes.clone());
+        assert_eq!(rle.runs(), 5);
+        for (i, &c) in bytes.iter().enumerate() {
+            assert_eq!(rle.get(i), c);
+        }
+        for c in [b'a', b'b', b'c', b'd'] {
+            for i in 0..=bytes.len() {
+                let count = bytes[..i].iter().filter(|&&x| x == c).count();
+                assert_eq!(rle.rank(c, i), count);
+            }
+        }
+    }
+
+    #[test]
+    fn test_r_index() {
+        let mut text = Vec::new();
+        for version in 0..20u8 {
+            text.extend_from_slice(b"the quick brown fox jumps over the lazy dog ");
+            text.push(b'0' + version % 10);
+        }
+        text.extend_from_slice(b"\0\0");
+        let index = RIndex::<u32>::new(&text);
+        assert!(index.runs() < text.len() / 4);
+        let suffix_array = SuffixArray::<u32>::new(&text);
+        for pattern in [
+            &b"the"[..],
+            b"o",
+            b"dog 7",
+            b"x",
+            b"s over",
+            b"\0",
+            b"",
+            b"5the",
+        ] {
+            assert_eq!(index.count(pattern), suffix_array.count(pattern));
+            let expected = suffix_array.locate(pattern).collect::<Vec<_>>();
+            assert_eq!(index.locate(pattern), expected);
+        }
+        let empty = RIndex::<u32>::new(b"");
+        assert_eq!(empty.count(b"a"), 0);
+        assert!(empty.locate(b"").is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+use std::ops::Range;
+
+/// A range minimum query structure, the backend of lce queries
+pub trait Rmq<T> {
+    fn new(values: Vec<T>) -> Self;
+
+    fn values(&self) -> &[T];
+
+    /// The index of the leftmost minimum in `range`, which must not be empty
+    fn min_index(&self, range: Range<usize>) -> usize;
+
+    /// The minimum in `range`, which must not be empty
+    fn min(&self, range: Range<usize>) -> T
+    where
+        T: Copy,
+    {
+        self.values()[self.min_index(range)]
+    }
+}
+
+/// Range minimum queries in constant time after `O(n log n)` preprocessing
+#[derive(Debug, Clone)]
+pub struct SparseTable<T> {
+    values: Vec<T>,
+    /// `table[k][i]` is the index of the leftmost minimum of `values[i..i + 2^(k + 1)]`
+    table: Vec<Vec<usize>>,
+}
+
+impl<T: Ord + Copy> SparseTable<T> {
+    pub fn new(values: Vec<T>) -> Self {
+        let mut table: Vec<Vec<usize>> = Vec::new();
+        let mut width = 1;
+        while 2 * width <= values.len() {
+            let level = (0..=values.len() - 2 * width)
+                .map(|i| {
+                    let (left, right) = match table.last() {
+                        None => (i, i + 1),
+                        Some(previous) => (previous[i], previous[i + width]),
+                    };
+                    if values[right] < values[left] {
+                        right
+                    } else {
+                        left
+                    }
+                })
+                .collect();
+            table.push(level);
+            width *= 2;
+        }
+        Self { values, table }
+    }
+
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// The index of the leftmost minimum in `range`, which must not be empty
+    pub fn min_index(&self, range: Range<usize>) -> usize {
+        assert!(range.start < range.end && range.end <= self.values.len());
+        let len = range.end - range.start;
+        if len == 1 {
+            return range.start;
+        }
+        let level = (usize::BITS - 1 - len.leading_zeros()) as usize;
+        let left = self.table[level - 1][range.start];
+        let right = self.table[level - 1][range.end - (1 << level)];
+        if self.values[right] < self.values[left] {
+            right
+        } else {
+            left
+        }
+    }
+
+    /// The minimum in `range`, which must not be empty
+    pub fn min(&self, range: Range<usize>) -> T {
+        self.values[self.min_index(range)]
+    }
+}
+
+impl<T: Ord + Copy> Rmq<T> for SparseTable<T> {
+    fn new(values: Vec<T>) -> Self {
+        SparseTable::new(values)
+    }
+
+    fn values(&self) -> &[T] {
+        SparseTable::values(self)
+    }
+
+    fn min_index(&self, range: Range<usize>) -> usize {
+        SparseTable::min_index(self, range)
+    }
+}
+
+const BLOCK: usize = u32::BITS as usize;
+
+/// Range minimum queries in constant time with `32` bits per value and a sparse table over the
+/// minima of blocks of `32` values.
+///
+/// Queries inside a block use the stack of the Cartesian tree of the block prefix ending at the
+/// right bound, stored as a bit mask (Fischer and Heun).
+#[derive(Debug, Clone)]
+pub struct BlockRmq<T> {
+    values: Vec<T>,
+    /// The positions on the stack of smaller values in the block up to every index
+    masks: Vec<u32>,
+    blocks: SparseTable<T>,
+}
+
+impl<T: Ord + Copy> BlockRmq<T> {
+    pub fn new(values: Vec<T>) -> Self {
+        let mut masks = Vec::with_capacity(values.len());
+        for block in values.chunks(BLOCK) {
+            let mut mask = 0u32;
+            for (i, value) in block.iter().enumerate() {
+                // Equal values stay on the stack for the leftmost minimum
+                while mask != 0 {
+                    let top = (u32::BITS - 1 - mask.leading_zeros()) as usize;
+                    if block[top] <= *value {
+                        break;
+                    }
+                    mask ^= 1 << top;
+                }
+                mask |= 1 << i;
+                masks.push(mask);
+            }
+        }
+        let minima = values
+            .chunks(BLOCK)
+            .enumerate()
+            .map(|(block, values)| {
+                let last = block * BLOCK + values.len() - 1;
+                values[masks[last].trailing_zeros() as usize]
+            })
+            .collect();
+        Self {
+            values,
+            masks,
+            blocks: SparseTable::new(minima),
+        }
+    }
+
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// The index of the leftmost minimum of `start..=last` inside one block
+    fn block_min_index(&self, start: usize, last: usize) -> usize {
+        let offset = start % BLOCK;
+        let mask = self.masks[last] & (u32::MAX << offset);
+        start - offset + mask.trailing_zeros() as usize
+    }
+
+    /// The index of the leftmost minimum in `range`, which must not be empty
+    pub fn min_index(&self, range: Range<usize>) -> usize {
+        assert!(range.start < range.end && range.end <= self.values.len());
+        let last = range.end - 1;
+        let (first_block, last_block) = (range.start / BLOCK, last / BLOCK);
+        if first_block == last_block {
+            return self.block_min_index(range.start, last);
+        }
+        let smaller = |min: usize, index: usize| {
+            if self.values[index] < self.values[min] {
+                index
+            } else {
+                min
+            }
+        };
+        let mut min = self.block_min_index(range.start, first_block * BLOCK + BLOCK - 1);
+        if first_block + 1 < last_block {
+            let block = self.blocks.min_index(first_block + 1..last_block);
+            min = smaller(
+                min,
+                self.block_min_index(block * BLOCK, block * BLOCK + BLOCK - 1),
+            );
+        }
+        smaller(min, self.block_min_index(last_block * BLOCK, last))
+    }
+
+    /// The minimum in `range`, which must not be empty
+    pub fn min(&self, range: Range<usize>) -> T {
+        self.values[self.min_index(range)]
+    }
+}
+
+impl<T: Ord + Copy> Rmq<T> for BlockRmq<T> {
+    fn new(values: Vec<T>) -> Self {
+        BlockRmq::new(values)
+    }
+
+    fn values(&self) -> &[T] {
+        BlockRmq::values(self)
+    }
+
+    fn min_index(&self, range: Range<usize>) -> usize {
+        BlockRmq::min_index(self, range)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sparse_table() {
+        let values = vec![5, 2, 7, 2, 9, 1, 1, 8, 3, 4, 0];
+        let table = SparseTable::new(values.clone());
+        for start in 0..values.len() {
+            for end in start + 1..=values.len() {
+                let min = *values[start..end].iter().min().unwrap();
+                let index = start + values[start..end].iter().position(|&v| v == min).unwrap();
+                assert_eq!(table.min_index(start..end), index);
+                assert_eq!(table.min(start..end), min);
+            }
+        }
+    }
+
+    #[test]
+    fn test_block_rmq() {
+        let values = (0..300u32)
+            .map(|i| i.wrapping_mul(2654435761) % 37)
+            .collect::<Vec<_>>();
+        let rmq = BlockRmq::new(values.clone());
+        let table = SparseTable::new(values.clone());
+        for start in 0..values.len() {
+            for end in start + 1..=values.len() {
+                assert_eq!(rmq.min_index(start..end), table.min_index(start..end));
+            }
+        }
+        assert_eq!(BlockRmq::new(vec![3]).min(0..1), 3);
+    }
+}
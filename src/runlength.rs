@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+
+use crate::sorter::SuffixSorter;
+use crate::suffix_index::{AsIndex, SuffixIndex};
+
+/// Sorts the suffixes starting at the runs of equal characters with another backend and derives
+/// all other suffixes from them, for highly repetitive texts with long runs.
+///
+/// A suffix starting in a run of `c` is ordered by `c`, whether the next run is greater, the
+/// remaining length of the run and then by the suffix starting at the next run.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct RunLength<S>(pub S);
+
+#[derive(Debug, Copy, Clone)]
+struct Run {
+    start: usize,
+    len: usize,
+    /// Whether the next run has a greater character, `false` for the last run
+    greater: bool,
+}
+
+fn runs<C: Ord>(text: &[C]) -> Vec<Run> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for i in 1..=text.len() {
+        if i == text.len() || text[i] != text[start] {
+            runs.push(Run {
+                start,
+                len: i - start,
+                greater: i < text.len() && text[i] > text[start],
+            });
+            start = i;
+        }
+    }
+    runs
+}
+
+impl<S: SuffixSorter> SuffixSorter for RunLength<S> {
+    fn sort<C: AsIndex + Ord, I: SuffixIndex>(&self, text: &[C], suffix_array: &mut [I]) {
+        assert_eq!(text.len(), suffix_array.len());
+        assert!(text.len() < I::MAX, "text is too large for the index type");
+        if text.is_empty() {
+            return;
+        }
+        let runs = runs(text);
+
+        // A longer run is larger if the next run is smaller and smaller otherwise
+        let keys = runs
+            .iter()
+            .map(|run| {
+                let len = if run.greater {
+                    usize::MAX - run.len
+                } else {
+                    run.len
+                };
+                (text[run.start].as_index(), run.greater, len)
+            })
+            .collect::<Vec<_>>();
+        let mut symbols = keys.clone();
+        symbols.sort_unstable();
+        symbols.dedup();
+        let reduced = keys
+            .iter()
+            .map(|key| I::from_index(symbols.binary_search(key).unwrap()))
+            .collect::<Vec<_>>();
+        let sorted_runs = self.0.suffix_array::<_, I>(&reduced);
+
+        // The runs of every character in the order of the suffixes following them, the last run
+        // is followed by the empty suffix
+        let mut groups = BTreeMap::<_, Vec<Run>>::new();
+        let following = sorted_runs
+            .iter()
+            .filter_map(|run| run.as_index().checked_sub(1));
+        for run in std::iter::once(runs.len() - 1).chain(following) {
+            let run = runs[run];
+            groups
+                .entry((text[run.start].as_index(), run.greater))
+                .or_default()
+                .push(run);
+        }
+
+        // Suffixes with shorter remaining runs come first if the next run is smaller, the
+        // others are emitted reversed and turned around
+        let mut position = 0;
+        for ((_, greater), mut group) in groups {
+            let start = position;
+            if greater {
+                group.reverse();
+            }
+            let mut remaining = 1;
+            while !group.is_empty() {
+                for run in &group {
+                    suffix_array[position] = I::from_index(run.start + run.len - remaining);
+                    position += 1;
+                }
+                group.retain(|run| run.len > remaining);
+                remaining += 1;
+            }
+            if greater {
+                suffix_array[start..position].reverse();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sais::suffix_array;
+    use crate::sorter::Sais;
+
+    #[test]
+    fn test_run_length() {
+        let mut state = 11u32;
+        let mut random = Vec::new();
+        for _ in 0..300 {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            random.extend(std::iter::repeat_n(
+                b"abc"[state as usize % 3],
+                state as usize % 7,
+            ));
+        }
+        let (mut fibonacci, mut previous) = (b"ab".to_vec(), b"a".to_vec());
+        while fibonacci.len() < 2000 {
+            let next = [&fibonacci[..], &previous].concat();
+            previous = std::mem::replace(&mut fibonacci, next);
+        }
+        for text in [
+            &b"mississippi"[..],
+            b"a",
+            b"aaaaaa",
+            b"aaabbbaaab",
+            b"bbbaaabbba",
+            b"",
+            &random,
+            &fibonacci,
+        ]
+        .iter()
+        {
+            let sorted: Vec<u32> = RunLength(Sais).suffix_array(text);
+            assert_eq!(sorted, suffix_array::<u32>(text));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "text is too large for the index type")]
+    fn test_run_length_too_large() {
+        RunLength(Sais).suffix_array::<_, u8>(&[b'a'; 300]);
+    }
+}
@@ -1,41 +1,292 @@
 use std::cmp::Ordering;
 use std::mem::replace;
+use std::ops::Range;
+use std::time::Instant;
 
+use crate::alphabet::ranks;
+use crate::error::SaisError;
+use crate::lcp::lcp_array;
+use crate::progress::{CancellationToken, Cancelled, Level, Phase, Progress, Stats};
 use crate::suffix_index::{AsIndex, SuffixIndex};
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
 pub enum Type {
+    #[default]
     L,
     S,
 }
 
-impl Default for Type {
-    fn default() -> Self {
-        Self::L
+const WORD: usize = u64::BITS as usize;
+
+/// The types of the suffixes of a text with one bit per suffix, a set bit is [`Type::S`].
+///
+/// Views of a prefix borrow the words, the construction reuses the prefix for the reduced text.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct Types<W = Vec<u64>> {
+    words: W,
+    len: usize,
+}
+
+impl Types {
+    /// The types of the suffixes of `text`
+    pub fn classify<C: AsIndex + Ord>(text: &[C]) -> Self {
+        let mut types = Self::new(text.len());
+        if !text.is_empty() {
+            classify(text, types.view_mut());
+        }
+        types
+    }
+
+    /// `len` suffixes of type [`Type::L`]
+    pub fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(WORD)],
+            len,
+        }
+    }
+
+    pub fn with_capacity(len: usize) -> Self {
+        Self {
+            words: Vec::with_capacity(len.div_ceil(WORD)),
+            len: 0,
+        }
+    }
+
+    /// Resizes to `len` suffixes, new suffixes have type [`Type::L`]
+    pub fn resize(&mut self, len: usize) {
+        self.words.truncate(len.div_ceil(WORD));
+        if len < self.len && !len.is_multiple_of(WORD) {
+            *self.words.last_mut().unwrap() &= !(u64::MAX << (len % WORD));
+        }
+        self.words.resize(len.div_ceil(WORD), 0);
+        self.len = len;
+    }
+}
+
+impl<W: AsRef<[u64]>> Types<W> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline(always)]
+    pub fn get(&self, index: usize) -> Type {
+        debug_assert!(index < self.len);
+        if self.words.as_ref()[index / WORD] >> (index % WORD) & 1 == 1 {
+            Type::S
+        } else {
+            Type::L
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Type> + '_ {
+        (0..self.len).map(move |index| self.get(index))
+    }
+
+    fn view(&self) -> Types<&[u64]> {
+        Types {
+            words: self.words.as_ref(),
+            len: self.len,
+        }
+    }
+}
+
+impl<W: AsMut<[u64]>> Types<W> {
+    #[inline(always)]
+    pub fn set(&mut self, index: usize, value: Type) {
+        debug_assert!(index < self.len);
+        let word = &mut self.words.as_mut()[index / WORD];
+        match value {
+            Type::L => *word &= !(1 << (index % WORD)),
+            Type::S => *word |= 1 << (index % WORD),
+        }
+    }
+
+    fn view_mut(&mut self) -> Types<&mut [u64]> {
+        self.prefix_mut(self.len)
+    }
+
+    /// The first `len` suffixes, the bits after them in the last word are not written
+    fn prefix_mut(&mut self, len: usize) -> Types<&mut [u64]> {
+        assert!(len <= self.len);
+        Types {
+            words: &mut self.words.as_mut()[..len.div_ceil(WORD)],
+            len,
+        }
+    }
+
+    /// The suffixes starting at `word * 64`
+    #[cfg(feature = "simd")]
+    fn words_from_mut(&mut self, word: usize) -> Types<&mut [u64]> {
+        Types {
+            words: &mut self.words.as_mut()[word..],
+            len: self.len - word * WORD,
+        }
     }
 }
 
-fn classify<C: Ord>(text: &[C], types: &mut [Type]) {
+fn classify<C: AsIndex + Ord>(text: &[C], mut types: Types<&mut [u64]>) {
     debug_assert_eq!(types.len(), text.len());
     debug_assert_ne!(text.len(), 0);
 
-    *types.last_mut().unwrap() = Type::L;
+    types.set(text.len() - 1, Type::L);
+    #[cfg(feature = "parallel")]
+    if parallel(text.len()) {
+        return classify_parallel(text, types);
+    }
     classify_sub_slice(text, types);
 }
 
+/// [`classify`] of independent blocks, the types of a run of equal characters at the end of a
+/// block are fixed afterwards from the type following it.
+///
+/// Assumes
+/// - `types.get(len - 1)` is already set
+#[cfg(feature = "parallel")]
+fn classify_parallel<C: AsIndex + Ord>(text: &[C], mut types: Types<&mut [u64]>) {
+    use rayon::prelude::*;
+
+    let len = text.len();
+    // Whether the last type of every block still depends on the next block
+    let unresolved = types
+        .words
+        .par_chunks_mut(PARALLEL_BLOCK / WORD)
+        .enumerate()
+        .map(|(block, words)| {
+            let start = block * PARALLEL_BLOCK;
+            let end = (start + PARALLEL_BLOCK).min(len);
+            let mut types = Types {
+                words,
+                len: end - start,
+            };
+            let unresolved = end < len && text[end - 1] == text[end];
+            if end < len {
+                let last = match text[end - 1].cmp(&text[end]) {
+                    Ordering::Less => Type::S,
+                    _ => Type::L,
+                };
+                types.set(end - start - 1, last);
+            }
+            classify_sub_slice(&text[start..end], types);
+            unresolved
+        })
+        .collect::<Vec<_>>();
+
+    for (block, unresolved) in unresolved.into_iter().enumerate().rev() {
+        let end = (block + 1) * PARALLEL_BLOCK;
+        if !unresolved || types.get(end - 1) == types.get(end) {
+            continue;
+        }
+        let mut i = end - 1;
+        loop {
+            let next = types.get(end);
+            types.set(i, next);
+            if i == 0 || text[i - 1] != text[i] {
+                break;
+            }
+            i -= 1;
+        }
+    }
+}
+
 /// Assumes
-/// - `types[len - 1]` is already set
-fn classify_sub_slice<C: Ord>(text: &[C], types: &mut [Type]) {
+/// - `types.get(len - 1)` is already set
+fn classify_sub_slice<C: AsIndex + Ord>(text: &[C], types: Types<&mut [u64]>) {
+    #[cfg(feature = "simd")]
+    if let Some(text) = C::as_bytes(text) {
+        return classify_bytes(text, types);
+    }
+    classify_sub_slice_scalar(text, types);
+}
+
+fn classify_sub_slice_scalar<C: Ord>(text: &[C], mut types: Types<&mut [u64]>) {
     use Type::*;
 
     for i in (0..types.len() - 1).rev() {
         let r = match text[i].cmp(&text[i + 1]) {
             Ordering::Less => S,
             Ordering::Greater => L,
-            Ordering::Equal => types[i + 1],
+            Ordering::Equal => types.get(i + 1),
         };
 
-        types[i] = r;
+        types.set(i, r);
+    }
+}
+
+/// [`classify_sub_slice`] of bytes in words of `64` types with vector compares, equal characters
+/// take the type of the next different pair by filling the bit masks downwards.
+///
+/// Assumes
+/// - `types.get(len - 1)` is already set
+#[cfg(feature = "simd")]
+fn classify_bytes(text: &[u8], mut types: Types<&mut [u64]>) {
+    let words = (types.len() - 1) / WORD;
+    // The types in the last word are classified one by one, it may be shared with a longer text
+    classify_sub_slice_scalar(
+        &text[words * WORD..types.len()],
+        types.words_from_mut(words),
+    );
+    let mut next_s = types.get(words * WORD) == Type::S;
+    for word in (0..words).rev() {
+        let start = word * WORD;
+        let (less, greater) = compare_bytes(&text[start..start + WORD + 1]);
+        let different = less | greater;
+        // The equal characters above the last different pair continue the next word
+        let top = u64::MAX
+            .checked_shl(u64::BITS - different.leading_zeros())
+            .unwrap_or(0);
+        let mut s = less | if next_s { top } else { 0 };
+        let mut equal = !different & !top;
+        let mut shift = 1;
+        while shift < u64::BITS {
+            s |= equal & (s >> shift);
+            equal &= equal >> shift;
+            shift *= 2;
+        }
+        types.words[word] = s;
+        next_s = s & 1 == 1;
+    }
+}
+
+/// Bit masks of `window[i] < window[i + 1]` and `window[i] > window[i + 1]` for a window of `65`
+/// bytes
+#[cfg(feature = "simd")]
+fn compare_bytes(window: &[u8]) -> (u64, u64) {
+    assert_eq!(window.len(), 65);
+    #[cfg(target_arch = "x86_64")]
+    {
+        use std::arch::x86_64::*;
+
+        let (mut less, mut greater) = (0, 0);
+        for block in 0..4 {
+            // Safety: sse2 is part of x86_64 and both loads are in bounds of `window`
+            let (lt, gt) = unsafe {
+                let flip = _mm_set1_epi8(i8::MIN);
+                let ptr = window.as_ptr().add(block * 16);
+                // Unsigned compares by flipping the sign bits
+                let left = _mm_xor_si128(_mm_loadu_si128(ptr as *const __m128i), flip);
+                let right = _mm_xor_si128(_mm_loadu_si128(ptr.add(1) as *const __m128i), flip);
+                (
+                    _mm_movemask_epi8(_mm_cmplt_epi8(left, right)) as u16,
+                    _mm_movemask_epi8(_mm_cmpgt_epi8(left, right)) as u16,
+                )
+            };
+            less |= (lt as u64) << (block * 16);
+            greater |= (gt as u64) << (block * 16);
+        }
+        (less, greater)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        let (mut less, mut greater) = (0, 0);
+        for (i, pair) in window.windows(2).enumerate() {
+            less |= ((pair[0] < pair[1]) as u64) << i;
+            greater |= ((pair[0] > pair[1]) as u64) << i;
+        }
+        (less, greater)
     }
 }
 
@@ -44,11 +295,42 @@ fn buckets_count<C: AsIndex, I: SuffixIndex>(text: &[C], buckets: &mut [I]) {
         assert!(buckets.iter().all(|v| v.as_index() == 0));
     }
 
+    #[cfg(feature = "parallel")]
+    if parallel(text.len()) && buckets.len() * rayon::current_num_threads() <= text.len() {
+        return buckets_count_parallel(text, buckets);
+    }
     for bucket in text.iter().map(AsIndex::as_index) {
         buckets[bucket] += I::from_index(1);
     }
 }
 
+/// [`buckets_count`] with one histogram per block of the text, merged at the end
+#[cfg(feature = "parallel")]
+fn buckets_count_parallel<C: AsIndex, I: SuffixIndex>(text: &[C], buckets: &mut [I]) {
+    use rayon::prelude::*;
+
+    let counts = text
+        .par_chunks(PARALLEL_BLOCK)
+        .fold(
+            || vec![0usize; buckets.len()],
+            |mut counts, block| {
+                for bucket in block.iter().map(AsIndex::as_index) {
+                    counts[bucket] += 1;
+                }
+                counts
+            },
+        )
+        .reduce_with(|mut left, right| {
+            for (left, right) in left.iter_mut().zip(right) {
+                *left += right;
+            }
+            left
+        });
+    for (bucket, count) in buckets.iter_mut().zip(counts.into_iter().flatten()) {
+        *bucket = I::from_index(count);
+    }
+}
+
 fn bucket_ends<I: SuffixIndex>(buckets: &mut [I]) {
     let mut sum = I::from_index(0);
     for value in buckets.iter_mut() {
@@ -115,18 +397,21 @@ impl<'a, C, I: SuffixIndex> Buckets<'a, C, I> {
     }
 }
 
-fn is_lms<I: SuffixIndex>(suffix: I, types: &[Type]) -> bool {
+fn is_lms<I: SuffixIndex>(suffix: I, types: Types<&[u64]>) -> bool {
     use Type::*;
     debug_assert_ne!(suffix, I::from_index(0));
     matches!(
-        (types[suffix.as_index() - 1], types[suffix.as_index()]),
+        (
+            types.get(suffix.as_index() - 1),
+            types.get(suffix.as_index())
+        ),
         (L, S)
     )
 }
 
 /// Assumes:
 /// - text has a lms character at index
-fn lms_substring<'a, C>(index: usize, text: &'a [C], types: &[Type]) -> &'a [C] {
+fn lms_substring<'a, C>(index: usize, text: &'a [C], types: Types<&[u64]>) -> &'a [C] {
     debug_assert!(index < text.len());
     debug_assert_ne!(index, 0);
 
@@ -138,23 +423,20 @@ fn lms_substring<'a, C>(index: usize, text: &'a [C], types: &[Type]) -> &'a [C]
     &text[index..]
 }
 
+/// Compares the lms substrings `left` and `right` starting at `left_start` and `right_start`
+/// including their types
 fn lms_substrings_eq<C: Eq>(
     left: &[C],
-    left_types: &[Type],
+    left_start: usize,
     right: &[C],
-    right_types: &[Type],
+    right_start: usize,
+    types: Types<&[u64]>,
 ) -> bool {
-    debug_assert_eq!(left_types.len(), left.len());
-    debug_assert_eq!(right_types.len(), right.len());
     if left.len() != right.len() {
         false
     } else {
-        for ((l, lt), (r, rt)) in left
-            .iter()
-            .zip(left_types)
-            .zip(right.iter().zip(right_types.iter()))
-        {
-            if l != r || lt != rt {
+        for (i, (l, r)) in left.iter().zip(right).enumerate() {
+            if l != r || types.get(left_start + i) != types.get(right_start + i) {
                 return false;
             }
         }
@@ -164,7 +446,7 @@ fn lms_substrings_eq<C: Eq>(
 
 /// Moves all values matching the predicate to the front of the slice
 /// The remaining values are unspecified
-fn retain<'a, T: Copy, P: FnMut(&T) -> bool>(
+fn retain<T: Copy, P: FnMut(&T) -> bool>(
     values: &mut [T],
     mut predicate: P,
 ) -> (&mut [T], &mut [T]) {
@@ -180,27 +462,77 @@ fn retain<'a, T: Copy, P: FnMut(&T) -> bool>(
     values.split_at_mut(write_offset)
 }
 
-fn induce_ls<C: AsIndex, I: SuffixIndex>(
+/// How many entries of the suffix array ahead [`induce_ls`] prefetches the preceding suffix
+const PREFETCH_DISTANCE: usize = 32;
+
+/// Hints the cpu to load `values[index]` into the cache, `index` may be out of bounds
+#[inline(always)]
+fn prefetch<T>(values: &[T], index: usize) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+        // Safety: prefetching does not access memory and never faults
+        unsafe { _mm_prefetch::<_MM_HINT_T0>(values.as_ptr().wrapping_add(index) as *const i8) }
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    let _ = (values, index);
+}
+
+/// Prefetches the type and character of the suffix preceding `suffixes[index]`, which are read
+/// in random order while inducing
+#[inline(always)]
+fn prefetch_preceding<C, I: SuffixIndex>(
+    text: &[C],
+    types: Types<&[u64]>,
+    suffixes: &[I],
+    index: usize,
+) {
+    if let Some(suffix) = suffixes.get(index) {
+        let suffix = suffix.as_index();
+        if suffix != I::MAX && suffix != 0 {
+            prefetch(types.words, (suffix - 1) / WORD);
+            prefetch(text, suffix - 1);
+        }
+    }
+}
+
+/// Inducing calls its checkpoint with the fraction done after every this many entries
+const CHECKPOINT_INTERVAL: usize = if cfg!(test) { 1 << 4 } else { 1 << 22 };
+
+/// Reports the fraction of an induction that is done and stops it on an error
+type Checkpoint<'a> = &'a mut dyn FnMut(f64) -> Result<(), Cancelled>;
+
+/// Induces all suffixes from the sorted LMS suffixes, `visit` is called with every index and its
+/// final suffix from right to left
+fn induce_ls<C: AsIndex, I: SuffixIndex, V: FnMut(usize, I)>(
     text: &[C],
-    types: &[Type],
+    types: Types<&[u64]>,
     buckets: &mut [I],
     suffixes: &mut [I],
-) {
+    mut visit: V,
+    checkpoint: Checkpoint<'_>,
+) -> Result<(), Cancelled> {
     use Type::*;
 
+    let len = suffixes.len() as f64;
+
     // Step 2
     let mut buckets = Buckets::make_starts(text, buckets);
 
     let last = I::from_index(suffixes.len() - 1);
-    if let L = types[last.as_index()] {
+    if let L = types.get(last.as_index()) {
         let index = buckets.suffix_bucket_next(last).as_index();
         suffixes[index] = last;
     }
     for i in 0..suffixes.len() {
+        if i > 0 && i.is_multiple_of(CHECKPOINT_INTERVAL) {
+            checkpoint(i as f64 / len / 2.0)?;
+        }
+        prefetch_preceding(text, types, suffixes, i + PREFETCH_DISTANCE);
         let suffix = suffixes[i];
         if suffix != I::from_index(I::MAX) && suffix != I::from_index(0) {
             let previous_suffix: I = suffix - I::from_index(1);
-            if let L = types[previous_suffix.as_index()] {
+            if let L = types.get(previous_suffix.as_index()) {
                 // Push previous_suffix to the front of its bucket
                 let index = buckets.suffix_bucket_next(previous_suffix).as_index();
                 suffixes[index] = previous_suffix;
@@ -213,10 +545,16 @@ fn induce_ls<C: AsIndex, I: SuffixIndex>(
     let mut buckets = Buckets::make_ends(text, buckets);
 
     for i in (0..suffixes.len()).rev() {
+        if i > 0 && i.is_multiple_of(CHECKPOINT_INTERVAL) {
+            checkpoint(1.0 - i as f64 / len / 2.0)?;
+        }
+        if let Some(ahead) = i.checked_sub(PREFETCH_DISTANCE) {
+            prefetch_preceding(text, types, suffixes, ahead);
+        }
         let suffix = suffixes[i];
         if suffix != I::from_index(I::MAX) && suffix != I::from_index(0) {
             let previous_suffix: I = suffix - I::from_index(1);
-            if let S = types[previous_suffix.as_index()] {
+            if let S = types.get(previous_suffix.as_index()) {
                 // Push previous_suffix to the back of its bucket
                 let index = buckets
                     .suffix_bucket_next_reverse(previous_suffix)
@@ -224,16 +562,359 @@ fn induce_ls<C: AsIndex, I: SuffixIndex>(
                 suffixes[index] = previous_suffix;
             }
         }
+        visit(i, suffix);
+    }
+    buckets.into_cleared();
+    Ok(())
+}
+
+/// Induces all suffixes from the sorted LMS suffixes, in parallel for large texts
+fn induce_suffixes<C: AsIndex, I: SuffixIndex>(
+    text: &[C],
+    types: Types<&[u64]>,
+    buckets: &mut [I],
+    suffixes: &mut [I],
+    checkpoint: Checkpoint<'_>,
+) -> Result<(), Cancelled> {
+    #[cfg(feature = "parallel")]
+    if parallel(suffixes.len()) {
+        return induce_ls_parallel(text, types, buckets, suffixes, checkpoint);
+    }
+    induce_ls(text, types, buckets, suffixes, |_, _| {}, checkpoint)
+}
+
+/// Texts shorter than this are induced sequentially
+#[cfg(feature = "parallel")]
+const PARALLEL_MIN_LEN: usize = 1 << 20;
+/// The number of entries prepared at once by [`induce_ls_parallel`]
+#[cfg(feature = "parallel")]
+const PARALLEL_BLOCK: usize = 1 << 16;
+
+/// Whether `len` entries are processed in parallel, never in a pool with a single thread
+#[cfg(feature = "parallel")]
+fn parallel(len: usize) -> bool {
+    len >= PARALLEL_MIN_LEN && rayon::current_num_threads() > 1
+}
+
+/// The bucket of the suffix preceding `suffix` if it has type `wanted`, `usize::MAX` otherwise
+#[cfg(feature = "parallel")]
+fn preceding_bucket<C: AsIndex, I: SuffixIndex>(
+    text: &[C],
+    types: Types<&[u64]>,
+    suffix: I,
+    wanted: Type,
+) -> usize {
+    if suffix == I::from_index(I::MAX) || suffix == I::from_index(0) {
+        return usize::MAX;
+    }
+    let previous = suffix.as_index() - 1;
+    if types.get(previous) == wanted {
+        text[previous].as_index()
+    } else {
+        usize::MAX
+    }
+}
+
+/// Looks up the bucket of the suffix preceding every entry of `block` in parallel, see
+/// [`preceding_bucket`]
+#[cfg(feature = "parallel")]
+fn prepare_block<C: AsIndex, I: SuffixIndex>(
+    text: &[C],
+    types: Types<&[u64]>,
+    block: &[I],
+    wanted: Type,
+    prepared: &mut [(I, usize)],
+) {
+    use rayon::prelude::*;
+
+    prepared
+        .par_iter_mut()
+        .zip(block.par_iter())
+        .with_min_len(1024)
+        .for_each(|(prepared, &suffix)| {
+            *prepared = (suffix, preceding_bucket(text, types, suffix, wanted));
+        });
+}
+
+/// [`induce_ls`] that looks up the buckets of the preceding suffixes of a block of entries in
+/// parallel before inducing them sequentially (Lao et al., 2018).
+///
+/// Entries written into the block after it was prepared are looked up again.
+#[cfg(feature = "parallel")]
+fn induce_ls_parallel<C: AsIndex, I: SuffixIndex>(
+    text: &[C],
+    types: Types<&[u64]>,
+    buckets: &mut [I],
+    suffixes: &mut [I],
+    checkpoint: Checkpoint<'_>,
+) -> Result<(), Cancelled> {
+    use Type::*;
+
+    let len = suffixes.len() as f64;
+
+    let mut prepared = vec![(I::from_index(0), 0); PARALLEL_BLOCK.min(suffixes.len())];
+    let starts = (0..suffixes.len()).step_by(PARALLEL_BLOCK);
+
+    // Step 2
+    let mut buckets = Buckets::make_starts(text, buckets);
+    let last = I::from_index(suffixes.len() - 1);
+    if let L = types.get(last.as_index()) {
+        let index = buckets.suffix_bucket_next(last).as_index();
+        suffixes[index] = last;
+    }
+    for start in starts.clone() {
+        if start > 0 && start.is_multiple_of(CHECKPOINT_INTERVAL) {
+            checkpoint(start as f64 / len / 2.0)?;
+        }
+        let end = (start + PARALLEL_BLOCK).min(suffixes.len());
+        let prepared = &mut prepared[..end - start];
+        prepare_block(text, types, &suffixes[start..end], L, prepared);
+        for (i, &(prepared_suffix, bucket)) in (start..end).zip(prepared.iter()) {
+            let suffix = suffixes[i];
+            let bucket = if suffix == prepared_suffix {
+                bucket
+            } else {
+                preceding_bucket(text, types, suffix, L)
+            };
+            if bucket != usize::MAX {
+                let index = buckets.next(bucket).as_index();
+                suffixes[index] = suffix - I::from_index(1);
+            }
+        }
+    }
+
+    // Step 3
+    let buckets = buckets.into_cleared();
+    let mut buckets = Buckets::make_ends(text, buckets);
+    for start in starts.rev() {
+        if start > 0 && start.is_multiple_of(CHECKPOINT_INTERVAL) {
+            checkpoint(1.0 - start as f64 / len / 2.0)?;
+        }
+        let end = (start + PARALLEL_BLOCK).min(suffixes.len());
+        let prepared = &mut prepared[..end - start];
+        prepare_block(text, types, &suffixes[start..end], S, prepared);
+        for (i, &(prepared_suffix, bucket)) in (start..end).zip(prepared.iter()).rev() {
+            let suffix = suffixes[i];
+            let bucket = if suffix == prepared_suffix {
+                bucket
+            } else {
+                preceding_bucket(text, types, suffix, S)
+            };
+            if bucket != usize::MAX {
+                let index = buckets.next_reverse(bucket).as_index();
+                suffixes[index] = suffix - I::from_index(1);
+            }
+        }
+    }
+    buckets.into_cleared();
+    Ok(())
+}
+
+/// [`induce_ls`] that replaces every entry with the character preceding its suffix as soon as it
+/// was scanned for the last time, the entry of the first suffix is set to [`SuffixIndex::MAX`].
+///
+/// The row of every suffix starting at a multiple of `rate` is written to `aux`, rows are counted
+/// including the row of the end marker in front.
+fn induce_ls_bwt<C: AsIndex, I: SuffixIndex>(
+    text: &[C],
+    types: Types<&[u64]>,
+    buckets: &mut [I],
+    suffixes: &mut [I],
+    aux: &mut [I],
+    rate: usize,
+) {
+    use Type::*;
+
+    // Step 2
+    let mut buckets = Buckets::make_starts(text, buckets);
+
+    let last = I::from_index(suffixes.len() - 1);
+    if let L = types.get(last.as_index()) {
+        let index = buckets.suffix_bucket_next(last).as_index();
+        suffixes[index] = last;
+    }
+    for i in 0..suffixes.len() {
+        let suffix = suffixes[i];
+        if suffix != I::from_index(I::MAX) && suffix != I::from_index(0) {
+            let previous_suffix: I = suffix - I::from_index(1);
+            if let L = types.get(previous_suffix.as_index()) {
+                let index = buckets.suffix_bucket_next(previous_suffix).as_index();
+                suffixes[index] = previous_suffix;
+            }
+        }
+    }
+
+    // Step 3, suffixes are only induced to the left of the scanned entry
+    let buckets = buckets.into_cleared();
+    let mut buckets = Buckets::make_ends(text, buckets);
+
+    for i in (0..suffixes.len()).rev() {
+        let suffix = suffixes[i];
+        if suffix.as_index().is_multiple_of(rate) {
+            aux[suffix.as_index() / rate] = I::from_index(i + 1);
+        }
+        if suffix == I::from_index(0) {
+            suffixes[i] = I::from_index(I::MAX);
+            continue;
+        }
+        let previous_suffix: I = suffix - I::from_index(1);
+        if let S = types.get(previous_suffix.as_index()) {
+            let index = buckets
+                .suffix_bucket_next_reverse(previous_suffix)
+                .as_index();
+            suffixes[index] = previous_suffix;
+        }
+        suffixes[i] = I::from_index(text[previous_suffix.as_index()].as_index());
+    }
+    buckets.into_cleared();
+}
+
+fn common_prefix<C: Eq>(text: &[C], a: usize, b: usize) -> usize {
+    text[a..]
+        .iter()
+        .zip(&text[b..])
+        .take_while(|(a, b)| a == b)
+        .count()
+}
+
+/// Minimum of `lcp[start..=end]` over the positions pushed to a monotone `stack` of
+/// `(position, lcp)` pairs with increasing lcp values.
+/// The scan direction decides which end of the range is the last pushed position.
+fn stack_min(stack: &[(usize, usize)], start: usize, end: usize, left_to_right: bool) -> usize {
+    let index = if left_to_right {
+        stack.partition_point(|&(position, _)| position < start)
+    } else {
+        stack.partition_point(|&(position, _)| position > end)
+    };
+    stack[index].1
+}
+
+fn stack_push(stack: &mut Vec<(usize, usize)>, position: usize, lcp: usize) {
+    while stack.last().is_some_and(|&(_, top)| top >= lcp) {
+        stack.pop();
+    }
+    stack.push((position, lcp));
+}
+
+/// [`induce_ls`] that also induces the lcp array (Fischer, 2011).
+///
+/// Assumes `lcp` holds the lcp of every placed LMS suffix with the previous LMS suffix of its
+/// bucket and 0 for the first LMS suffix of a bucket.
+///
+/// Two suffixes induced into the same bucket share one more character than their inducers, whose
+/// lcp is the minimum of the lcp values between them. The lcp of the last L and the first S suffix
+/// of a bucket is computed directly, both start with a run of the same character.
+fn induce_ls_lcp<C: AsIndex + Eq, I: SuffixIndex>(
+    text: &[C],
+    types: Types<&[u64]>,
+    buckets: &mut [I],
+    suffixes: &mut [I],
+    lcp: &mut [I],
+) {
+    use Type::*;
+
+    let empty = I::from_index(I::MAX);
+    let mut stack = Vec::new();
+    // For every bucket the start of the lcp range to the inducer of the last suffix put there
+    let mut last_inducer = vec![None; buckets.len()];
+    let mut last_l = vec![None; buckets.len()];
+    let mut seen_s = vec![false; buckets.len()];
+
+    // Step 2
+    let mut buckets = Buckets::make_starts(text, buckets);
+
+    let last = I::from_index(suffixes.len() - 1);
+    if let L = types.get(last.as_index()) {
+        let bucket = text[last.as_index()].as_index();
+        let index = buckets.next(bucket).as_index();
+        suffixes[index] = last;
+        lcp[index] = I::from_index(0);
+        // Induced by the virtual sentinel before the first suffix
+        last_inducer[bucket] = Some(0);
+        last_l[bucket] = Some(index);
+    }
+    for i in 0..suffixes.len() {
+        let suffix = suffixes[i];
+        if suffix == empty {
+            continue;
+        }
+        if let S = types.get(suffix.as_index()) {
+            let bucket = text[suffix.as_index()].as_index();
+            if !replace(&mut seen_s[bucket], true) {
+                let seam = last_l[bucket].map_or(0, |index: usize| {
+                    common_prefix(text, suffixes[index].as_index(), suffix.as_index())
+                });
+                lcp[i] = I::from_index(seam);
+            }
+        }
+        stack_push(&mut stack, i, lcp[i].as_index());
+
+        if suffix != I::from_index(0) {
+            let previous_suffix: I = suffix - I::from_index(1);
+            if let L = types.get(previous_suffix.as_index()) {
+                let bucket = text[previous_suffix.as_index()].as_index();
+                let index = buckets.next(bucket).as_index();
+                suffixes[index] = previous_suffix;
+                let value =
+                    last_inducer[bucket].map_or(0, |start| 1 + stack_min(&stack, start, i, true));
+                lcp[index] = I::from_index(value);
+                last_inducer[bucket] = Some(i + 1);
+                last_l[bucket] = Some(index);
+            }
+        }
+    }
+
+    // Step 3
+    let buckets = buckets.into_cleared();
+    let mut buckets = Buckets::make_ends(text, buckets);
+    stack.clear();
+    last_inducer.fill(None);
+
+    for i in (0..suffixes.len()).rev() {
+        let suffix = suffixes[i];
+        if suffix != empty && suffix != I::from_index(0) {
+            let previous_suffix: I = suffix - I::from_index(1);
+            if let S = types.get(previous_suffix.as_index()) {
+                let bucket = text[previous_suffix.as_index()].as_index();
+                let index = buckets.next_reverse(bucket).as_index();
+                suffixes[index] = previous_suffix;
+                if let Some(end) = last_inducer[bucket] {
+                    let value = 1 + stack_min(&stack, i + 1, end, false);
+                    lcp[index + 1] = I::from_index(value);
+                }
+                last_inducer[bucket] = Some(i);
+            }
+        }
+
+        // All suffixes up to the current one are placed, the lcp with the previous one is final
+        // unless the current suffix is the first S suffix of its bucket
+        if let S = types.get(suffix.as_index()) {
+            let c = &text[suffix.as_index()];
+            let previous = i
+                .checked_sub(1)
+                .map(|i| suffixes[i])
+                .filter(|&previous| previous != empty && text[previous.as_index()] == *c);
+            match previous {
+                None => lcp[i] = I::from_index(0),
+                Some(previous) if types.get(previous.as_index()) == L => {
+                    lcp[i] =
+                        I::from_index(common_prefix(text, previous.as_index(), suffix.as_index()))
+                }
+                Some(_) => {}
+            }
+        }
+        stack_push(&mut stack, i, lcp[i].as_index());
     }
     buckets.into_cleared();
 }
 
 fn induce<'a, C: AsIndex + Eq, I: SuffixIndex>(
     text: &[C],
-    types: &[Type],
+    types: Types<&[u64]>,
     suffixes: &'a mut [I],
     buckets: &mut [I],
-) -> Option<Reduced<'a, I>> {
+    checkpoint: Checkpoint<'_>,
+) -> Result<Option<Reduced<'a, I>>, Cancelled> {
     debug_assert_ne!(types.len(), 0);
     suffixes.fill(I::from_index(0));
 
@@ -251,8 +932,8 @@ fn induce<'a, C: AsIndex + Eq, I: SuffixIndex>(
     }
     let buckets = buckets.into_cleared();
 
-    if lms_count > 1 {
-        induce_ls(text, types, buckets, suffixes);
+    Ok(if lms_count > 1 {
+        induce_suffixes(text, types, buckets, suffixes, checkpoint)?;
         let reduce = reduce(text, types, suffixes);
         debug_assert_eq!(reduce.lms_suffixes_sorted.len(), lms_count);
         Some(reduce)
@@ -269,7 +950,7 @@ fn induce<'a, C: AsIndex + Eq, I: SuffixIndex>(
         })
     } else {
         None
-    }
+    })
 }
 
 #[derive(Debug)]
@@ -287,7 +968,7 @@ struct Reduced<'a, I> {
 /// - suffixes contains the sorted lms substrings
 fn reduce<'a, C: AsIndex + Eq, I: SuffixIndex>(
     text: &[C],
-    types: &[Type],
+    types: Types<&[u64]>,
     suffixes: &'a mut [I],
 ) -> Reduced<'a, I> {
     // There is at most 1 lms every two characters
@@ -305,25 +986,22 @@ fn reduce<'a, C: AsIndex + Eq, I: SuffixIndex>(
         rest.fill(I::from_index(I::MAX));
 
         let mut iter = lms_suffixes_sorted.iter();
-        let (mut last_str, mut last_types) = {
+        let (mut last_str, mut last_suffix) = {
             let first_suffix = iter.next().unwrap().as_index();
             rest[first_suffix / 2] = I::from_index(0);
-            let str = lms_substring(first_suffix, text, types);
-            let types = &types[first_suffix..first_suffix + str.len()];
-            (str, types)
+            (lms_substring(first_suffix, text, types), first_suffix)
         };
 
         let mut order = 0;
         for suffix in iter {
             let suffix = suffix.as_index();
             let sub_str = lms_substring(suffix, text, types);
-            let types = &types[suffix..suffix + sub_str.len()];
-            if !lms_substrings_eq(last_str, last_types, sub_str, types) {
+            if !lms_substrings_eq(last_str, last_suffix, sub_str, suffix, types) {
                 order += 1;
             }
             rest[suffix / 2] = I::from_index(order);
             last_str = sub_str;
-            last_types = types;
+            last_suffix = suffix;
         }
 
         (
@@ -339,12 +1017,132 @@ fn reduce<'a, C: AsIndex + Eq, I: SuffixIndex>(
     }
 }
 
-fn induced_sort<C: AsIndex + Ord, I: SuffixIndex>(
+/// Places the sorted LMS suffixes in front of `suffix_array` at the ends of their buckets and
+/// induces all other suffixes from them
+pub(crate) fn induce_from_lms<C: AsIndex, I: SuffixIndex>(
     text: &[C],
+    types: &Types,
     suffix_array: &mut [I],
-    types: &mut [Type],
+    lms_count: usize,
+    buckets: &mut [I],
+) {
+    suffix_array[lms_count..].fill(I::from_index(I::MAX));
+    let mut bucket_ends = Buckets::make_ends(text, buckets);
+    for i in (0..lms_count).rev() {
+        let suffix = replace(&mut suffix_array[i], I::from_index(I::MAX));
+        let index = bucket_ends.suffix_bucket_next_reverse(suffix).as_index();
+        suffix_array[index] = suffix;
+    }
+    let buckets = bucket_ends.into_cleared();
+    let result = induce_suffixes(text, types.view(), buckets, suffix_array, &mut |_| Ok(()));
+    debug_assert!(result.is_ok());
+}
+
+pub(crate) fn induced_sort<C: AsIndex + Ord, I: SuffixIndex>(
+    text: &[C],
+    suffix_array: &mut [I],
+    types: &mut Types,
     buckets: &mut Vec<I>,
 ) {
+    let result = induced_sort_observed(
+        text,
+        suffix_array,
+        types.view_mut(),
+        buckets,
+        Output::SuffixArray,
+        &mut |_| Ok(()),
+        0.0..1.0,
+        0,
+    );
+    debug_assert!(result.is_ok());
+}
+
+/// What the final induction computes besides the suffix array
+enum Output<'a, I> {
+    SuffixArray,
+    Lcp(&'a mut [I]),
+    Inverse(&'a mut [I]),
+    /// The suffix array is replaced by the Burrows-Wheeler transform, see [`induce_ls_bwt`]
+    Bwt {
+        aux: &'a mut [I],
+        rate: usize,
+    },
+}
+
+/// Texts and reduced texts shorter than this are sorted by comparing their suffixes.
+///
+/// [`radix_sort`](crate::radix_sort) is no faster base case for the recursion. Sorting random
+/// byte texts with [`sort`] and with `radix_sort::make_suffix_array`, `base_case_benchmark`
+/// measured:
+///
+/// | length | 4 characters: SA-IS | radix sort | 256 characters: SA-IS | radix sort |
+/// |-------:|--------------------:|-----------:|----------------------:|-----------:|
+/// |     16 |              0.3 µs |     2.7 µs |                0.3 µs |     0.6 µs |
+/// |     64 |              2.4 µs |    13.8 µs |                2.4 µs |     3.1 µs |
+/// |    256 |               11 µs |      58 µs |                7.8 µs |      20 µs |
+/// |   1024 |               39 µs |     244 µs |                 26 µs |      82 µs |
+/// |   4096 |              270 µs |    1196 µs |                267 µs |     233 µs |
+///
+/// Only the longest text over all bytes favours the radix sort, which sorts bytes only, while a
+/// reduced text that long usually has more than 256 characters.
+const COMPARISON_SORT_LEN: usize = 64;
+
+/// Share of the work of one level spent classifying, before the recursion and in the recursion
+const CLASSIFY_SHARE: f64 = 0.05;
+const SORT_LMS_SHARE: f64 = 0.45;
+const RECURSE_SHARE: f64 = 0.2;
+
+/// Sorts the suffixes of a short text by comparing them, without buckets or types
+fn comparison_sort<C: Ord, I: SuffixIndex>(
+    text: &[C],
+    suffix_array: &mut [I],
+    output: Output<'_, I>,
+) {
+    for (i, suffix) in suffix_array.iter_mut().enumerate() {
+        *suffix = I::from_index(i);
+    }
+    suffix_array.sort_unstable_by(|a, b| text[a.as_index()..].cmp(&text[b.as_index()..]));
+    match output {
+        Output::Inverse(inverse) => inverse_into(suffix_array, inverse),
+        Output::Lcp(lcp) => {
+            lcp[0] = I::from_index(0);
+            for i in 1..suffix_array.len() {
+                let (a, b) = (suffix_array[i - 1].as_index(), suffix_array[i].as_index());
+                lcp[i] = I::from_index(common_prefix(text, a, b));
+            }
+        }
+        Output::SuffixArray | Output::Bwt { .. } => {}
+    }
+}
+
+/// Reports progress within `span` of the whole construction to `observer` after every phase and
+/// stops if it returns an error.
+///
+/// After an error `buckets` has its original length, the contents of all buffers are unspecified.
+#[allow(clippy::too_many_arguments)]
+fn induced_sort_observed<C, I, P>(
+    text: &[C],
+    suffix_array: &mut [I],
+    mut types: Types<&mut [u64]>,
+    buckets: &mut Vec<I>,
+    output: Output<'_, I>,
+    observer: &mut P,
+    span: Range<f64>,
+    depth: usize,
+) -> Result<(), Cancelled>
+where
+    C: AsIndex + Ord,
+    I: SuffixIndex,
+    P: FnMut(Progress) -> Result<(), Cancelled>,
+{
+    let width = span.end - span.start;
+    let checkpoint = |phase, fraction: f64| Progress {
+        phase,
+        depth,
+        fraction: span.start + fraction * width,
+        bytes: text.len(),
+    };
+
     debug_assert_eq!(text.len(), suffix_array.len());
     if cfg!(debug_assertions) {
         for c in text {
@@ -352,9 +1150,32 @@ fn induced_sort<C: AsIndex + Ord, I: SuffixIndex>(
         }
     }
 
-    classify(text, types);
-    let reduced = induce(text, types, suffix_array, buckets);
-    if let Some(reduced) = reduced {
+    if text.len() < COMPARISON_SORT_LEN && !matches!(output, Output::Bwt { .. }) {
+        comparison_sort(text, suffix_array, output);
+        return observer(checkpoint(Phase::Induce, 1.0));
+    }
+
+    let (mut lcp, mut bwt, mut inverse) = (None, None, None);
+    match output {
+        Output::SuffixArray => {}
+        Output::Inverse(output) => inverse = Some(output),
+        Output::Lcp(output) => {
+            output.fill(I::from_index(0));
+            lcp = Some(output);
+        }
+        Output::Bwt { aux, rate } => bwt = Some((aux, rate)),
+    }
+    classify(text, types.view_mut());
+    observer(checkpoint(Phase::Classify, CLASSIFY_SHARE))?;
+    let reduced = induce(text, types.view(), suffix_array, buckets, &mut |fraction| {
+        let share = SORT_LMS_SHARE - CLASSIFY_SHARE;
+        observer(checkpoint(
+            Phase::SortLms,
+            CLASSIFY_SHARE + fraction * share,
+        ))
+    })?;
+    observer(checkpoint(Phase::SortLms, SORT_LMS_SHARE))?;
+    if let Some(reduced) = reduced {
         let Reduced {
             lms_suffixes_sorted,
             reduced_str,
@@ -363,6 +1184,8 @@ fn induced_sort<C: AsIndex + Ord, I: SuffixIndex>(
         debug_assert!(max_order <= reduced_str.len());
         debug_assert_eq!(lms_suffixes_sorted.len(), reduced_str.len());
         let lms_count = lms_suffixes_sorted.len();
+        // The lcp of every lms suffix with the previous one in lms suffix order
+        let mut lms_lcp = None;
         if max_order < reduced_str.len() - 1 {
             // let buckets = &mut buckets[..=max_order];
             let suffix_array = lms_suffixes_sorted;
@@ -370,33 +1193,68 @@ fn induced_sort<C: AsIndex + Ord, I: SuffixIndex>(
             let old_len = buckets.len();
             buckets.resize(required_len, I::from_index(0));
 
-            induced_sort(
+            let start = span.start + SORT_LMS_SHARE * width;
+            let result = induced_sort_observed(
                 reduced_str,
                 suffix_array,
-                &mut types[..suffix_array.len()],
+                types.prefix_mut(suffix_array.len()),
                 buckets,
+                Output::SuffixArray,
+                observer,
+                start..start + RECURSE_SHARE * width,
+                depth + 1,
             );
 
             // restore
-            classify_sub_slice(text, &mut types[..suffix_array.len() + 1]);
             buckets.resize(old_len, I::from_index(0));
+            result?;
+            classify_sub_slice(text, types.prefix_mut(suffix_array.len() + 1));
             buckets.fill(I::from_index(0));
+            let reduced_lcp = lcp.is_some().then(|| lcp_array(reduced_str, suffix_array));
 
             // Convert the lexical names to suffix indices, lookup their order, write to lms_suffixes_sorted
             let suffix_indices = reduced_str;
             let mut suffix_indices_offset = 0;
             for suffix in (1..types.len()).map(I::from_index) {
-                if is_lms(suffix, types) {
+                if is_lms(suffix, types.view()) {
                     suffix_indices[suffix_indices_offset] = suffix;
                     suffix_indices_offset += 1;
                 }
             }
 
+            if let Some(mut reduced_lcp) = reduced_lcp {
+                // Equal names are equal lms substrings, only the text after them is compared
+                let position = |i: I| suffix_indices[i.as_index()].as_index();
+                for i in 1..lms_count {
+                    let (a, b) = (suffix_array[i - 1], suffix_array[i]);
+                    let names = reduced_lcp[i].as_index();
+                    let skip = match names {
+                        0 => 0,
+                        names => position(a + I::from_index(names - 1)) - position(a),
+                    };
+                    let value = skip + common_prefix(text, position(a) + skip, position(b) + skip);
+                    reduced_lcp[i] = I::from_index(value);
+                }
+                lms_lcp = Some(reduced_lcp);
+            }
+
             for i in 0..lms_count {
                 suffix_array[i] = suffix_indices[suffix_array[i].as_index()];
             }
         } else {
             // reduced_str is unique => this is the order
+            if lcp.is_some() {
+                let lms = &lms_suffixes_sorted;
+                let values = (0..lms_count).map(|i| match i {
+                    0 => I::from_index(0),
+                    i => I::from_index(common_prefix(
+                        text,
+                        lms[i - 1].as_index(),
+                        lms[i].as_index(),
+                    )),
+                });
+                lms_lcp = Some(values.collect::<Vec<_>>());
+            }
         }
 
         // lms_suffixes_sorted now contains all lms suffixes in the correct order
@@ -412,11 +1270,41 @@ fn induced_sort<C: AsIndex + Ord, I: SuffixIndex>(
             suffix_array[i] = I::from_index(I::MAX);
             let index = buckets.suffix_bucket_next_reverse(suffix).as_index();
             suffix_array[index] = suffix;
+            if let (Some(lcp), Some(lms_lcp)) = (lcp.as_deref_mut(), &lms_lcp) {
+                let same_bucket =
+                    i > 0 && text[suffix_array[i - 1].as_index()] == text[suffix.as_index()];
+                lcp[index] = if same_bucket {
+                    lms_lcp[i]
+                } else {
+                    I::from_index(0)
+                };
+            }
         }
         buckets.into_cleared();
     }
+    observer(checkpoint(Phase::Recurse, SORT_LMS_SHARE + RECURSE_SHARE))?;
 
-    induce_ls(text, types, buckets, suffix_array);
+    let types = types.view();
+    let induced = &mut |fraction| {
+        let start = SORT_LMS_SHARE + RECURSE_SHARE;
+        observer(checkpoint(Phase::Induce, start + fraction * (1.0 - start)))
+    };
+    match (lcp, bwt, inverse) {
+        (Some(lcp), _, _) => induce_ls_lcp(text, types, buckets, suffix_array, lcp),
+        (None, Some((aux, rate)), _) => {
+            induce_ls_bwt(text, types, buckets, suffix_array, aux, rate)
+        }
+        (None, None, Some(inverse)) => induce_ls(
+            text,
+            types,
+            buckets,
+            suffix_array,
+            |i, suffix| inverse[suffix.as_index()] = I::from_index(i),
+            induced,
+        )?,
+        (None, None, None) => induce_suffixes(text, types, buckets, suffix_array, induced)?,
+    }
+    observer(checkpoint(Phase::Induce, 1.0))
 }
 
 pub fn sort<I: SuffixIndex, C: AsIndex + Ord>(
@@ -424,34 +1312,632 @@ pub fn sort<I: SuffixIndex, C: AsIndex + Ord>(
     suffix_array: &mut [I],
     types: &mut [Type],
     buckets: &mut Vec<I>,
+) {
+    assert_eq!(text.len(), types.len());
+    with_packed(types, |types| {
+        sort_packed(text, suffix_array, types, buckets)
+    });
+}
+
+/// Like [`sort`], but with the types packed into one bit per suffix
+pub fn sort_packed<I: SuffixIndex, C: AsIndex + Ord>(
+    text: &[C],
+    suffix_array: &mut [I],
+    types: &mut Types,
+    buckets: &mut Vec<I>,
 ) {
     assert_eq!(text.len(), suffix_array.len());
     assert_eq!(text.len(), types.len());
-    assert!(buckets.len() - 1 >= C::MAX);
+    assert!(buckets.len() > C::MAX);
     induced_sort(text, suffix_array, types, buckets);
 }
 
+/// Runs `f` with `types` packed into a [`Types`] and unpacks the result back into `types`
+fn with_packed<R>(types: &mut [Type], f: impl FnOnce(&mut Types) -> R) -> R {
+    let mut packed = Types::new(types.len());
+    let result = f(&mut packed);
+    for (t, packed) in types.iter_mut().zip(packed.iter()) {
+        *t = packed;
+    }
+    result
+}
+
+/// Like [`sort`], but returns an error instead of panicking on invalid input
+pub fn try_sort<I: SuffixIndex, C: AsIndex + Ord>(
+    text: &[C],
+    suffix_array: &mut [I],
+    types: &mut [Type],
+    buckets: &mut Vec<I>,
+) -> Result<(), SaisError> {
+    if text.len() != suffix_array.len() || text.len() != types.len() {
+        return Err(SaisError::LengthMismatch {
+            text: text.len(),
+            suffix_array: suffix_array.len(),
+            types: types.len(),
+        });
+    }
+    if text.is_empty() {
+        return Err(SaisError::EmptyInput);
+    }
+    if text.len() >= I::MAX {
+        return Err(SaisError::TextTooLargeForIndex {
+            len: text.len(),
+            max: I::MAX - 1,
+        });
+    }
+    let required = text.iter().map(|c| c.as_index()).max().unwrap() + 1;
+    if buckets.len() < required {
+        return Err(SaisError::AlphabetTooLarge {
+            buckets: buckets.len(),
+            required,
+        });
+    }
+    with_packed(types, |types| {
+        induced_sort(text, suffix_array, types, buckets)
+    });
+    Ok(())
+}
+
+/// Like [`suffix_array`], but returns an error if the text is too large for the index type.
+/// The suffix array of an empty text is empty.
+pub fn try_suffix_array<I: SuffixIndex>(text: &[u8]) -> Result<Vec<I>, SaisError> {
+    if text.len() >= I::MAX {
+        return Err(SaisError::TextTooLargeForIndex {
+            len: text.len(),
+            max: I::MAX - 1,
+        });
+    }
+    Ok(suffix_array(text))
+}
+
+/// Computes the suffix array of `text`, allocating all buffers
+pub fn suffix_array<I: SuffixIndex>(text: &[u8]) -> Vec<I> {
+    assert!(text.len() < I::MAX, "text is too large for the index type");
+    if text.is_empty() {
+        return Vec::new();
+    }
+    let mut suffix_array = vec![I::from_index(0); text.len()];
+    let mut types = Types::new(text.len());
+    let mut buckets = vec![I::from_index(0); u8::MAX as usize + 1];
+    sort_packed(text, &mut suffix_array, &mut types, &mut buckets);
+    suffix_array
+}
+
+/// The inverse suffix array, the rank of every suffix in `suffix_array`
+pub fn inverse<I: SuffixIndex>(suffix_array: &[I]) -> Vec<I> {
+    let mut inverse = vec![I::from_index(0); suffix_array.len()];
+    inverse_into(suffix_array, &mut inverse);
+    inverse
+}
+
+/// Like [`sort`], also fills `inverse` with the inverse suffix array while the final induction
+/// places the suffixes
+pub fn sort_with_inverse<I: SuffixIndex, C: AsIndex + Ord>(
+    text: &[C],
+    suffix_array: &mut [I],
+    inverse: &mut [I],
+    types: &mut [Type],
+    buckets: &mut Vec<I>,
+) {
+    assert_eq!(text.len(), suffix_array.len());
+    assert_eq!(text.len(), inverse.len());
+    assert_eq!(text.len(), types.len());
+    assert!(buckets.len() > C::MAX);
+    let result = with_packed(types, |types| {
+        induced_sort_observed(
+            text,
+            suffix_array,
+            types.view_mut(),
+            buckets,
+            Output::Inverse(inverse),
+            &mut |_| Ok(()),
+            0.0..1.0,
+            0,
+        )
+    });
+    debug_assert!(result.is_ok());
+}
+
+/// Like [`inverse`], writing the ranks to a caller-provided buffer
+pub fn inverse_into<I: SuffixIndex>(suffix_array: &[I], inverse: &mut [I]) {
+    assert_eq!(suffix_array.len(), inverse.len());
+    for (index, suffix) in suffix_array.iter().enumerate() {
+        inverse[suffix.as_index()] = I::from_index(index);
+    }
+}
+
+/// Computes the suffix array of the bytes of `text`
+pub fn str_suffix_array<I: SuffixIndex>(text: &str) -> Vec<I> {
+    suffix_array(text.as_bytes())
+}
+
+/// Computes the suffix array of a text of 16 bit characters like UTF-16.
+///
+/// The characters are replaced by their ranks among the characters that occur first, so the
+/// buckets only cover those and stay in the cache while inducing instead of spanning all 65536
+/// characters.
+pub fn u16_suffix_array<I: SuffixIndex>(text: &[u16]) -> Vec<I> {
+    assert!(text.len() < I::MAX, "text is too large for the index type");
+    if text.is_empty() {
+        return Vec::new();
+    }
+    let (ranked, symbols) = ranks::<_, u16>(text);
+
+    let mut suffix_array = vec![I::from_index(0); text.len()];
+    let mut buckets = vec![I::from_index(0); symbols.len()];
+    induced_sort(
+        &ranked,
+        &mut suffix_array,
+        &mut Types::new(text.len()),
+        &mut buckets,
+    );
+    suffix_array
+}
+
+/// Scratch buffers reused across constructions of many suffix arrays
+#[derive(Debug, Clone)]
+pub struct Workspace<I> {
+    suffix_array: Vec<I>,
+    types: Types,
+    buckets: Vec<I>,
+}
+
+impl<I: SuffixIndex> Default for Workspace<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I: SuffixIndex> Workspace<I> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Preallocates the buffers for texts of up to `len` characters
+    pub fn with_capacity(len: usize) -> Self {
+        Self {
+            suffix_array: Vec::with_capacity(len),
+            types: Types::with_capacity(len),
+            buckets: vec![I::from_index(0); u8::MAX as usize + 1],
+        }
+    }
+
+    /// Computes the suffix array of `text`, it is valid until the next construction
+    pub fn build(&mut self, text: &[u8]) -> &[I] {
+        assert!(text.len() < I::MAX, "text is too large for the index type");
+        // All buffers but the buckets are initialized by the construction
+        self.suffix_array.resize(text.len(), I::from_index(0));
+        self.types.resize(text.len());
+        self.buckets.resize(u8::MAX as usize + 1, I::from_index(0));
+        self.buckets.fill(I::from_index(0));
+        if !text.is_empty() {
+            induced_sort(
+                text,
+                &mut self.suffix_array,
+                &mut self.types,
+                &mut self.buckets,
+            );
+        }
+        &self.suffix_array
+    }
+}
+
+/// Like [`sort`], also computes the lcp array of the suffix array in the same induction passes
+pub fn sort_with_lcp<I: SuffixIndex, C: AsIndex + Ord>(
+    text: &[C],
+    suffix_array: &mut [I],
+    lcp: &mut [I],
+    types: &mut [Type],
+    buckets: &mut Vec<I>,
+) {
+    assert_eq!(text.len(), suffix_array.len());
+    assert_eq!(text.len(), lcp.len());
+    assert_eq!(text.len(), types.len());
+    assert!(buckets.len() > C::MAX);
+    let result = with_packed(types, |types| {
+        induced_sort_observed(
+            text,
+            suffix_array,
+            types.view_mut(),
+            buckets,
+            Output::Lcp(lcp),
+            &mut |_| Ok(()),
+            0.0..1.0,
+            0,
+        )
+    });
+    debug_assert!(result.is_ok());
+}
+
+/// Computes the suffix array and the lcp array of `text`, allocating all buffers
+pub fn suffix_array_with_lcp<I: SuffixIndex>(text: &[u8]) -> (Vec<I>, Vec<I>) {
+    assert!(text.len() < I::MAX, "text is too large for the index type");
+    if text.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let mut suffix_array = vec![I::from_index(0); text.len()];
+    let mut lcp = vec![I::from_index(0); text.len()];
+    let mut types = vec![Type::default(); text.len()];
+    let mut buckets = vec![I::from_index(0); u8::MAX as usize + 1];
+    sort_with_lcp(text, &mut suffix_array, &mut lcp, &mut types, &mut buckets);
+    (suffix_array, lcp)
+}
+
+/// Like [`sort`], but leaves the Burrows-Wheeler transform in `suffix_array` and samples rows to
+/// `aux`, see [`induce_ls_bwt`]
+pub(crate) fn sort_bwt<I: SuffixIndex, C: AsIndex + Ord>(
+    text: &[C],
+    suffix_array: &mut [I],
+    aux: &mut [I],
+    rate: usize,
+    types: &mut Types,
+    buckets: &mut Vec<I>,
+) {
+    assert_eq!(text.len(), suffix_array.len());
+    assert_eq!(aux.len(), text.len().div_ceil(rate));
+    assert_eq!(text.len(), types.len());
+    assert!(buckets.len() > C::MAX);
+    let result = induced_sort_observed(
+        text,
+        suffix_array,
+        types.view_mut(),
+        buckets,
+        Output::Bwt { aux, rate },
+        &mut |_| Ok(()),
+        0.0..1.0,
+        0,
+    );
+    debug_assert!(result.is_ok());
+}
+
+/// Like [`sort`], calls `progress` after every phase of the construction and periodically while
+/// inducing the suffix array or sorting the LMS substrings of a large text
+pub fn sort_with_progress<I: SuffixIndex, C: AsIndex + Ord, P: FnMut(Progress)>(
+    text: &[C],
+    suffix_array: &mut [I],
+    types: &mut [Type],
+    buckets: &mut Vec<I>,
+    progress: P,
+) {
+    assert_eq!(text.len(), types.len());
+    with_packed(types, |types| {
+        sort_packed_with_progress(text, suffix_array, types, buckets, progress)
+    });
+}
+
+fn sort_packed_with_progress<I: SuffixIndex, C: AsIndex + Ord, P: FnMut(Progress)>(
+    text: &[C],
+    suffix_array: &mut [I],
+    types: &mut Types,
+    buckets: &mut Vec<I>,
+    mut progress: P,
+) {
+    assert_eq!(text.len(), suffix_array.len());
+    assert_eq!(text.len(), types.len());
+    assert!(buckets.len() > C::MAX);
+    let mut observer = |checkpoint| {
+        progress(checkpoint);
+        Ok(())
+    };
+    let result = induced_sort_observed(
+        text,
+        suffix_array,
+        types.view_mut(),
+        buckets,
+        Output::SuffixArray,
+        &mut observer,
+        0.0..1.0,
+        0,
+    );
+    debug_assert!(result.is_ok());
+}
+
+/// Like [`sort`], measures the recursion levels and phases of the construction.
+///
+/// The time between two checkpoints is attributed to the phase of the later one, see
+/// [`sort_with_progress`].
+pub fn sort_with_stats<I: SuffixIndex, C: AsIndex + Ord>(
+    text: &[C],
+    suffix_array: &mut [I],
+    types: &mut Types,
+    buckets: &mut Vec<I>,
+) -> Stats {
+    let mut levels = Vec::<Level>::new();
+    let mut last = Instant::now();
+    sort_packed_with_progress(text, suffix_array, types, buckets, |progress| {
+        let now = Instant::now();
+        if levels.len() <= progress.depth {
+            levels.resize(progress.depth + 1, Level::default());
+        }
+        let level = &mut levels[progress.depth];
+        level.len = progress.bytes;
+        *level.duration_mut(progress.phase) += now - last;
+        last = now;
+    });
+
+    // Short texts are sorted without classifying them, the recursion restores the types of the
+    // input text otherwise
+    if !text.is_empty() && text.len() < COMPARISON_SORT_LEN {
+        classify(text, types.view_mut());
+    }
+    let lms_suffixes = (1..text.len())
+        .filter(|&i| types.get(i - 1) == Type::L && types.get(i) == Type::S)
+        .count();
+    Stats {
+        levels,
+        lms_suffixes,
+        scratch_capacity: types.words.capacity() * std::mem::size_of::<u64>()
+            + buckets.capacity() * std::mem::size_of::<I>(),
+    }
+}
+
+/// Like [`sort`], stops early once `token` is cancelled, it is polled between the phases of the
+/// construction and periodically while inducing.
+///
+/// After cancellation the contents of `suffix_array` and `types` are unspecified and `buckets` is
+/// zeroed with its original length, so all buffers can be reused for another construction.
+pub fn sort_cancellable<I: SuffixIndex, C: AsIndex + Ord, T: CancellationToken + ?Sized>(
+    text: &[C],
+    suffix_array: &mut [I],
+    types: &mut [Type],
+    buckets: &mut Vec<I>,
+    token: &T,
+) -> Result<(), Cancelled> {
+    assert_eq!(text.len(), suffix_array.len());
+    assert_eq!(text.len(), types.len());
+    assert!(buckets.len() > C::MAX);
+    let mut observer = |_| {
+        if token.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    };
+    let result = with_packed(types, |types| {
+        induced_sort_observed(
+            text,
+            suffix_array,
+            types.view_mut(),
+            buckets,
+            Output::SuffixArray,
+            &mut observer,
+            0.0..1.0,
+            0,
+        )
+    });
+    if result.is_err() {
+        buckets.fill(I::from_index(0));
+    }
+    result
+}
+
+/// The bit of a suffix index marking an S suffix in [`sort_without_types`]
+fn type_flag<I: SuffixIndex>() -> usize {
+    (I::MAX >> 1) + 1
+}
+
+/// Calls `f` with every LMS suffix from right to left, the types are computed on the fly
+fn for_each_lms_reverse<C: Ord, F: FnMut(usize)>(text: &[C], mut f: F) {
+    // The type of the suffix at `i`, the last suffix is L
+    let mut s = false;
+    for i in (1..text.len()).rev() {
+        let previous_s = match text[i - 1].cmp(&text[i]) {
+            Ordering::Less => true,
+            Ordering::Greater => false,
+            Ordering::Equal => s,
+        };
+        if s && !previous_s {
+            f(i);
+        }
+        s = previous_s;
+    }
+}
+
+/// [`induce_ls`] with the type of every placed suffix in its [`type_flag`] instead of a types
+/// buffer. The type of the preceding suffix follows from the characters and the type of the
+/// scanned suffix if they are equal.
+fn induce_ls_flagged<C: AsIndex + Ord, I: SuffixIndex>(
+    text: &[C],
+    buckets: &mut [I],
+    suffixes: &mut [I],
+) {
+    let flag = type_flag::<I>();
+    let empty = I::from_index(I::MAX);
+
+    // Step 2
+    let mut buckets = Buckets::make_starts(text, buckets);
+    let last = I::from_index(suffixes.len() - 1);
+    let index = buckets.suffix_bucket_next(last).as_index();
+    suffixes[index] = last;
+    for i in 0..suffixes.len() {
+        if suffixes[i] == empty {
+            continue;
+        }
+        let (suffix, s) = (
+            suffixes[i].as_index() & !flag,
+            suffixes[i].as_index() & flag != 0,
+        );
+        if suffix == 0 {
+            continue;
+        }
+        let previous = suffix - 1;
+        let previous_l = match text[previous].cmp(&text[suffix]) {
+            Ordering::Less => false,
+            Ordering::Greater => true,
+            Ordering::Equal => !s,
+        };
+        if previous_l {
+            let index = buckets.next(text[previous].as_index()).as_index();
+            suffixes[index] = I::from_index(previous);
+        }
+    }
+
+    // Step 3
+    let buckets = buckets.into_cleared();
+    let mut buckets = Buckets::make_ends(text, buckets);
+    for i in (0..suffixes.len()).rev() {
+        if suffixes[i] == empty {
+            continue;
+        }
+        let (suffix, s) = (
+            suffixes[i].as_index() & !flag,
+            suffixes[i].as_index() & flag != 0,
+        );
+        if suffix == 0 {
+            continue;
+        }
+        let previous = suffix - 1;
+        let previous_s = match text[previous].cmp(&text[suffix]) {
+            Ordering::Less => true,
+            Ordering::Greater => false,
+            Ordering::Equal => s,
+        };
+        if previous_s {
+            let index = buckets.next_reverse(text[previous].as_index()).as_index();
+            suffixes[index] = I::from_index(previous | flag);
+        }
+    }
+    buckets.into_cleared();
+}
+
+/// Induced sorting that keeps the types of the placed suffixes in their highest bit, see
+/// [`sort_without_types`].
+///
+/// Two LMS substrings are equal if they have the same length and characters, the types follow
+/// from the characters. The lengths are kept where the names are written afterwards.
+fn induced_sort_flagged<C: AsIndex + Ord, I: SuffixIndex>(
+    text: &[C],
+    suffix_array: &mut [I],
+    buckets: &mut Vec<I>,
+) {
+    let flag = type_flag::<I>();
+    let empty = I::from_index(I::MAX);
+    suffix_array.fill(empty);
+
+    // Sort the LMS substrings
+    let mut bucket_ends = Buckets::make_ends(text, buckets);
+    for_each_lms_reverse(text, |suffix| {
+        let index = bucket_ends.next_reverse(text[suffix].as_index()).as_index();
+        suffix_array[index] = I::from_index(suffix | flag);
+    });
+    bucket_ends.into_cleared();
+    induce_ls_flagged(text, buckets, suffix_array);
+
+    let (lms_suffixes_sorted, rest) = retain(suffix_array, |&suffix| {
+        let suffix = suffix.as_index();
+        suffix != I::MAX
+            && suffix & flag != 0
+            && suffix != flag
+            && text[(suffix & !flag) - 1] > text[suffix & !flag]
+    });
+    for suffix in lms_suffixes_sorted.iter_mut() {
+        *suffix = I::from_index(suffix.as_index() & !flag);
+    }
+    let lms_count = lms_suffixes_sorted.len();
+
+    if lms_count > 1 {
+        // Name the LMS substrings, the length of the last one exceeds the text
+        rest.fill(empty);
+        let mut next = text.len() + 1;
+        for_each_lms_reverse(text, |suffix| {
+            rest[suffix / 2] = I::from_index(next - suffix);
+            next = suffix + 1;
+        });
+        let mut order = 0;
+        let mut last: Option<(usize, usize)> = None;
+        for &suffix in lms_suffixes_sorted.iter() {
+            let suffix = suffix.as_index();
+            let len = rest[suffix / 2].as_index();
+            let equal = last.is_some_and(|(last, last_len)| {
+                len == last_len
+                    && last.max(suffix) + len <= text.len()
+                    && text[last..last + len] == text[suffix..suffix + len]
+            });
+            if last.is_some() && !equal {
+                order += 1;
+            }
+            rest[suffix / 2] = I::from_index(order);
+            last = Some((suffix, len));
+        }
+        let reduced_str = retain(rest, |&order| order != empty).0;
+
+        if order + 1 < lms_count {
+            let old_len = buckets.len();
+            buckets.resize(order + 1, I::from_index(0));
+            induced_sort_flagged(reduced_str, lms_suffixes_sorted, buckets);
+            buckets.resize(old_len, I::from_index(0));
+            buckets.fill(I::from_index(0));
+
+            // Map the suffixes of the reduced text back to their LMS suffixes
+            let mut offset = lms_count;
+            for_each_lms_reverse(text, |suffix| {
+                offset -= 1;
+                reduced_str[offset] = I::from_index(suffix);
+            });
+            for suffix in lms_suffixes_sorted.iter_mut() {
+                *suffix = reduced_str[suffix.as_index()];
+            }
+        }
+    }
+
+    // Place the sorted LMS suffixes at the ends of their buckets
+    suffix_array[lms_count..].fill(empty);
+    let mut bucket_ends = Buckets::make_ends(text, buckets);
+    for i in (0..lms_count).rev() {
+        let suffix = replace(&mut suffix_array[i], empty);
+        let index = bucket_ends.suffix_bucket_next_reverse(suffix).as_index();
+        suffix_array[index] = I::from_index(suffix.as_index() | flag);
+    }
+    bucket_ends.into_cleared();
+    induce_ls_flagged(text, buckets, suffix_array);
+    for suffix in suffix_array.iter_mut() {
+        *suffix = I::from_index(suffix.as_index() & !flag);
+    }
+}
+
+/// Like [`sort`], but without a buffer for the types of the suffixes, they are kept in the highest
+/// bit of the suffix indices while inducing (Nong, 2013).
+///
+/// The text must be shorter than half of [`AsIndex::MAX`] of the index type.
+pub fn sort_without_types<I: SuffixIndex, C: AsIndex + Ord>(
+    text: &[C],
+    suffix_array: &mut [I],
+    buckets: &mut Vec<I>,
+) {
+    assert_eq!(text.len(), suffix_array.len());
+    assert!(
+        text.len() < type_flag::<I>(),
+        "text is too large for the index type"
+    );
+    assert!(buckets.len() > C::MAX);
+    if !text.is_empty() {
+        induced_sort_flagged(text, suffix_array, buckets);
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use std::cell::Cell;
     use std::fs::File;
     use std::io::Read;
+    use std::sync::atomic::AtomicBool;
     use std::time::SystemTime;
 
     use super::*;
 
     fn is_sorted<I: AsIndex>(indices: &[I], text: &[u8]) -> Option<usize> {
-        let compare = |a: &&I, b: &&I| (&text[a.as_index()..]).cmp(&text[b.as_index()..]);
+        let compare = |a: &&I, b: &&I| text[a.as_index()..].cmp(&text[b.as_index()..]);
         indices
             .windows(2)
             .enumerate()
-            .find_map(|(index, w)| (compare(&&w[0], &&w[1]) == Ordering::Greater).then(|| index))
+            .find_map(|(index, w)| (compare(&&w[0], &&w[1]) == Ordering::Greater).then_some(index))
     }
 
     #[test]
     fn test_sort() {
         const TEXT: &str = "And now map the suffix indices from the reduced text to suffix";
-        let mut types = [Type::S; TEXT.len()];
-        classify(TEXT.as_bytes(), &mut types);
+        let mut types = Types::new(TEXT.len());
+        classify(TEXT.as_bytes(), types.view_mut());
         for i in 0..TEXT.len() {
             print!("{} ", i % 10)
         }
@@ -462,14 +1948,14 @@ mod test {
         }
         println!();
 
-        for i in 0..TEXT.len() {
-            print!("{:?} ", types[i])
+        for t in types.iter() {
+            print!("{:?} ", t)
         }
         println!();
 
         print!("  ");
         for i in 1..TEXT.len() {
-            if is_lms(i, &types) {
+            if is_lms(i, types.view()) {
                 print!("* ");
             } else {
                 print!("  ");
@@ -488,6 +1974,503 @@ mod test {
         assert_eq!(is_sorted(&output, TEXT.as_bytes()), None)
     }
 
+    #[test]
+    fn test_suffix_array() {
+        assert_eq!(suffix_array::<u32>(b"banana"), vec![5, 3, 1, 0, 4, 2]);
+        assert_eq!(str_suffix_array::<usize>("banana"), vec![5, 3, 1, 0, 4, 2]);
+        assert_eq!(suffix_array::<u8>(b""), vec![]);
+        let text = "mississippi".repeat(10);
+        assert_eq!(
+            is_sorted(&str_suffix_array::<u64>(&text), text.as_bytes()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_types() {
+        let mut types = Types::new(70);
+        types.set(3, Type::S);
+        types.set(69, Type::S);
+        assert_eq!(types.get(3), Type::S);
+        assert_eq!(types.get(4), Type::L);
+        assert_eq!(types.iter().filter(|&t| t == Type::S).count(), 2);
+        // Shrinking clears the types after the new end, growing adds L types
+        types.resize(66);
+        types.resize(70);
+        assert_eq!(types, {
+            let mut expected = Types::new(70);
+            expected.set(3, Type::S);
+            expected
+        });
+    }
+
+    #[test]
+    fn test_sort_packed() {
+        let text = b"mississippi";
+        let mut suffix_array = vec![0u32; text.len()];
+        let mut types = vec![Type::L; text.len()];
+        let mut buckets = vec![0u32; 256];
+        sort(text, &mut suffix_array, &mut types, &mut buckets);
+        let mut packed_suffix_array = vec![0u32; text.len()];
+        let mut packed = Types::new(text.len());
+        sort_packed(text, &mut packed_suffix_array, &mut packed, &mut buckets);
+        assert_eq!(packed_suffix_array, suffix_array);
+        assert!(packed.iter().eq(types.iter().copied()));
+    }
+
+    #[test]
+    fn test_sort_without_types() {
+        let mut state = 3u32;
+        let mut random = (0..5000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                b"ab"[state as usize % 2]
+            })
+            .collect::<Vec<_>>();
+        // Repeats of a long substring need several levels of recursion
+        random.extend_from_within(..2000);
+        let texts = [
+            &b"mississippi"[..],
+            b"a",
+            b"aaaaaaa",
+            b"abcabcabcabc",
+            b"cba",
+            b"And now map the suffix indices from the reduced text to suffix",
+            &random,
+        ];
+        for text in texts.iter() {
+            let mut suffix_array = vec![0u32; text.len()];
+            let mut buckets = vec![0; 256];
+            sort_without_types(text, &mut suffix_array, &mut buckets);
+            assert_eq!(suffix_array, super::suffix_array::<u32>(text));
+            assert!(buckets.iter().all(|&bucket| bucket == 0));
+        }
+        let mut suffix_array = [0u8; 11];
+        sort_without_types(b"mississippi", &mut suffix_array, &mut vec![0; 256]);
+        assert_eq!(suffix_array, [10, 7, 4, 1, 0, 9, 8, 6, 3, 5, 2]);
+    }
+
+    #[test]
+    fn test_u16_suffix_array() {
+        use crate::sorter::{Sais, SuffixSorter};
+
+        let mut state = 7u32;
+        let random = (0..3000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                [0x3042u16, 0x3093, 0xffff, 0][state as usize % 4]
+            })
+            .collect::<Vec<_>>();
+        let utf16 = "mississippi ミシシッピ mississippi"
+            .encode_utf16()
+            .collect();
+        for text in [random, utf16, vec![0xffff; 100], vec![]].iter() {
+            let wide = text.iter().map(|&c| c as u32).collect::<Vec<_>>();
+            assert_eq!(
+                u16_suffix_array::<u32>(text),
+                Sais.suffix_array::<_, u32>(&wide)
+            );
+        }
+    }
+
+    #[test]
+    fn test_try_sort() {
+        let text = b"banana";
+        let mut suffix_array = vec![0u8; text.len()];
+        let mut types = vec![Type::L; text.len()];
+        let mut buckets = vec![0u8; 256];
+        assert_eq!(
+            try_sort(text, &mut suffix_array, &mut types[1..], &mut buckets),
+            Err(SaisError::LengthMismatch {
+                text: 6,
+                suffix_array: 6,
+                types: 5
+            })
+        );
+        assert_eq!(
+            try_sort(b"", &mut [0u8; 0], &mut [], &mut buckets),
+            Err(SaisError::EmptyInput)
+        );
+        let mut small = vec![0u8; b'n' as usize];
+        assert_eq!(
+            try_sort(text, &mut suffix_array, &mut types, &mut small),
+            Err(SaisError::AlphabetTooLarge {
+                buckets: b'n' as usize,
+                required: b'n' as usize + 1
+            })
+        );
+        let mut small = vec![0u8; b'n' as usize + 1];
+        try_sort(text, &mut suffix_array, &mut types, &mut small).unwrap();
+        assert_eq!(suffix_array, [5, 3, 1, 0, 4, 2]);
+
+        assert_eq!(
+            try_suffix_array::<u8>(&[0; 300]),
+            Err(SaisError::TextTooLargeForIndex { len: 300, max: 254 })
+        );
+        assert_eq!(try_suffix_array::<u8>(b""), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_sort_with_lcp() {
+        let mut texts = vec![
+            "a".to_owned(),
+            "banana".to_owned(),
+            "mississippi".to_owned(),
+            "aaaaaaaa".to_owned(),
+            "abababab".to_owned(),
+            "And now map the suffix indices from the reduced text to suffix".to_owned(),
+            "abracadabra abracadabra mississippi banana".repeat(4),
+        ];
+        // pseudo random texts over small alphabets
+        let mut state = 17u64;
+        for len in 1..200 {
+            let sigma = 1 + len % 4;
+            let text = (0..len)
+                .map(|_| {
+                    state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                    (b'a' + (state >> 33) as u8 % sigma as u8) as char
+                })
+                .collect();
+            texts.push(text);
+        }
+        for text in texts {
+            let (suffix_array, lcp) = suffix_array_with_lcp::<u32>(text.as_bytes());
+            assert_eq!(suffix_array, str_suffix_array::<u32>(&text));
+            assert_eq!(lcp, lcp_array(text.as_bytes(), &suffix_array), "{}", text);
+        }
+        assert_eq!(suffix_array_with_lcp::<u32>(b""), (vec![], vec![]));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_buckets_count_parallel() {
+        let text = (0..PARALLEL_BLOCK * 3 + 17)
+            .map(|i| (i * i % 251) as u8)
+            .collect::<Vec<_>>();
+        let mut expected = vec![0u32; 256];
+        for &c in &text {
+            expected[c as usize] += 1;
+        }
+        let mut buckets = vec![0u32; 256];
+        buckets_count_parallel(&text, &mut buckets);
+        assert_eq!(buckets, expected);
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn test_classify_bytes() {
+        let mut state = 0x9e3779b9u32;
+        let mut text = Vec::new();
+        while text.len() < 1000 {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            // Runs of up to 150 equal characters span whole blocks
+            let len = [1, 2, 3, 70, 150][state as usize % 5];
+            text.extend(std::iter::repeat_n(
+                b"\x00a\xffb"[(state >> 8) as usize % 4],
+                len,
+            ));
+        }
+        for len in 1..text.len() {
+            let text = &text[..len];
+            let mut expected = Types::new(len);
+            classify_sub_slice_scalar(text, expected.view_mut());
+            // The types after the prefix belong to a longer text and are not written
+            let mut types = Types::new(len + WORD);
+            for i in 0..types.len() {
+                types.set(i, Type::S);
+            }
+            types.set(len - 1, Type::L);
+            classify_bytes(text, types.prefix_mut(len));
+            assert!(types.iter().take(len).eq(expected.iter()), "{}", len);
+            assert!(types.iter().skip(len).all(|t| t == Type::S));
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_classify_parallel() {
+        // Runs of equal characters ending at, crossing and spanning block boundaries
+        let mut text = Vec::new();
+        for (i, len) in [
+            PARALLEL_BLOCK - 3,
+            5,
+            7,
+            PARALLEL_BLOCK * 2,
+            1,
+            PARALLEL_BLOCK,
+        ]
+        .iter()
+        .enumerate()
+        {
+            text.extend(std::iter::repeat_n([b'b', b'a', b'c'][i % 3], *len));
+        }
+        let mut expected = Types::new(text.len());
+        classify_sub_slice(&text, expected.view_mut());
+        let mut types = Types::new(text.len());
+        for i in 0..types.len() - 1 {
+            types.set(i, Type::S);
+        }
+        classify_parallel(&text, types.view_mut());
+        assert_eq!(types, expected);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_induce_parallel() {
+        let mut state = 1u64;
+        let mut text = (0..PARALLEL_MIN_LEN)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                b'a' + (state % 4) as u8
+            })
+            .collect::<Vec<_>>();
+        text.extend_from_within(..4096);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+        let suffix_array = pool.install(|| suffix_array::<u32>(&text));
+        assert_eq!(is_sorted(&suffix_array, &text), None);
+        let mut sorted = suffix_array.clone();
+        sorted.sort_unstable();
+        assert!(sorted
+            .iter()
+            .enumerate()
+            .all(|(i, &suffix)| suffix as usize == i));
+    }
+
+    #[test]
+    fn test_inverse() {
+        let suffix_array = str_suffix_array::<u32>("mississippi");
+        let inverse = inverse(&suffix_array);
+        assert_eq!(inverse, vec![4, 3, 10, 8, 2, 9, 7, 1, 6, 5, 0]);
+        for (index, &suffix) in suffix_array.iter().enumerate() {
+            assert_eq!(inverse[suffix as usize] as usize, index);
+        }
+
+        for text in ["mississippi", "a", "aaaaaaa", "abracadabra abracadabra"] {
+            let text = text.as_bytes();
+            let mut suffix_array = vec![0u32; text.len()];
+            let mut inverse = vec![0u32; text.len()];
+            let mut types = vec![Type::default(); text.len()];
+            let mut buckets = vec![0u32; 256];
+            sort_with_inverse(
+                text,
+                &mut suffix_array,
+                &mut inverse,
+                &mut types,
+                &mut buckets,
+            );
+            assert_eq!(suffix_array, super::suffix_array::<u32>(text));
+            assert_eq!(inverse, super::inverse(&suffix_array));
+        }
+    }
+
+    #[test]
+    fn test_workspace() {
+        let mut workspace = Workspace::<u32>::new();
+        for text in ["mississippi", "", "banana", "abracadabra", "a"] {
+            let expected = str_suffix_array::<u32>(text);
+            assert_eq!(workspace.build(text.as_bytes()), expected);
+        }
+    }
+
+    #[test]
+    fn test_comparison_sort() {
+        let text = "abracadabra mississippi banana".repeat(3);
+        // Below, at and above the threshold
+        for len in [1, COMPARISON_SORT_LEN - 1, COMPARISON_SORT_LEN, 80].iter() {
+            let text = &text.as_bytes()[..*len];
+            let (suffix_array, lcp) = suffix_array_with_lcp::<u32>(text);
+            assert_eq!(is_sorted(&suffix_array, text), None);
+            assert_eq!(lcp, lcp_array(text, &suffix_array));
+            let mut inverse = vec![0u32; text.len()];
+            let mut sorted = vec![0u32; text.len()];
+            sort_with_inverse(
+                text,
+                &mut sorted,
+                &mut inverse,
+                &mut vec![Type::L; text.len()],
+                &mut vec![0; 256],
+            );
+            assert_eq!(sorted, suffix_array);
+            assert_eq!(inverse, super::inverse(&suffix_array));
+        }
+    }
+
+    #[test]
+    fn test_sort_with_progress() {
+        let text = "abracadabra abracadabra mississippi banana".repeat(4);
+        let mut suffix_array = vec![0u32; text.len()];
+        let mut types = vec![Type::L; text.len()];
+        let mut buckets = vec![0u32; 256];
+        let mut checkpoints = Vec::new();
+        sort_with_progress(
+            text.as_bytes(),
+            &mut suffix_array,
+            &mut types,
+            &mut buckets,
+            |progress| checkpoints.push(progress),
+        );
+        assert_eq!(is_sorted(&suffix_array, text.as_bytes()), None);
+
+        assert!(checkpoints.iter().any(|progress| progress.depth > 0));
+        assert!(checkpoints
+            .windows(2)
+            .all(|w| w[0].fraction <= w[1].fraction));
+        let last = checkpoints.last().unwrap();
+        assert_eq!(
+            (last.phase, last.depth, last.bytes),
+            (Phase::Induce, 0, text.len())
+        );
+        assert_eq!(last.fraction, 1.0);
+    }
+
+    #[test]
+    fn test_progress_while_inducing() {
+        let text = "abracadabra abracadabra mississippi banana".repeat(4);
+        let mut suffix_array = vec![0u32; text.len()];
+        let mut checkpoints = Vec::new();
+        sort_with_progress(
+            text.as_bytes(),
+            &mut suffix_array,
+            &mut vec![Type::L; text.len()],
+            &mut vec![0u32; 256],
+            |progress| checkpoints.push(progress),
+        );
+        let within = |phase| {
+            checkpoints
+                .iter()
+                .filter(|progress| progress.phase == phase && progress.depth == 0)
+                .count()
+        };
+        // Every phase ends with one checkpoint, the others are reported while inducing
+        assert!(within(Phase::SortLms) > 1);
+        assert!(within(Phase::Induce) > 1);
+        assert!(checkpoints
+            .windows(2)
+            .all(|w| w[0].fraction <= w[1].fraction));
+    }
+
+    #[test]
+    fn test_sort_cancellable() {
+        let text = "abracadabra abracadabra mississippi banana".repeat(4);
+        let mut suffix_array = vec![0u32; text.len()];
+        let mut types = vec![Type::L; text.len()];
+        let mut buckets = vec![0u32; 256];
+
+        let checks = Cell::new(0);
+        let cancel_in_recursion = || {
+            checks.set(checks.get() + 1);
+            checks.get() > 3
+        };
+        let result = sort_cancellable(
+            text.as_bytes(),
+            &mut suffix_array,
+            &mut types,
+            &mut buckets,
+            &cancel_in_recursion,
+        );
+        assert_eq!(result, Err(Cancelled));
+        assert_eq!(buckets, vec![0; 256]);
+
+        // the buffers can be reused
+        let token = AtomicBool::new(false);
+        sort_cancellable(
+            text.as_bytes(),
+            &mut suffix_array,
+            &mut types,
+            &mut buckets,
+            &token,
+        )
+        .unwrap();
+        assert_eq!(is_sorted(&suffix_array, text.as_bytes()), None);
+
+        token.store(true, std::sync::atomic::Ordering::Relaxed);
+        let result = sort_cancellable(
+            text.as_bytes(),
+            &mut suffix_array,
+            &mut types,
+            &mut buckets,
+            &token,
+        );
+        assert_eq!(result, Err(Cancelled));
+    }
+
+    #[test]
+    fn test_sort_with_stats() {
+        let text = "abracadabra abracadabra mississippi banana".repeat(4);
+        let mut suffix_array = vec![0u32; text.len()];
+        let mut types = Types::new(text.len());
+        let mut buckets = vec![0u32; 256];
+        let stats = sort_with_stats(text.as_bytes(), &mut suffix_array, &mut types, &mut buckets);
+        assert_eq!(is_sorted(&suffix_array, text.as_bytes()), None);
+        assert!(stats.depth() > 0);
+        assert_eq!(stats.levels[0].len, text.len());
+        // The reduced string has a name for every LMS suffix
+        assert_eq!(stats.levels[1].len, stats.lms_suffixes);
+        assert!(stats.scratch_capacity >= 256 * 4 + text.len() / 8);
+        assert!(stats.total() >= stats.levels[0].duration(Phase::Induce));
+
+        let mut suffix_array = [0u8; 11];
+        let stats = sort_with_stats(
+            b"mississippi",
+            &mut suffix_array,
+            &mut Types::new(11),
+            &mut vec![0; 256],
+        );
+        assert_eq!((stats.depth(), stats.lms_suffixes), (0, 3));
+
+        let stats = sort_with_stats::<u32, u8>(b"", &mut [], &mut Types::new(0), &mut vec![0; 256]);
+        assert_eq!((stats.depth(), stats.lms_suffixes), (0, 0));
+    }
+
+    #[test]
+    fn test_cancel_while_inducing() {
+        let text = "abracadabra abracadabra mississippi banana".repeat(4);
+        let mut suffix_array = vec![0u32; text.len()];
+        let mut types = vec![Type::L; text.len()];
+        let mut buckets = vec![0u32; 256];
+        let checks = Cell::new(0);
+        let count = || {
+            checks.set(checks.get() + 1);
+            false
+        };
+        sort_cancellable(
+            text.as_bytes(),
+            &mut suffix_array,
+            &mut types,
+            &mut buckets,
+            &count,
+        )
+        .unwrap();
+
+        // The last poll follows the induction, the one before is within it
+        let total = checks.replace(0);
+        let cancel_in_induction = || {
+            checks.set(checks.get() + 1);
+            checks.get() == total - 1
+        };
+        let result = sort_cancellable(
+            text.as_bytes(),
+            &mut suffix_array,
+            &mut types,
+            &mut buckets,
+            &cancel_in_induction,
+        );
+        assert_eq!(result, Err(Cancelled));
+        assert_eq!(checks.get(), total - 1);
+        assert_eq!(buckets, vec![0; 256]);
+    }
+
     #[test]
     fn test_sort_file() {
         let mut text = Vec::new();
@@ -498,7 +2481,7 @@ mod test {
         let mut indices = vec![0u32; text.len()];
         let time = SystemTime::now();
         let mut buckets = vec![0u32; 256];
-        let mut types = vec![Type::L; text.len()];
+        let mut types = Types::new(text.len());
         induced_sort(&text, &mut indices, &mut types, &mut buckets);
         println!("{:?}", time.elapsed().unwrap());
 
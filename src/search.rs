@@ -0,0 +1,406 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::marker::PhantomData;
+use std::ops::Range;
+
+use crate::rmq::{Rmq, SparseTable};
+use crate::suffix_index::SuffixIndex;
+
+/// Compares the suffix at `suffix` with `pattern`, suffixes prefixed by `pattern` are equal
+fn prefix_cmp(text: &[u8], suffix: usize, pattern: &[u8]) -> Ordering {
+    let suffix = &text[suffix..];
+    let len = suffix.len().min(pattern.len());
+    match suffix[..len].cmp(&pattern[..len]) {
+        Ordering::Equal if suffix.len() < pattern.len() => Ordering::Less,
+        ordering => ordering,
+    }
+}
+
+/// Returns the interval of `suffix_array` containing all suffixes of `text` prefixed by `pattern`
+pub fn search<I: SuffixIndex>(text: &[u8], suffix_array: &[I], pattern: &[u8]) -> Range<usize> {
+    search_from(text, suffix_array, pattern, 0)
+}
+
+/// Like [`search`] for patterns known to start at or after `start`
+fn search_from<I: SuffixIndex>(
+    text: &[u8],
+    suffix_array: &[I],
+    pattern: &[u8],
+    start: usize,
+) -> Range<usize> {
+    let cmp = |suffix: &I| prefix_cmp(text, suffix.as_index(), pattern);
+    let start =
+        start + suffix_array[start..].partition_point(|suffix| cmp(suffix) == Ordering::Less);
+    let end =
+        start + suffix_array[start..].partition_point(|suffix| cmp(suffix) == Ordering::Equal);
+    start..end
+}
+
+/// Returns the length of the longest prefix of `pattern` occurring in `text` and the interval of
+/// `suffix_array` containing all suffixes prefixed by it
+pub fn longest_prefix_match<I: SuffixIndex>(
+    text: &[u8],
+    suffix_array: &[I],
+    pattern: &[u8],
+) -> (usize, Range<usize>) {
+    let mut interval = 0..suffix_array.len();
+    for (depth, &c) in pattern.iter().enumerate() {
+        // All suffixes in the interval share the first `depth` characters of the pattern
+        let suffixes = &suffix_array[interval.clone()];
+        let next = |suffix: &I| text.get(suffix.as_index() + depth).copied();
+        let start = suffixes.partition_point(|suffix| next(suffix) < Some(c));
+        let end = suffixes.partition_point(|suffix| next(suffix) <= Some(c));
+        if start == end {
+            return (depth, interval);
+        }
+        interval = interval.start + start..interval.start + end;
+    }
+    (pattern.len(), interval)
+}
+
+/// Searches patterns in ascending order, every search starts at the interval of the previous one
+fn search_sorted<I: SuffixIndex, P: AsRef<[u8]>>(
+    text: &[u8],
+    suffix_array: &[I],
+    patterns: &[P],
+    order: &[usize],
+) -> Vec<Range<usize>> {
+    let mut start = 0;
+    order
+        .iter()
+        .map(|&pattern| {
+            let interval = search_from(text, suffix_array, patterns[pattern].as_ref(), start);
+            start = interval.start;
+            interval
+        })
+        .collect()
+}
+
+/// Patterns searched in order by one thread
+#[cfg(feature = "parallel")]
+const PATTERNS_PER_TASK: usize = 1024;
+
+/// Searches all `patterns`, returns their intervals in the order of `patterns`.
+///
+/// Patterns are searched in sorted order, so consecutive searches touch nearby parts of the suffix
+/// array and narrow each other. With the `parallel` feature blocks of sorted patterns are searched
+/// on all threads.
+pub fn search_many<I, P>(text: &[u8], suffix_array: &[I], patterns: &[P]) -> Vec<Range<usize>>
+where
+    I: SuffixIndex + Sync,
+    P: AsRef<[u8]> + Sync,
+{
+    let mut order = (0..patterns.len()).collect::<Vec<_>>();
+    order.sort_unstable_by_key(|&pattern| patterns[pattern].as_ref());
+
+    #[cfg(feature = "parallel")]
+    let sorted = {
+        use rayon::prelude::*;
+        order
+            .par_chunks(PATTERNS_PER_TASK)
+            .flat_map_iter(|order| search_sorted(text, suffix_array, patterns, order))
+            .collect::<Vec<_>>()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let sorted = search_sorted(text, suffix_array, patterns, &order);
+
+    let mut intervals = vec![0..0; patterns.len()];
+    for (pattern, interval) in order.into_iter().zip(sorted) {
+        intervals[pattern] = interval;
+    }
+    intervals
+}
+
+/// The number of occurrences of `pattern` in `text`
+pub fn count<I: SuffixIndex>(text: &[u8], suffix_array: &[I], pattern: &[u8]) -> usize {
+    search(text, suffix_array, pattern).len()
+}
+
+/// Returns the positions of all occurrences of `pattern` in `text` in suffix array order
+pub fn positions<'a, I: SuffixIndex>(
+    text: &[u8],
+    suffix_array: &'a [I],
+    pattern: &[u8],
+) -> impl Iterator<Item = usize> + 'a {
+    suffix_array[search(text, suffix_array, pattern)]
+        .iter()
+        .map(|suffix| suffix.as_index())
+}
+
+/// Range minimum queries over a suffix array to report occurrences in text order
+#[derive(Debug, Clone)]
+pub struct TextOrderIndex<I, R = SparseTable<I>> {
+    suffixes: R,
+    _index: PhantomData<I>,
+}
+
+impl<I: SuffixIndex> TextOrderIndex<I> {
+    pub fn new(suffix_array: &[I]) -> Self {
+        Self::with_backend(suffix_array)
+    }
+}
+
+impl<I: SuffixIndex, R: Rmq<I>> TextOrderIndex<I, R> {
+    /// Builds the index with `R` as the range minimum backend over the suffix array
+    pub fn with_backend(suffix_array: &[I]) -> Self {
+        Self {
+            suffixes: R::new(suffix_array.to_vec()),
+            _index: PhantomData,
+        }
+    }
+
+    /// Returns the positions of the suffixes in `interval` in ascending order.
+    ///
+    /// Every position is found when it is requested in `O(log k)` for `k` positions returned.
+    pub fn positions(&self, interval: Range<usize>) -> TextOrderPositions<'_, I, R> {
+        let mut positions = TextOrderPositions {
+            suffixes: &self.suffixes,
+            ranges: BinaryHeap::new(),
+        };
+        positions.push(interval);
+        positions
+    }
+}
+
+/// Iterator over positions in ascending order, see [`TextOrderIndex::positions`]
+#[derive(Debug, Clone)]
+pub struct TextOrderPositions<'a, I, R = SparseTable<I>> {
+    suffixes: &'a R,
+    /// The smallest suffix of ranges of the suffix array with its index and the range
+    ranges: BinaryHeap<Reverse<(I, usize, usize, usize)>>,
+}
+
+impl<I: SuffixIndex, R: Rmq<I>> TextOrderPositions<'_, I, R> {
+    fn push(&mut self, range: Range<usize>) {
+        if !range.is_empty() {
+            let index = self.suffixes.min_index(range.clone());
+            let suffix = self.suffixes.values()[index];
+            self.ranges
+                .push(Reverse((suffix, index, range.start, range.end)));
+        }
+    }
+}
+
+impl<I: SuffixIndex, R: Rmq<I>> Iterator for TextOrderPositions<'_, I, R> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((suffix, index, start, end)) = self.ranges.pop()?;
+        self.push(start..index);
+        self.push(index + 1..end);
+        Some(suffix.as_index())
+    }
+}
+
+/// The lcp of the suffixes at the bounds of every step of a binary search with the middle suffix
+/// for searches in `O(m + log n)` (Manber and Myers, 1993)
+#[derive(Debug, Clone)]
+pub struct LcpLr<I> {
+    /// The lcp with the left bound of the search interval of every middle
+    llcp: Vec<I>,
+    /// The lcp with the right bound of the search interval of every middle
+    rlcp: Vec<I>,
+}
+
+impl<I: SuffixIndex> LcpLr<I> {
+    /// Computes the arrays from the lcp array of the suffix array
+    pub fn new(lcp: &[I]) -> Self {
+        let mut lcp_lr = Self {
+            llcp: vec![I::from_index(0); lcp.len()],
+            rlcp: vec![I::from_index(0); lcp.len()],
+        };
+        if lcp.len() > 1 {
+            lcp_lr.fill(lcp, 0, lcp.len() - 1);
+        }
+        lcp_lr
+    }
+
+    /// Fills the interval `left..=right` and returns the lcp of its bounds
+    fn fill(&mut self, lcp: &[I], left: usize, right: usize) -> I {
+        if right - left == 1 {
+            return lcp[right];
+        }
+        let middle = (left + right) / 2;
+        self.llcp[middle] = self.fill(lcp, left, middle);
+        self.rlcp[middle] = self.fill(lcp, middle, right);
+        self.llcp[middle].min(self.rlcp[middle])
+    }
+
+    /// The first index whose suffix is on the right of `pattern`, suffixes prefixed by `pattern`
+    /// are on the right if `prefixed_right`
+    fn bound(
+        &self,
+        text: &[u8],
+        suffix_array: &[I],
+        pattern: &[u8],
+        prefixed_right: bool,
+    ) -> usize {
+        // The lcp with the pattern and whether the suffix is on the left, starting after `known`
+        let side = |index: usize, known: usize| {
+            let suffix = &text[suffix_array[index].as_index()..];
+            let lcp = known
+                + suffix[known.min(suffix.len())..]
+                    .iter()
+                    .zip(&pattern[known..])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+            let left = match (suffix.get(lcp), pattern.get(lcp)) {
+                (_, None) => !prefixed_right,
+                (None, Some(_)) => true,
+                (Some(a), Some(b)) => a < b,
+            };
+            (lcp, left)
+        };
+
+        let n = suffix_array.len();
+        let (mut l, first_left) = side(0, 0);
+        if !first_left {
+            return 0;
+        }
+        let (mut r, last_left) = side(n - 1, 0);
+        if last_left {
+            return n;
+        }
+        let (mut left, mut right) = (0, n - 1);
+        while right - left > 1 {
+            let middle = (left + right) / 2;
+            let (llcp, rlcp) = (self.llcp[middle].as_index(), self.rlcp[middle].as_index());
+            // The middle suffix continues like a bound for longer than the bound matches
+            let step = if l >= r {
+                match llcp.cmp(&l) {
+                    Ordering::Greater => Some((true, l)),
+                    Ordering::Less => Some((false, llcp)),
+                    Ordering::Equal => None,
+                }
+            } else {
+                match rlcp.cmp(&r) {
+                    Ordering::Greater => Some((false, r)),
+                    Ordering::Less => Some((true, rlcp)),
+                    Ordering::Equal => None,
+                }
+            };
+            let (is_left, lcp) = step.unwrap_or_else(|| {
+                let (lcp, is_left) = side(middle, l.max(r));
+                (is_left, lcp)
+            });
+            if is_left {
+                left = middle;
+                l = lcp;
+            } else {
+                right = middle;
+                r = lcp;
+            }
+        }
+        right
+    }
+
+    /// Like [`search`], comparing every character of `pattern` at most once
+    pub fn search(&self, text: &[u8], suffix_array: &[I], pattern: &[u8]) -> Range<usize> {
+        assert_eq!(suffix_array.len(), self.llcp.len());
+        if suffix_array.is_empty() {
+            return 0..0;
+        }
+        let start = self.bound(text, suffix_array, pattern, true);
+        let end = self.bound(text, suffix_array, pattern, false);
+        start..end
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lcp::lcp_array;
+    use crate::rmq::BlockRmq;
+    use crate::sais::suffix_array;
+
+    #[test]
+    fn test_search() {
+        let text = b"abracadabra";
+        let suffix_array = suffix_array::<u32>(text);
+        assert_eq!(search(text, &suffix_array, b"abra"), 1..3);
+        assert_eq!(count(text, &suffix_array, b"a"), 5);
+        assert_eq!(count(text, &suffix_array, b""), text.len());
+        assert_eq!(count(text, &suffix_array, b"abracadabrab"), 0);
+        assert_eq!(search(text, &suffix_array, b"b").len(), 2);
+        assert_eq!(search(text, &suffix_array, b"z"), 11..11);
+        let mut found = positions(text, &suffix_array, b"bra").collect::<Vec<_>>();
+        found.sort_unstable();
+        assert_eq!(found, vec![1, 8]);
+    }
+
+    #[test]
+    fn test_search_many() {
+        let text = b"abracadabra mississippi";
+        let suffix_array = suffix_array::<u32>(text);
+        let patterns = [
+            "ssi",
+            "a",
+            "",
+            "abra",
+            "zz",
+            "a",
+            "i",
+            "abracadabra mississippi!",
+        ];
+        let expected = patterns
+            .iter()
+            .map(|pattern| search(text, &suffix_array, pattern.as_bytes()))
+            .collect::<Vec<_>>();
+        assert_eq!(search_many(text, &suffix_array, &patterns), expected);
+        assert!(search_many::<u32, &[u8]>(text, &suffix_array, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_longest_prefix_match() {
+        let text = b"abracadabra";
+        let suffix_array = suffix_array::<u32>(text);
+        let longest = |pattern: &[u8]| longest_prefix_match(text, &suffix_array, pattern);
+        assert_eq!(longest(b"abrax"), (4, search(text, &suffix_array, b"abra")));
+        assert_eq!(longest(b"cad"), (3, search(text, &suffix_array, b"cad")));
+        assert_eq!(longest(b"ra!"), (2, search(text, &suffix_array, b"ra")));
+        assert_eq!(longest(b"xyz"), (0, 0..text.len()));
+        assert_eq!(longest(b""), (0, 0..text.len()));
+        assert_eq!(longest(b"abracadabrab"), (11, 2..3));
+    }
+
+    #[test]
+    fn test_text_order() {
+        let text = b"abracadabra mississippi abracadabra";
+        let suffix_array = suffix_array::<u32>(text);
+        let index = TextOrderIndex::new(&suffix_array);
+        let block = TextOrderIndex::<u32, BlockRmq<u32>>::with_backend(&suffix_array);
+        for pattern in [&b"a"[..], b"abra", b"ssi", b"", b"x"] {
+            let mut expected = positions(text, &suffix_array, pattern).collect::<Vec<_>>();
+            expected.sort_unstable();
+            let interval = search(text, &suffix_array, pattern);
+            assert!(block
+                .positions(interval.clone())
+                .eq(expected.iter().copied()));
+            assert!(index.positions(interval).eq(expected));
+        }
+        let interval = search(text, &suffix_array, b"a");
+        assert_eq!(
+            index.positions(interval).take(3).collect::<Vec<_>>(),
+            vec![0, 3, 5]
+        );
+    }
+
+    #[test]
+    fn test_lcp_lr() {
+        let text = b"abracadabra mississippi abracadabra banana".repeat(3);
+        let suffix_array = suffix_array::<u32>(&text);
+        let lcp_lr = LcpLr::new(&lcp_array(&text, &suffix_array));
+        for start in 0..text.len() {
+            for len in [0, 1, 3, 10, 60] {
+                let pattern = &text[start..text.len().min(start + len)];
+                let expected = search(&text, &suffix_array, pattern);
+                assert_eq!(lcp_lr.search(&text, &suffix_array, pattern), expected);
+            }
+        }
+        for pattern in [&b"abrab"[..], b"z", b"\0", b"bananaz", b"aa"] {
+            let expected = search(&text, &suffix_array, pattern);
+            assert_eq!(lcp_lr.search(&text, &suffix_array, pattern), expected);
+        }
+        assert_eq!(LcpLr::<u32>::new(&[]).search(b"", &[], b"a"), 0..0);
+    }
+}
@@ -0,0 +1,37 @@
+use crate::sais::{induced_sort, Types};
+use crate::suffix_index::{AsIndex, SuffixIndex};
+
+/// A suffix array construction algorithm, the backends can be exchanged to pick the fastest one
+/// for a workload
+pub trait SuffixSorter {
+    /// Sorts the suffixes of `text` into `suffix_array`, which must have the same length
+    fn sort<C: AsIndex + Ord, I: SuffixIndex>(&self, text: &[C], suffix_array: &mut [I]);
+
+    fn suffix_array<C: AsIndex + Ord, I: SuffixIndex>(&self, text: &[C]) -> Vec<I> {
+        let mut suffix_array = vec![I::from_index(0); text.len()];
+        self.sort(text, &mut suffix_array);
+        suffix_array
+    }
+}
+
+/// Induced sorting (Nong et al., 2009), see [`sort`](crate::sort)
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Sais;
+
+impl SuffixSorter for Sais {
+    fn sort<C: AsIndex + Ord, I: SuffixIndex>(&self, text: &[C], suffix_array: &mut [I]) {
+        assert_eq!(text.len(), suffix_array.len());
+        assert!(text.len() < I::MAX, "text is too large for the index type");
+        if text.is_empty() {
+            return;
+        }
+        let sigma = text.iter().map(AsIndex::as_index).max().unwrap() + 1;
+        let mut buckets = vec![I::from_index(0); sigma];
+        induced_sort(
+            text,
+            suffix_array,
+            &mut Types::new(text.len()),
+            &mut buckets,
+        );
+    }
+}
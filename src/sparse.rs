@@ -0,0 +1,153 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+use crate::radix_sort;
+use crate::suffix_index::SuffixIndex;
+
+/// Start positions of all (w,k)-minimizers of `text` in ascending order.
+///
+/// The minimizer of a window of `w` consecutive k-mers is its lexicographically smallest k-mer,
+/// ties are broken by position.
+pub fn minimizers(text: &[u8], w: usize, k: usize) -> Vec<usize> {
+    assert!(w > 0 && k > 0);
+    let mut positions = Vec::new();
+    if text.len() < k {
+        return positions;
+    }
+    let kmers = text.len() - k + 1;
+    let kmer = |i: usize| &text[i..i + k];
+
+    // Candidates with increasing k-mers, the front is the minimizer of the current window
+    let mut window = VecDeque::new();
+    for i in 0..kmers {
+        while window.back().is_some_and(|&j| kmer(j) > kmer(i)) {
+            window.pop_back();
+        }
+        window.push_back(i);
+        if window[0] + w <= i {
+            window.pop_front();
+        }
+        if i + 1 >= w.min(kmers) && positions.last() != Some(&window[0]) {
+            positions.push(window[0]);
+        }
+    }
+    positions
+}
+
+/// Sparse suffix array containing only suffixes starting at (w,k)-minimizers.
+///
+/// Every occurrence of a pattern of at least `w + k - 1` characters contains a minimizer at the
+/// same offset as the first minimizer of the pattern, matches are found from there and verified.
+#[derive(Debug, Clone)]
+pub struct MinimizerIndex<'a, I> {
+    text: &'a [u8],
+    w: usize,
+    k: usize,
+    suffixes: Vec<I>,
+}
+
+impl<'a, I: SuffixIndex> MinimizerIndex<'a, I> {
+    pub fn new(text: &'a [u8], w: usize, k: usize) -> Self {
+        assert!(text.len() < I::MAX);
+        let mut suffixes = minimizers(text, w, k)
+            .into_iter()
+            .map(I::from_index)
+            .collect::<Vec<_>>();
+        // Safety: minimizer positions are unique and in range of `text`
+        unsafe {
+            radix_sort::sort(&mut suffixes, text);
+        }
+        Self {
+            text,
+            w,
+            k,
+            suffixes,
+        }
+    }
+
+    pub fn text(&self) -> &'a [u8] {
+        self.text
+    }
+
+    /// The sampled suffixes in lexicographic order
+    pub fn suffixes(&self) -> &[I] {
+        &self.suffixes
+    }
+
+    /// The length of the shortest pattern that can be searched
+    pub fn min_pattern_len(&self) -> usize {
+        self.w + self.k - 1
+    }
+
+    /// Returns the positions of all occurrences of `pattern` in ascending order.
+    ///
+    /// Panics if the pattern is shorter than [`Self::min_pattern_len`].
+    pub fn locate(&self, pattern: &[u8]) -> Vec<usize> {
+        assert!(
+            pattern.len() >= self.min_pattern_len(),
+            "pattern is too short for the minimizer index"
+        );
+        let anchor = minimizers(&pattern[..self.min_pattern_len()], self.w, self.k)[0];
+        let suffix = &pattern[anchor..];
+
+        let prefix_cmp = |position: &I| {
+            let text = &self.text[position.as_index()..];
+            let len = text.len().min(suffix.len());
+            match text[..len].cmp(&suffix[..len]) {
+                Ordering::Equal if text.len() < suffix.len() => Ordering::Less,
+                ordering => ordering,
+            }
+        };
+        let start = self
+            .suffixes
+            .partition_point(|position| prefix_cmp(position) == Ordering::Less);
+        let end = start
+            + self.suffixes[start..]
+                .partition_point(|position| prefix_cmp(position) == Ordering::Equal);
+
+        let mut positions = self.suffixes[start..end]
+            .iter()
+            .map(|position| position.as_index())
+            .filter(|&position| {
+                position >= anchor && self.text[position - anchor..position] == pattern[..anchor]
+            })
+            .map(|position| position - anchor)
+            .collect::<Vec<_>>();
+        positions.sort_unstable();
+        positions
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_minimizers() {
+        assert_eq!(minimizers(b"dcba", 2, 1), vec![1, 2, 3]);
+        assert_eq!(minimizers(b"abcd", 2, 1), vec![0, 1, 2]);
+        assert_eq!(minimizers(b"abcd", 10, 2), vec![0]);
+        assert_eq!(minimizers(b"a", 2, 2), vec![]);
+    }
+
+    #[test]
+    fn test_locate() {
+        let text = b"GATTACAGATTACATTAGACCAGATTACAGAT";
+        for (w, k) in [(1, 1), (2, 3), (4, 2), (5, 4)] {
+            let index = MinimizerIndex::<u32>::new(text, w, k);
+            assert!(index.suffixes().len() <= text.len());
+            for len in index.min_pattern_len()..10 {
+                for pattern in text.windows(len) {
+                    let expected = text
+                        .windows(len)
+                        .enumerate()
+                        .filter(|(_, window)| window == &pattern)
+                        .map(|(position, _)| position)
+                        .collect::<Vec<_>>();
+                    assert_eq!(index.locate(pattern), expected);
+                }
+            }
+            assert!(index.locate(b"GATTACAGATTACAGA").is_empty());
+        }
+    }
+}
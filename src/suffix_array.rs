@@ -0,0 +1,134 @@
+use std::borrow::Cow;
+use std::ops::Range;
+use std::slice;
+
+use crate::lcp::lcp_array;
+use crate::sais::suffix_array;
+use crate::search::{longest_prefix_match, search};
+use crate::suffix_index::SuffixIndex;
+
+/// A suffix array together with its text, which is borrowed or owned
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SuffixArray<'a, I> {
+    text: Cow<'a, [u8]>,
+    indices: Vec<I>,
+}
+
+impl<'a, I: SuffixIndex> SuffixArray<'a, I> {
+    /// Computes the suffix array of a borrowed text
+    pub fn new(text: &'a [u8]) -> Self {
+        Self {
+            indices: suffix_array(text),
+            text: Cow::Borrowed(text),
+        }
+    }
+
+    /// Computes the suffix array of an owned text
+    pub fn from_text(text: Vec<u8>) -> SuffixArray<'static, I> {
+        SuffixArray {
+            indices: suffix_array(&text),
+            text: Cow::Owned(text),
+        }
+    }
+
+    /// Wraps a suffix array computed elsewhere, `indices` must be the suffix array of `text`
+    pub fn from_parts(text: Cow<'a, [u8]>, indices: Vec<I>) -> Self {
+        assert_eq!(text.len(), indices.len());
+        Self { text, indices }
+    }
+
+    pub fn into_parts(self) -> (Cow<'a, [u8]>, Vec<I>) {
+        (self.text, self.indices)
+    }
+
+    pub fn text(&self) -> &[u8] {
+        &self.text
+    }
+
+    pub fn as_slice(&self) -> &[I] {
+        &self.indices
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, I> {
+        self.indices.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// The suffix at `index` in suffix array order
+    pub fn suffix(&self, index: usize) -> &[u8] {
+        &self.text[self.indices[index].as_index()..]
+    }
+
+    /// Returns the interval of the suffix array containing all suffixes prefixed by `pattern`
+    pub fn find(&self, pattern: &[u8]) -> Range<usize> {
+        search(&self.text, &self.indices, pattern)
+    }
+
+    /// The length of the longest prefix of `pattern` occurring in the text and the interval of
+    /// the suffixes prefixed by it
+    pub fn longest_prefix_match(&self, pattern: &[u8]) -> (usize, Range<usize>) {
+        longest_prefix_match(&self.text, &self.indices, pattern)
+    }
+
+    /// The longest common prefix of every suffix with its predecessor, the first entry is 0
+    pub fn lcp_array(&self) -> Vec<I> {
+        lcp_array(&self.text, &self.indices)
+    }
+
+    /// The number of occurrences of `pattern`
+    pub fn count(&self, pattern: &[u8]) -> usize {
+        self.find(pattern).len()
+    }
+
+    /// Returns the positions of all occurrences of `pattern` in suffix array order
+    pub fn locate(&self, pattern: &[u8]) -> impl Iterator<Item = usize> + '_ {
+        self.indices[self.find(pattern)]
+            .iter()
+            .map(|suffix| suffix.as_index())
+    }
+}
+
+impl<'s, I> IntoIterator for &'s SuffixArray<'_, I> {
+    type Item = &'s I;
+    type IntoIter = slice::Iter<'s, I>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.indices.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        let suffix_array = SuffixArray::<u32>::new(b"mississippi");
+        assert_eq!(suffix_array.len(), 11);
+        assert_eq!(suffix_array.find(b"ssi"), 9..11);
+        assert_eq!(suffix_array.count(b"i"), 4);
+        assert_eq!(suffix_array.count(b"x"), 0);
+        assert_eq!(suffix_array.count(b"mississippis"), 0);
+        assert_eq!(suffix_array.count(b""), 11);
+        let mut positions = suffix_array.locate(b"ss").collect::<Vec<_>>();
+        positions.sort_unstable();
+        assert_eq!(positions, vec![2, 5]);
+        assert_eq!(suffix_array.suffix(0), b"i");
+        assert_eq!(suffix_array.longest_prefix_match(b"ssp"), (2, 9..11));
+        assert_eq!(
+            suffix_array.lcp_array(),
+            vec![0, 1, 1, 4, 0, 0, 1, 0, 2, 1, 3]
+        );
+
+        let owned = SuffixArray::<u32>::from_text(b"mississippi".to_vec());
+        assert_eq!(owned.as_slice(), suffix_array.as_slice());
+        assert!(owned.iter().eq(&suffix_array));
+    }
+}
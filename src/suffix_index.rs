@@ -1,10 +1,18 @@
 use std::fmt::Debug;
 use std::ops::{Add, AddAssign, Sub, SubAssign};
 
-pub trait AsIndex {
+pub trait AsIndex: Sync {
     const MAX: usize;
 
     fn as_index(&self) -> usize;
+
+    /// `text` as bytes if `Self` is `u8`, for specialized byte loops
+    fn as_bytes(_text: &[Self]) -> Option<&[u8]>
+    where
+        Self: Sized,
+    {
+        None
+    }
 }
 
 pub trait SuffixIndex:
@@ -17,6 +25,7 @@ pub trait SuffixIndex:
     + Copy
     + PartialEq
     + Debug
+    + Send
     + AsIndex
 {
     fn from_index(value: usize) -> Self;
@@ -45,6 +54,10 @@ impl AsIndex for u8 {
     fn as_index(&self) -> usize {
         *self as usize
     }
+
+    fn as_bytes(text: &[Self]) -> Option<&[u8]> {
+        Some(text)
+    }
 }
 
 impl SuffixIndex for u8 {
@@ -55,6 +68,23 @@ impl SuffixIndex for u8 {
     }
 }
 
+impl AsIndex for u16 {
+    const MAX: usize = u16::MAX as usize;
+
+    #[inline(always)]
+    fn as_index(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl SuffixIndex for u16 {
+    #[inline(always)]
+    fn from_index(value: usize) -> Self {
+        debug_assert!(value <= Self::MAX as usize);
+        value as Self
+    }
+}
+
 impl AsIndex for u32 {
     const MAX: usize = u32::MAX as usize;
 
@@ -0,0 +1,263 @@
+use std::ops::Range;
+
+use crate::lcp::lcp_array;
+use crate::sais::suffix_array;
+use crate::suffix_index::SuffixIndex;
+
+/// A suffix tree built in linear time from the suffix array and lcp array.
+///
+/// Nodes are identified by indices, the leaf of the suffix at index `i` of the suffix array is
+/// node `i`, the internal nodes follow in postorder with the root last. There is no end marker, a
+/// suffix that is a prefix of another suffix is a leaf below the internal node of the same depth.
+#[derive(Debug, Clone)]
+pub struct SuffixTree<'a, I> {
+    text: &'a [u8],
+    suffix_array: Vec<I>,
+    nodes: Vec<Node<I>>,
+    /// The children of all nodes, consecutive for every node in lexicographic order
+    children: Vec<I>,
+}
+
+#[derive(Debug, Clone)]
+struct Node<I> {
+    depth: I,
+    start: I,
+    end: I,
+    parent: I,
+    /// The end of the children in `SuffixTree::children`, they start at the end of the previous node
+    children_end: I,
+}
+
+impl<'a, I: SuffixIndex> SuffixTree<'a, I> {
+    pub fn new(text: &'a [u8]) -> Self {
+        let suffix_array = suffix_array(text);
+        let lcp = lcp_array(text, &suffix_array);
+        Self::from_parts(text, suffix_array, &lcp)
+    }
+
+    /// Builds the tree, `suffix_array` and `lcp` must belong to `text`
+    pub fn from_parts(text: &'a [u8], suffix_array: Vec<I>, lcp: &[I]) -> Self {
+        assert_eq!(text.len(), suffix_array.len());
+        assert_eq!(text.len(), lcp.len());
+        let n = text.len();
+        let node = |depth: usize, range: Range<usize>| Node {
+            depth: I::from_index(depth),
+            start: I::from_index(range.start),
+            end: I::from_index(range.end),
+            parent: I::from_index(0),
+            children_end: I::from_index(0),
+        };
+        let mut nodes = suffix_array
+            .iter()
+            .enumerate()
+            .map(|(i, suffix)| node(n - suffix.as_index(), i..i + 1))
+            .collect::<Vec<_>>();
+        let mut children = Vec::with_capacity(2 * n);
+
+        // The depth, start and children of the open internal nodes, increasing in depth
+        let mut stack: Vec<(usize, usize, Vec<usize>)> = vec![(0, 0, Vec::new())];
+        let mut close =
+            |nodes: &mut Vec<Node<I>>, (depth, start, open): (usize, usize, Vec<usize>), end| {
+                let id = nodes.len();
+                let mut closed = node(depth, start..end);
+                for &child in &open {
+                    nodes[child].parent = I::from_index(id);
+                    children.push(I::from_index(child));
+                }
+                closed.children_end = I::from_index(children.len());
+                nodes.push(closed);
+                id
+            };
+        for i in 0..n {
+            let mut last = i;
+            // All nodes but the root are closed after the last suffix
+            let depth = lcp.get(i + 1).map_or(0, |lcp| lcp.as_index());
+            while depth < stack.last().unwrap().0 {
+                let mut top = stack.pop().unwrap();
+                top.2.push(last);
+                last = close(&mut nodes, top, i + 1);
+            }
+            let top = stack.last_mut().unwrap();
+            if depth > top.0 {
+                let start = nodes[last].start.as_index();
+                stack.push((depth, start, vec![last]));
+            } else {
+                top.2.push(last);
+            }
+        }
+        let root = stack.pop().unwrap();
+        let root = close(&mut nodes, root, n);
+        nodes[root].parent = I::from_index(root);
+
+        Self {
+            text,
+            suffix_array,
+            nodes,
+            children,
+        }
+    }
+
+    pub fn text(&self) -> &'a [u8] {
+        self.text
+    }
+
+    pub fn suffix_array(&self) -> &[I] {
+        &self.suffix_array
+    }
+
+    /// The number of nodes including leaves
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// All nodes in postorder
+    pub fn nodes(&self) -> impl Iterator<Item = usize> {
+        // Leaves are numbered before all internal nodes, the indices are not in postorder
+        let mut stack = vec![(self.root(), false)];
+        let mut order = Vec::with_capacity(self.len());
+        while let Some((node, expanded)) = stack.pop() {
+            if expanded {
+                order.push(node);
+            } else {
+                stack.push((node, true));
+                stack.extend(self.children(node).rev().map(|child| (child, false)));
+            }
+        }
+        order.into_iter()
+    }
+
+    pub fn root(&self) -> usize {
+        self.nodes.len() - 1
+    }
+
+    pub fn is_leaf(&self, node: usize) -> bool {
+        node < self.suffix_array.len()
+    }
+
+    /// The parent of `node`, `None` for the root
+    pub fn parent(&self, node: usize) -> Option<usize> {
+        (node != self.root()).then(|| self.nodes[node].parent.as_index())
+    }
+
+    /// The length of the path label of `node`
+    pub fn depth(&self, node: usize) -> usize {
+        self.nodes[node].depth.as_index()
+    }
+
+    /// The interval of the suffix array of the leaves below `node`
+    pub fn interval(&self, node: usize) -> Range<usize> {
+        let node = &self.nodes[node];
+        node.start.as_index()..node.end.as_index()
+    }
+
+    /// The concatenated edge labels from the root to `node`
+    pub fn label(&self, node: usize) -> &'a [u8] {
+        if self.depth(node) == 0 {
+            return &[];
+        }
+        let suffix = self.suffix_array[self.nodes[node].start.as_index()].as_index();
+        &self.text[suffix..suffix + self.depth(node)]
+    }
+
+    /// The start of the suffix of a leaf
+    pub fn suffix(&self, leaf: usize) -> usize {
+        assert!(self.is_leaf(leaf), "only leaves have a suffix");
+        self.suffix_array[leaf].as_index()
+    }
+
+    /// The children of `node` in lexicographic order
+    pub fn children(&self, node: usize) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        self.children[self.children_range(node)]
+            .iter()
+            .map(|child| child.as_index())
+    }
+
+    /// The range of the children of `node` in `children`
+    fn children_range(&self, node: usize) -> Range<usize> {
+        if self.is_leaf(node) {
+            return 0..0;
+        }
+        let start = if node == self.suffix_array.len() {
+            0
+        } else {
+            self.nodes[node - 1].children_end.as_index()
+        };
+        start..self.nodes[node].children_end.as_index()
+    }
+
+    /// The child of `node` whose edge label starts with `c`
+    pub fn child(&self, node: usize, c: u8) -> Option<usize> {
+        let depth = self.depth(node);
+        let children = &self.children[self.children_range(node)];
+        children
+            .binary_search_by_key(&Some(c), |child| {
+                let start = self.nodes[child.as_index()].start.as_index();
+                let suffix = self.suffix_array[start].as_index();
+                self.text.get(suffix + depth).copied()
+            })
+            .ok()
+            .map(|index| children[index].as_index())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_suffix_tree() {
+        let text = b"acaaacatat";
+        let tree = SuffixTree::<u32>::new(text);
+        let root = tree.root();
+        assert_eq!(tree.len(), 17);
+        assert_eq!(tree.depth(root), 0);
+        assert_eq!(tree.interval(root), 0..10);
+        assert_eq!(tree.parent(root), None);
+        let labels = |node| {
+            tree.children(node)
+                .map(|child| tree.label(child))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(labels(root), [&b"a"[..], b"ca", b"t"]);
+        let a = tree.child(root, b'a').unwrap();
+        assert_eq!(labels(a), [&b"aa"[..], b"aca", b"at"]);
+        assert_eq!(tree.child(a, b'g'), None);
+        let at = tree.child(a, b't').unwrap();
+        assert_eq!(tree.parent(at), Some(a));
+        assert_eq!(tree.interval(at), 4..6);
+        let leaf = tree.child(at, b'a').unwrap();
+        assert!(tree.is_leaf(leaf));
+        assert_eq!(tree.label(leaf), b"atat");
+        assert_eq!(tree.suffix(leaf), 6);
+        assert_eq!(tree.children(leaf).count(), 0);
+
+        let nodes = tree.nodes().collect::<Vec<_>>();
+        assert_eq!(nodes.len(), tree.len());
+        assert_eq!(nodes.last(), Some(&root));
+        for (index, &node) in nodes.iter().enumerate() {
+            if let Some(parent) = tree.parent(node) {
+                assert!(nodes[index..].contains(&parent));
+                assert!(tree.depth(parent) <= tree.depth(node));
+            }
+        }
+    }
+
+    #[test]
+    fn test_prefix_suffixes() {
+        let tree = SuffixTree::<u32>::new(b"aaa");
+        // The root has a single child, leaves of suffixes that are prefixes hang below their node
+        let a = tree.child(tree.root(), b'a').unwrap();
+        assert_eq!(tree.depth(a), 1);
+        assert_eq!(
+            tree.children(a)
+                .map(|child| tree.depth(child))
+                .collect::<Vec<_>>(),
+            [1, 2]
+        );
+        assert_eq!(SuffixTree::<u32>::new(b"").len(), 1);
+    }
+}
@@ -0,0 +1,270 @@
+use crate::fm::Rank;
+
+/// Bits with constant time rank and logarithmic time select
+#[derive(Debug, Clone, Default)]
+pub struct BitVector {
+    words: Vec<u64>,
+    /// The number of ones before every block of [`BLOCK_WORDS`] words
+    ranks: Vec<usize>,
+    len: usize,
+}
+
+/// The words counted by a rank of a [`BitVector`], it takes an eighth bit per bit
+const BLOCK_WORDS: usize = 8;
+
+impl BitVector {
+    pub fn new<B: IntoIterator<Item = bool>>(bits: B) -> Self {
+        let mut vector = Self::default();
+        for bit in bits {
+            if vector.len.is_multiple_of(64) {
+                vector.words.push(0);
+            }
+            *vector.words.last_mut().unwrap() |= (bit as u64) << (vector.len % 64);
+            vector.len += 1;
+        }
+        let mut ones = 0;
+        for block in vector.words.chunks(BLOCK_WORDS) {
+            vector.ranks.push(ones);
+            ones += block
+                .iter()
+                .map(|word| word.count_ones() as usize)
+                .sum::<usize>();
+        }
+        vector.ranks.push(ones);
+        vector
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        assert!(index < self.len);
+        self.words[index / 64] >> (index % 64) & 1 == 1
+    }
+
+    /// The number of ones in the first `index` bits
+    pub fn rank1(&self, index: usize) -> usize {
+        assert!(index <= self.len);
+        let (word, bit) = (index / 64, index % 64);
+        let block = word / BLOCK_WORDS;
+        let ones = self.ranks[block]
+            + self.words[block * BLOCK_WORDS..word]
+                .iter()
+                .map(|word| word.count_ones() as usize)
+                .sum::<usize>();
+        match bit {
+            0 => ones,
+            bit => ones + (self.words[word] << (64 - bit)).count_ones() as usize,
+        }
+    }
+
+    /// The number of zeros in the first `index` bits
+    pub fn rank0(&self, index: usize) -> usize {
+        index - self.rank1(index)
+    }
+
+    /// The index of the one with rank `rank`
+    pub fn select1(&self, rank: usize) -> Option<usize> {
+        self.select(rank, |index| self.rank1(index), |word| word)
+    }
+
+    /// The index of the zero with rank `rank`
+    pub fn select0(&self, rank: usize) -> Option<usize> {
+        self.select(rank, |index| self.rank0(index), |word| !word)
+    }
+
+    fn select(
+        &self,
+        rank: usize,
+        rank_at: impl Fn(usize) -> usize,
+        ones: impl Fn(u64) -> u64,
+    ) -> Option<usize> {
+        if rank >= rank_at(self.len) {
+            return None;
+        }
+        // The last word with at most `rank` matching bits before it
+        let (mut low, mut high) = (0, self.words.len());
+        while high - low > 1 {
+            let middle = (low + high) / 2;
+            if rank_at(middle * 64) <= rank {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        let word = low;
+        let mut bits = ones(self.words[word]);
+        for _ in 0..rank - rank_at(word * 64) {
+            bits &= bits - 1;
+        }
+        Some(word * 64 + bits.trailing_zeros() as usize)
+    }
+}
+
+/// Wavelet matrix over bytes, supports access, rank and select in 8 bit vector operations.
+///
+/// Takes about nine bits per character independent of the alphabet, a bit and an eighth bit of
+/// rank directory per level.
+#[derive(Debug, Clone)]
+pub struct WaveletMatrix {
+    /// The bits of every level from the most significant bit, each level is stably partitioned
+    /// by the bit of the previous level
+    levels: Vec<BitVector>,
+    zeros: Vec<usize>,
+    len: usize,
+}
+
+const LEVELS: usize = u8::BITS as usize;
+
+impl WaveletMatrix {
+    pub fn new(mut bytes: Vec<u8>) -> Self {
+        let len = bytes.len();
+        let mut levels = Vec::with_capacity(LEVELS);
+        let mut zeros = Vec::with_capacity(LEVELS);
+        for level in 0..LEVELS {
+            let bit = |c: u8| c >> (LEVELS - 1 - level) & 1 == 1;
+            let vector = BitVector::new(bytes.iter().map(|&c| bit(c)));
+            zeros.push(vector.rank0(len));
+            levels.push(vector);
+            let (mut low, high): (Vec<_>, Vec<_>) = bytes.iter().partition(|&&c| !bit(c));
+            low.extend(high);
+            bytes = low;
+        }
+        Self { levels, zeros, len }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, mut index: usize) -> u8 {
+        let mut c = 0;
+        for (level, vector) in self.levels.iter().enumerate() {
+            let bit = vector.get(index);
+            c = c << 1 | bit as u8;
+            index = match bit {
+                true => self.zeros[level] + vector.rank1(index),
+                false => vector.rank0(index),
+            };
+        }
+        c
+    }
+
+    /// Follows the bits of `c` from `start` and `end`, the ranges of the last level are returned
+    fn descend(&self, c: u8, mut start: usize, mut end: usize) -> (usize, usize) {
+        for (level, vector) in self.levels.iter().enumerate() {
+            if c >> (LEVELS - 1 - level) & 1 == 1 {
+                start = self.zeros[level] + vector.rank1(start);
+                end = self.zeros[level] + vector.rank1(end);
+            } else {
+                start = vector.rank0(start);
+                end = vector.rank0(end);
+            }
+        }
+        (start, end)
+    }
+
+    /// The number of occurrences of `c` in the first `index` bytes
+    pub fn rank(&self, c: u8, index: usize) -> usize {
+        assert!(index <= self.len);
+        let (start, end) = self.descend(c, 0, index);
+        end - start
+    }
+
+    /// The index of the occurrence of `c` with rank `rank`
+    pub fn select(&self, c: u8, rank: usize) -> Option<usize> {
+        let (start, end) = self.descend(c, 0, self.len);
+        if rank >= end - start {
+            return None;
+        }
+        let mut index = start + rank;
+        for (level, vector) in self.levels.iter().enumerate().rev() {
+            index = if c >> (LEVELS - 1 - level) & 1 == 1 {
+                vector.select1(index - self.zeros[level])?
+            } else {
+                vector.select0(index)?
+            };
+        }
+        Some(index)
+    }
+}
+
+impl Rank for WaveletMatrix {
+    fn new(bytes: Vec<u8>) -> Self {
+        WaveletMatrix::new(bytes)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, index: usize) -> u8 {
+        WaveletMatrix::get(self, index)
+    }
+
+    fn rank(&self, c: u8, index: usize) -> usize {
+        WaveletMatrix::rank(self, c, index)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bit_vector() {
+        // Within the first rank block and across blocks
+        for len in [200, 512, 1300] {
+            let bits = (0..len)
+                .map(|i| i % 3 == 0 || i > 150 && i < 700)
+                .collect::<Vec<_>>();
+            let vector = BitVector::new(bits.iter().copied());
+            assert_eq!(vector.len(), len);
+            for i in 0..=len {
+                let ones = bits[..i].iter().filter(|&&bit| bit).count();
+                assert_eq!(vector.rank1(i), ones);
+                assert_eq!(vector.rank0(i), i - ones);
+            }
+            let ones = (0..len).filter(|&i| bits[i]).collect::<Vec<_>>();
+            let zeros = (0..len).filter(|&i| !bits[i]).collect::<Vec<_>>();
+            for (rank, &index) in ones.iter().enumerate() {
+                assert_eq!(vector.select1(rank), Some(index));
+            }
+            for (rank, &index) in zeros.iter().enumerate() {
+                assert_eq!(vector.select0(rank), Some(index));
+            }
+            assert_eq!(vector.select1(ones.len()), None);
+            assert_eq!(vector.select0(zeros.len()), None);
+        }
+    }
+
+    #[test]
+    fn test_wavelet_matrix() {
+        let text = b"the quick brown fox jumps over the lazy dog \xff\x00".repeat(3);
+        let matrix = WaveletMatrix::new(text.clone());
+        assert_eq!(matrix.len(), text.len());
+        for (i, &c) in text.iter().enumerate() {
+            assert_eq!(matrix.get(i), c);
+        }
+        for c in [b' ', b'o', b'z', b'x', 0, 0xff, b'A'] {
+            for i in 0..=text.len() {
+                let count = text[..i].iter().filter(|&&x| x == c).count();
+                assert_eq!(matrix.rank(c, i), count);
+            }
+            let positions = (0..text.len()).filter(|&i| text[i] == c);
+            for (rank, index) in positions.enumerate() {
+                assert_eq!(matrix.select(c, rank), Some(index));
+            }
+            assert_eq!(matrix.select(c, matrix.rank(c, text.len())), None);
+        }
+    }
+}